@@ -0,0 +1,55 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+// #[rst_opt_parse::main]
+// fn main( opts : MyOptions ) -> R { ... }
+//
+// becomes the fn main() -> R parsing the args into MyOptions (FromOptParse) by rst_opt_parse::run_main()
+#[proc_macro_attribute]
+pub fn main( _attr : TokenStream, item : TokenStream ) -> TokenStream {
+    let tokens : Vec<TokenTree> = item.into_iter().collect();
+
+    let fn_pos = tokens.iter().position( |token| matches!( token, TokenTree::Ident( ident ) if ident.to_string() == "fn" ) );
+    let params_pos = tokens.iter().position( |token| matches!( token, TokenTree::Group( group ) if group.delimiter() == Delimiter::Parenthesis ) );
+    let body_pos = tokens.iter().rposition( |token| matches!( token, TokenTree::Group( group ) if group.delimiter() == Delimiter::Brace ) );
+    let ( fn_pos, params_pos, body_pos ) = match ( fn_pos, params_pos, body_pos ) {
+        ( Some( fn_pos ), Some( params_pos ), Some( body_pos ) ) if fn_pos + 2 == params_pos && params_pos < body_pos => ( fn_pos, params_pos, body_pos ),
+        _ => return compile_error( "#[main] expects fn main( opts : T ) { ... }" ),
+    };
+
+    let stream = |tokens : &[TokenTree]| tokens.iter().cloned().collect::<TokenStream>();
+    let parse = |code : &str| code.parse::<TokenStream>().unwrap();
+    let attrs = stream( &tokens[ ..fn_pos ] );
+    let params = stream( &tokens[ params_pos..=params_pos ] );
+    let ret = stream( &tokens[ params_pos + 1..body_pos ] );
+    let body = stream( &tokens[ body_pos..=body_pos ] );
+
+    let mut inner = parse( "fn __opt_parse_main" );
+    inner.extend( [ params, ret.clone(), body ] );
+    inner.extend( parse( "::rst_opt_parse::run_main( __opt_parse_main )" ) );
+
+    let mut result = attrs;
+    result.extend( parse( "fn main()" ) );
+    result.extend( ret );
+    result.extend( [ TokenTree::Group( Group::new( Delimiter::Brace, inner ) ) ] );
+    result
+}
+
+fn compile_error( message : &str ) -> TokenStream {
+    format!( "compile_error!( {:?} );", message ).parse().unwrap()
+}
@@ -0,0 +1,127 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// --color=auto|always|never, for this crate's output and the application's
+// the help and the errors printed by this crate are colored by paint_help() and paint_error()
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::ValueEnum;
+
+const HEADING : &str = "\x1b[1;4m";      // bold and underline
+const ERROR : &str = "\x1b[1;31m";       // bold red
+const RESET : &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice
+{
+    #[default]
+    Auto,       // color on the terminal unless NO_COLOR or TERM=dumb
+    Always,
+    Never,
+}
+
+impl ValueEnum for ColorChoice
+{
+    fn possible_values() -> &'static [&'static str] {
+        &[ "auto", "always", "never" ]
+    }
+
+    fn from_value( value : &str ) -> Option<Self> {
+        match value {
+            "auto" => Some( ColorChoice::Auto ),
+            "always" => Some( ColorChoice::Always ),
+            "never" => Some( ColorChoice::Never ),
+            _ => None
+        }
+    }
+
+    fn as_value( &self ) -> &'static str {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+    }
+}
+
+impl ColorChoice
+{
+    // true: color the output to the stream which is_terminal tells
+    pub fn should_color( &self, is_terminal : bool ) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os( "NO_COLOR" ).is_none_or( |value| value.is_empty() ) && std::env::var_os( "TERM" ).is_none_or( |term| term != "dumb" )
+            }
+        }
+    }
+
+    pub fn should_color_stdout( &self ) -> bool {
+        self.should_color( crate::tty::is_stdout_terminal() )
+    }
+
+    pub fn should_color_stderr( &self ) -> bool {
+        self.should_color( crate::tty::is_stderr_terminal() )
+    }
+}
+
+
+// the headings of the help e.g. "Options:" in bold and underline
+pub fn paint_help( help : &str ) -> String {
+    let mut result = String::with_capacity( help.len() );
+    for line in help.split_inclusive( '\n' ) {
+        let text = line.trim_end_matches( '\n' );
+        if text.ends_with( ':' ) && !text.starts_with( char::is_whitespace ) {
+            result.push_str( HEADING );
+            result.push_str( text );
+            result.push_str( RESET );
+            result.push_str( &line[ text.len().. ] );
+        } else {
+            result.push_str( line );
+        }
+    }
+    result
+}
+
+// the error in bold red
+pub fn paint_error( error : &str ) -> String {
+    format!( "{}{}{}", ERROR, error, RESET )
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!( ColorChoice::from_value( "never" ), Some( ColorChoice::Never ) );
+        assert_eq!( ColorChoice::parse_value( "sometimes" ).is_err(), true );
+        assert_eq!( ColorChoice::Always.should_color( false ), true );
+        assert_eq!( ColorChoice::Never.should_color( true ), false );
+        assert_eq!( ColorChoice::Auto.should_color( false ), false );
+    }
+
+    #[test]
+    fn test_color_paint() {
+        assert_eq!( paint_help( "mytool\nOptions:\n -v\t --verbose\t : Enable verbose mode\n" ), "mytool\n\x1b[1;4mOptions:\x1b[0m\n -v\t --verbose\t : Enable verbose mode\n" );
+        assert_eq!( paint_error( "missing value for samplingRate" ), "\x1b[1;31mmissing value for samplingRate\x1b[0m" );
+    }
+}
@@ -0,0 +1,141 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the subcommands e.g. "tool convert -s 44100 in.wav" runs the "convert" Command with its own options
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Matches;
+use crate::OptParseError;
+use crate::OptParseItem;
+
+pub trait Command
+{
+    fn name( &self ) -> &str;
+
+    // this is displayed in the help
+    fn description( &self ) -> &str {
+        ""
+    }
+
+    // --version of this e.g. the version of the wrapped component. None for the version of the tool, see set_version()
+    fn version( &self ) -> Option<&str> {
+        None
+    }
+
+    // the heading of the help listing this e.g. "Conversion". empty for "Commands:"
+    fn category( &self ) -> &str {
+        ""
+    }
+
+    fn options( &self ) -> Vec<OptParseItem>;
+
+    // the exit code
+    fn run( &self, matches : &Matches ) -> Result<i32, OptParseError>;
+}
+
+
+// the registered commands in the order of add_command()
+#[derive(Clone, Default)]
+pub(crate) struct CommandRegistry
+{
+    pub(crate) commands : Vec<Arc<dyn Command + Send + Sync>>,
+    pub(crate) categories : Vec<String>,   // the categories listed first in this order, see set_command_categories()
+}
+
+impl CommandRegistry
+{
+    pub(crate) fn find( &self, name : &str ) -> Option<&Arc<dyn Command + Send + Sync>> {
+        self.commands.iter().find( |command| command.name() == name )
+    }
+
+    pub(crate) fn is_empty( &self ) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub(crate) fn names( &self ) -> Vec<String> {
+        self.commands.iter().map( |command| String::from( command.name() ) ).collect()
+    }
+
+    // ( the category, its commands ) of the uncategorized first, then the ordered categories, then the others in the order of the first appearance
+    pub(crate) fn categorized( &self ) -> Vec<( &str, Vec<&Arc<dyn Command + Send + Sync>> )> {
+        let mut result : Vec<( &str, Vec<&Arc<dyn Command + Send + Sync>> )> = Vec::new();
+        for command in &self.commands {
+            match result.iter_mut().find( |( category, _ )| *category == command.category() ) {
+                Some( ( _, commands ) ) => commands.push( command ),
+                None => result.push( ( command.category(), vec![ command ] ) ),
+            }
+        }
+        let order = |category : &str| match category.is_empty() {
+            true => 0,
+            false => self.categories.iter().position( |ordered| ordered == category ).map_or( self.categories.len() + 1, |position| position + 1 ),
+        };
+        result.sort_by_key( |( category, _ )| order( category ) );
+        result
+    }
+
+    // the closest name for the typo e.g. "convert" for "covnert". None if nothing is close enough
+    pub(crate) fn suggest( &self, name : &str ) -> Option<String> {
+        let max_distance = core::cmp::max( 1, name.chars().count() / 3 );
+        self.commands.iter()
+            .map( |command| ( edit_distance( name, command.name() ), command.name() ) )
+            .filter( |( distance, _ )| *distance <= max_distance )
+            .min_by_key( |( distance, _ )| *distance )
+            .map( |( _, name )| String::from( name ) )
+    }
+}
+
+// the edits to turn a into b where the swap of the adjacent characters is also one edit
+fn edit_distance( a : &str, b : &str ) -> usize {
+    let a : Vec<char> = a.chars().collect();
+    let b : Vec<char> = b.chars().collect();
+    let mut distances = vec![ vec![ 0; b.len() + 1 ]; a.len() + 1 ];
+    for ( i, row ) in distances.iter_mut().enumerate() {
+        row[ 0 ] = i;
+    }
+    for ( j, distance ) in distances[ 0 ].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[ i - 1 ] == b[ j - 1 ] { 0 } else { 1 };
+            let mut distance = ( distances[ i - 1 ][ j ] + 1 ).min( distances[ i ][ j - 1 ] + 1 ).min( distances[ i - 1 ][ j - 1 ] + cost );
+            if i > 1 && j > 1 && a[ i - 1 ] == b[ j - 2 ] && a[ i - 2 ] == b[ j - 1 ] {
+                distance = distance.min( distances[ i - 2 ][ j - 2 ] + 1 );
+            }
+            distances[ i ][ j ] = distance;
+        }
+    }
+    distances[ a.len() ][ b.len() ]
+}
+
+impl core::fmt::Debug for CommandRegistry
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.debug_list().entries( self.commands.iter().map( |command| command.name() ) ).finish()
+    }
+}
+
+// the commands can't be compared then the same names are the same
+impl PartialEq for CommandRegistry
+{
+    fn eq( &self, other : &Self ) -> bool {
+        self.names() == other.names()
+    }
+}
@@ -0,0 +1,282 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the shell completion scripts generated from the options and the commands, and their installation to the per-shell location
+//   bash : $BASH_COMPLETION_USER_DIR/completions or $XDG_DATA_HOME/bash-completion/completions (~/.local/share/bash-completion/completions)
+//   zsh  : ~/.zfunc/_tool which needs fpath=(~/.zfunc $fpath) before compinit in ~/.zshrc
+//   fish : $XDG_CONFIG_HOME/fish/completions (~/.config/fish/completions)
+
+use std::fmt::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::IOptParse;
+use crate::OptParse;
+use crate::OptParseItem;
+use crate::ValueEnum;
+use crate::Visibility;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shell
+{
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ValueEnum for Shell
+{
+    fn possible_values() -> &'static [&'static str] {
+        &[ "bash", "zsh", "fish" ]
+    }
+
+    fn from_value( value : &str ) -> Option<Self> {
+        match value {
+            "bash" => Some( Shell::Bash ),
+            "zsh" => Some( Shell::Zsh ),
+            "fish" => Some( Shell::Fish ),
+            _ => None
+        }
+    }
+
+    fn as_value( &self ) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+// the file name of the running executable e.g. "mytool" for /usr/bin/mytool
+pub fn program_name() -> String {
+    std::env::args_os().next().as_deref().map( Path::new ).and_then( Path::file_stem ).map( |name| name.to_string_lossy().into_owned() ).unwrap_or_default()
+}
+
+// the completion script of the program for the shell
+pub fn generate( opt_parse : &OptParse, program : &str, shell : Shell ) -> String {
+    match shell {
+        Shell::Bash => generate_bash( opt_parse, program ),
+        Shell::Zsh => generate_zsh( opt_parse, program ),
+        Shell::Fish => generate_fish( opt_parse, program ),
+    }
+}
+
+// the internal options and the env only entries aren't completed
+fn is_completed( option : &OptParseItem ) -> bool {
+    !option.is_env_only() && option.get_visibility() != Visibility::Internal
+}
+
+// [ ".pcm", ".wav" ] -> "pcm|wav" for the glob patterns
+fn extension_names( extensions : &[String] ) -> String {
+    extensions.iter().map( |extension| extension.trim_start_matches( '.' ) ).collect::<Vec<_>>().join( "|" )
+}
+
+fn function_name( program : &str ) -> String {
+    program.chars().map( |c| if c.is_ascii_alphanumeric() { c } else { '_' } ).collect()
+}
+
+fn generate_bash( opt_parse : &OptParse, program : &str ) -> String {
+    let mut result = String::new();
+    let function = function_name( program );
+    let _ = writeln!( result, "_{}() {{", function );
+    let _ = writeln!( result, "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"" );
+    let _ = writeln!( result, "    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"" );
+    let _ = writeln!( result, "    case \"$prev\" in" );
+    for option in opt_parse.get_options().iter().filter( |option| option.is_arg_required() && is_completed( option ) ) {
+        let flags : Vec<&str> = [ option.get_option(), option.get_full_option() ].into_iter().filter( |flag| !flag.is_empty() ).collect();
+        if !option.get_extensions().is_empty() {
+            let _ = writeln!( result, "        {}) COMPREPLY=( $(compgen -d -- \"$cur\") $(compgen -f -X '!*.@({})' -- \"$cur\") ); return ;;", flags.join( "|" ), extension_names( option.get_extensions() ) );
+        } else if option.get_choices().is_empty() {
+            let _ = writeln!( result, "        {}) COMPREPLY=( $(compgen -f -- \"$cur\") ); return ;;", flags.join( "|" ) );
+        } else {
+            let _ = writeln!( result, "        {}) COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") ); return ;;", flags.join( "|" ), option.get_choices().join( " " ) );
+        }
+    }
+    let _ = writeln!( result, "    esac" );
+    let mut words : Vec<String> = opt_parse.commands.names();
+    for option in opt_parse.get_options().iter().filter( |option| is_completed( option ) ) {
+        words.extend( [ option.get_option(), option.get_full_option() ].into_iter().filter( |flag| !flag.is_empty() ).map( String::from ) );
+    }
+    let _ = writeln!( result, "    COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )", words.join( " " ) );
+    let _ = writeln!( result, "}}" );
+    let _ = writeln!( result, "complete -o default -F _{} {}", function, program );
+    result
+}
+
+// the text in the zsh's single quotes and [ ]
+fn zsh_escape( text : &str ) -> String {
+    text.replace( '\'', "'\\''" ).replace( '[', "\\[" ).replace( ']', "\\]" ).replace( ':', "\\:" )
+}
+
+fn generate_zsh( opt_parse : &OptParse, program : &str ) -> String {
+    let mut result = String::new();
+    let _ = writeln!( result, "#compdef {}", program );
+    let _ = writeln!( result );
+    let _ = writeln!( result, "_arguments \\" );
+    for option in opt_parse.get_options().iter().filter( |option| is_completed( option ) ) {
+        let value = if !option.is_arg_required() {
+            String::new()
+        } else if !option.get_extensions().is_empty() {
+            format!( ":value:_files -g \"*.({})\"", extension_names( option.get_extensions() ) )
+        } else if option.get_choices().is_empty() {
+            ":value:_files".to_string()
+        } else {
+            format!( ":value:({})", option.get_choices().join( " " ) )
+        };
+        for flag in [ option.get_option(), option.get_full_option() ].into_iter().filter( |flag| !flag.is_empty() ) {
+            let _ = writeln!( result, "  '{}[{}]{}' \\", flag, zsh_escape( option.get_description() ), value );
+        }
+    }
+    let extensions : Vec<String> = opt_parse.positionals.iter().flat_map( |( _, _, extensions )| extensions.iter().cloned() ).collect();
+    if !opt_parse.commands.is_empty() {
+        let commands : Vec<String> = opt_parse.commands.commands.iter().map( |command| format!( "{}\\:{}", command.name(), zsh_escape( command.description() ).replace( ' ', "\\ " ) ) ).collect();
+        let _ = writeln!( result, "  '1:command:(({}))' \\", commands.join( " " ) );
+    }
+    if extensions.is_empty() {
+        let _ = writeln!( result, "  '*:file:_files'" );
+    } else {
+        let _ = writeln!( result, "  '*:file:_files -g \"*.({})\"'", extension_names( &extensions ) );
+    }
+    result
+}
+
+// the text in the fish's single quotes
+fn fish_quote( text : &str ) -> String {
+    format!( "'{}'", text.replace( '\\', "\\\\" ).replace( '\'', "\\'" ) )
+}
+
+fn generate_fish( opt_parse : &OptParse, program : &str ) -> String {
+    let mut result = String::new();
+    for command in &opt_parse.commands.commands {
+        let _ = writeln!( result, "complete -c {} -n __fish_use_subcommand -f -a {} -d {}", program, command.name(), fish_quote( command.description() ) );
+    }
+    for option in opt_parse.get_options().iter().filter( |option| is_completed( option ) ) {
+        let _ = write!( result, "complete -c {}", program );
+        if let Some( short ) = option.get_option().strip_prefix( '-' ).filter( |short| short.chars().count() == 1 ) {
+            let _ = write!( result, " -s {}", short );
+        }
+        if let Some( long ) = option.get_full_option().strip_prefix( "--" ) {
+            let _ = write!( result, " -l {}", long );
+        }
+        if option.is_arg_required() {
+            if !option.get_extensions().is_empty() {
+                let _ = write!( result, " -r -a {}", fish_quote( &format!( "(__fish_complete_suffix {})", option.get_extensions().join( " " ) ) ) );
+            } else if option.get_choices().is_empty() {
+                let _ = write!( result, " -r" );
+            } else {
+                let _ = write!( result, " -x -a {}", fish_quote( &option.get_choices().join( " " ) ) );
+            }
+        }
+        let _ = writeln!( result, " -d {}", fish_quote( option.get_description() ) );
+    }
+    result
+}
+
+// the conventional per-user location of the script. None without HOME
+pub fn install_path( shell : Shell, program : &str ) -> Option<PathBuf> {
+    let var = |name : &str| std::env::var_os( name ).filter( |value| !value.is_empty() ).map( PathBuf::from );
+    let home = var( "HOME" );
+    match shell {
+        Shell::Bash => {
+            let dir = match var( "BASH_COMPLETION_USER_DIR" ) {
+                Some( dir ) => dir,
+                None => var( "XDG_DATA_HOME" ).or_else( || home.map( |home| home.join( ".local/share" ) ) )?.join( "bash-completion" ),
+            };
+            Some( dir.join( "completions" ).join( program ) )
+        },
+        Shell::Zsh => Some( home?.join( ".zfunc" ).join( format!( "_{}", program ) ) ),
+        Shell::Fish => Some( var( "XDG_CONFIG_HOME" ).or_else( || home.map( |home| home.join( ".config" ) ) )?.join( "fish/completions" ).join( format!( "{}.fish", program ) ) ),
+    }
+}
+
+// write the script to the path creating the directories
+pub fn install_to( opt_parse : &OptParse, program : &str, shell : Shell, path : &Path ) -> std::io::Result<()> {
+    if let Some( dir ) = path.parent() {
+        std::fs::create_dir_all( dir )?;
+    }
+    std::fs::write( path, generate( opt_parse, program, shell ) )
+}
+
+// write the script to install_path() and return the path
+pub fn install( opt_parse : &OptParse, program : &str, shell : Shell ) -> std::io::Result<PathBuf> {
+    let path = install_path( shell, program ).ok_or_else( || std::io::Error::new( std::io::ErrorKind::NotFound, "HOME isn't set" ) )?;
+    install_to( opt_parse, program, shell, &path )?;
+    Ok( path )
+}
+
+// what the user does to enable the script installed at the path
+pub fn instructions( shell : Shell, path : &Path ) -> String {
+    match shell {
+        Shell::Bash => format!( "installed {}. it's loaded by bash-completion in the new shells, or run: source {}\n", path.display(), path.display() ),
+        Shell::Zsh => format!( "installed {}. add these to ~/.zshrc before the other compinit if not yet:\n  fpath=({} $fpath)\n  autoload -Uz compinit && compinit\n", path.display(), path.parent().unwrap_or( path ).display() ),
+        Shell::Fish => format!( "installed {}. it's loaded in the new shells\n", path.display() ),
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::vec_init_then_push, clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::OptParseItem;
+
+    fn opt_parse() -> OptParse {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ) );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        OptParse::new( Vec::new(), options, "rst_opt_parse_test" )
+    }
+
+    #[test]
+    fn test_completions_generate() {
+        let opt_parse = opt_parse();
+        let bash = generate( &opt_parse, "my-tool", Shell::Bash );
+        assert_eq!( bash.contains( "        -e|--encoding) COMPREPLY=( $(compgen -W \"PCM16 PCM24\" -- \"$cur\") ); return ;;\n" ), true );
+        assert_eq!( bash.contains( "compgen -W \"-s --samplingRate -e --encoding -v --verbose\"" ), true );
+        assert_eq!( bash.ends_with( "complete -o default -F _my_tool my-tool\n" ), true );
+
+        let zsh = generate( &opt_parse, "my-tool", Shell::Zsh );
+        assert_eq!( zsh.starts_with( "#compdef my-tool\n" ), true );
+        assert_eq!( zsh.contains( "  '--encoding[Set encoding]:value:(PCM16 PCM24)' \\\n" ), true );
+
+        let fish = generate( &opt_parse, "my-tool", Shell::Fish );
+        assert_eq!( fish, "complete -c my-tool -s s -l samplingRate -r -d 'Set sampling rate'\ncomplete -c my-tool -s e -l encoding -x -a 'PCM16 PCM24' -d 'Set encoding'\ncomplete -c my-tool -s v -l verbose -d 'Enable verbose mode'\n" );
+    }
+
+    #[test]
+    fn test_completions_extensions() {
+        let mut opt_parse = opt_parse();
+        opt_parse.add_options( vec![ OptParseItem::new( "-o", "--output", true, "", "Output file").with_extensions( &[ ".pcm", ".wav" ] ) ] );
+        opt_parse.add_positional_with_extensions( "INPUT", "Input file", &[ ".wav" ] );
+        assert_eq!( generate( &opt_parse, "my-tool", Shell::Bash ).contains( "        -o|--output) COMPREPLY=( $(compgen -d -- \"$cur\") $(compgen -f -X '!*.@(pcm|wav)' -- \"$cur\") ); return ;;\n" ), true );
+        let zsh = generate( &opt_parse, "my-tool", Shell::Zsh );
+        assert_eq!( zsh.contains( "  '--output[Output file]:value:_files -g \"*.(pcm|wav)\"' \\\n" ), true );
+        assert_eq!( zsh.ends_with( "  '*:file:_files -g \"*.(wav)\"'\n" ), true );
+        assert_eq!( generate( &opt_parse, "my-tool", Shell::Fish ).contains( "complete -c my-tool -s o -l output -r -a '(__fish_complete_suffix .pcm .wav)' -d 'Output file'\n" ), true );
+    }
+
+    #[test]
+    fn test_completions_install_to() {
+        let dir = std::env::temp_dir().join( format!( "rst_opt_parse_completions_{}", std::process::id() ) );
+        let path = dir.join( "fish/completions/my-tool.fish" );
+        install_to( &opt_parse(), "my-tool", Shell::Fish, &path ).unwrap();
+        assert_eq!( std::fs::read_to_string( &path ).unwrap(), generate( &opt_parse(), "my-tool", Shell::Fish ) );
+        assert_eq!( instructions( Shell::Zsh, Path::new( "/home/me/.zfunc/_my-tool" ) ).contains( "fpath=(/home/me/.zfunc $fpath)" ), true );
+        std::fs::remove_dir_all( &dir ).unwrap();
+    }
+}
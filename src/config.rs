@@ -0,0 +1,58 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the values from the config file e.g.
+//   # ~/.mytool.conf
+//   samplingRate = 44100
+//   apiToken = "secret"   # the quotes are optional
+// the keys are the names or the flags of the options, see add_config_from_str()
+
+use crate::strip_comment;
+use crate::strip_quotes;
+use crate::OptParseError;
+
+// the ( key, value ) in the order of the lines
+pub fn read_str( text : &str ) -> Result<Vec<( String, String )>, OptParseError> {
+    let mut result = Vec::new();
+    for ( i, line ) in text.lines().enumerate() {
+        let line = strip_comment( line ).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let ( key, value ) = line.split_once( '=' ).ok_or_else( || OptParseError::InvalidSpec { line : i + 1, message : "expected key = value".to_string() } )?;
+        result.push( ( key.trim().to_string(), strip_quotes( value.trim() ).to_string() ) );
+    }
+    Ok( result )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_read_str() {
+        assert_eq!( read_str( "# audio\nsamplingRate = 44100\n\n--encoding=\"PCM 24\"  # quoted\n" ), Ok( vec![
+            ( "samplingRate".to_string(), "44100".to_string() ),
+            ( "--encoding".to_string(), "PCM 24".to_string() ),
+        ] ) );
+        assert_eq!( read_str( "apiToken = \"ab#c\"  # quoted #\nname = 'x#y'\n" ), Ok( vec![
+            ( "apiToken".to_string(), "ab#c".to_string() ),
+            ( "name".to_string(), "x#y".to_string() ),
+        ] ) );
+        assert_eq!( read_str( "samplingRate 44100" ), Err( OptParseError::InvalidSpec { line : 1, message : "expected key = value".to_string() } ) );
+    }
+}
@@ -0,0 +1,165 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the date and time value e.g. --since=2022-10-01 or --schedule-at=2022-10-01T12:30:00+09:00
+// the accepted forms are YYYY-MM-DD or YYYY/MM/DD optionally followed by T or a space and HH:MM[:SS[.fraction]], then Z or +HH:MM, -HH:MM
+
+use crate::MessageKey;
+use crate::ValueMessage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DateTime
+{
+    pub year : i32,
+    pub month : u32,
+    pub day : u32,
+    pub hour : u32,
+    pub minute : u32,
+    pub second : u32,
+    pub offset_minutes : Option<i32>,   // the UTC offset. None for the local time
+}
+
+impl DateTime
+{
+    // the seconds since 1970-01-01T00:00:00Z. the local time is taken as UTC
+    pub fn to_unix_seconds( &self ) -> i64 {
+        // days from the civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let year = if self.month <= 2 { self.year - 1 } else { self.year } as i64;
+        let era = year.div_euclid( 400 );
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = ( 153 * ( if month > 2 { month - 3 } else { month + 9 } ) + 2 ) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+        days * 86400 + ( self.hour * 3600 + self.minute * 60 + self.second ) as i64 - self.offset_minutes.unwrap_or( 0 ) as i64 * 60
+    }
+}
+
+fn days_in_month( year : i32, month : u32 ) -> u32 {
+    match month {
+        2 if ( year % 4 == 0 && year % 100 != 0 ) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn number<T : core::str::FromStr>( value : &str, digits : usize ) -> Result<T, ValueMessage> {
+    if value.len() != digits || !value.bytes().all( |c| c.is_ascii_digit() ) {
+        return Err( ValueMessage::new( MessageKey::DateTimeFormat, &[] ) );
+    }
+    value.parse::<T>().map_err( |_| ValueMessage::new( MessageKey::DateTimeFormat, &[] ) )
+}
+
+impl core::str::FromStr for DateTime
+{
+    type Err = ValueMessage;
+
+    fn from_str( value : &str ) -> Result<Self, Self::Err> {
+        let ( date, time ) = match value.find( ['T', 't', ' '] ) {
+            Some( pos ) => ( &value[ ..pos ], Some( &value[ pos + 1.. ] ) ),
+            None => ( value, None ),
+        };
+        let separator = if date.contains( '/' ) { '/' } else { '-' };
+        let mut parts = date.splitn( 3, separator );
+        let mut result = DateTime {
+            year : number( parts.next().unwrap_or( "" ), 4 )?,
+            month : number( parts.next().unwrap_or( "" ), 2 )?,
+            day : number( parts.next().unwrap_or( "" ), 2 )?,
+            ..Default::default()
+        };
+        if !( 1..=12 ).contains( &result.month ) || result.day == 0 || result.day > days_in_month( result.year, result.month ) {
+            return Err( ValueMessage::new( MessageKey::InvalidDate, &[] ) );
+        }
+
+        if let Some( time ) = time {
+            let ( time, offset ) = match time.find( ['Z', 'z', '+', '-'] ) {
+                Some( pos ) => ( &time[ ..pos ], Some( &time[ pos.. ] ) ),
+                None => ( time, None ),
+            };
+            let ( time, fraction ) = match time.split_once( '.' ) {
+                Some( ( time, fraction ) ) => ( time, Some( fraction ) ),
+                None => ( time, None ),
+            };
+            // the fraction of the seconds is digits and ignored
+            if fraction.is_some_and( |fraction| fraction.is_empty() || !fraction.bytes().all( |c| c.is_ascii_digit() ) || time.len() != 8 ) {
+                return Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) );
+            }
+            let mut parts = time.split( ':' );
+            result.hour = number( parts.next().unwrap_or( "" ), 2 )?;
+            result.minute = number( parts.next().unwrap_or( "" ), 2 )?;
+            result.second = match parts.next() {
+                Some( second ) => number( second, 2 )?,
+                None => 0,
+            };
+            if parts.next().is_some() || result.hour > 23 || result.minute > 59 || result.second > 60 {
+                return Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) );
+            }
+            result.offset_minutes = match offset {
+                None => None,
+                Some( "Z" ) | Some( "z" ) => Some( 0 ),
+                Some( offset ) => {
+                    let sign = if offset.starts_with( '-' ) { -1 } else { 1 };
+                    let offset = offset[ 1.. ].replace( ':', "" );
+                    // sliced by the bytes below
+                    if !offset.is_ascii() {
+                        return Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) );
+                    }
+                    let hours : i32 = number( &offset[ ..offset.len().min( 2 ) ], 2 )?;
+                    let minutes : i32 = if offset.len() > 2 { number( &offset[ 2.. ], 2 )? } else { 0 };
+                    if hours > 23 || minutes > 59 {
+                        return Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) );
+                    }
+                    Some( sign * ( hours * 60 + minutes ) )
+                },
+            };
+        }
+        Ok( result )
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_datetime_from_str() {
+        assert_eq!( "2022-10-01".parse::<DateTime>(), Ok( DateTime { year : 2022, month : 10, day : 1, ..Default::default() } ) );
+        assert_eq!( "2022/10/01 12:30".parse::<DateTime>(), Ok( DateTime { year : 2022, month : 10, day : 1, hour : 12, minute : 30, ..Default::default() } ) );
+        assert_eq!( "2022-10-01T12:30:15.250+09:00".parse::<DateTime>(), Ok( DateTime { year : 2022, month : 10, day : 1, hour : 12, minute : 30, second : 15, offset_minutes : Some( 540 ) } ) );
+        assert_eq!( "2022-02-29".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidDate, &[] ) ) );
+        assert_eq!( "2022-10-01T25:00".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) ) );
+        assert_eq!( "yesterday".parse::<DateTime>().is_err(), true );
+        assert_eq!( "2022-10-01T12:00+1é".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) ) );
+        assert_eq!( "2022-10-01T12:00+é1:00".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) ) );
+        assert_eq!( "2022-10-01T12:00+24:00".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) ) );
+        assert_eq!( "2022-10-01T12:00-09:60".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidOffset, &[] ) ) );
+        assert_eq!( "2022-10-01T12:00-23:59".parse::<DateTime>().map( |datetime| datetime.offset_minutes ), Ok( Some( -1439 ) ) );
+        assert_eq!( "2022-10-01T12:30:15.x5Z".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) ) );
+        assert_eq!( "2022-10-01T12:30:15.Z".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) ) );
+        assert_eq!( "2022-10-01T12:30.5".parse::<DateTime>(), Err( ValueMessage::new( MessageKey::InvalidTime, &[] ) ) );
+        assert_eq!( "2022-10-01T12:30:15.5".parse::<DateTime>().map( |datetime| datetime.second ), Ok( 15 ) );
+    }
+
+    #[test]
+    fn test_datetime_to_unix_seconds() {
+        assert_eq!( "1970-01-01T00:00:00Z".parse::<DateTime>().unwrap().to_unix_seconds(), 0 );
+        assert_eq!( "2022-10-01T09:00:00+09:00".parse::<DateTime>().unwrap().to_unix_seconds(), 1664582400 );
+        assert_eq!( "2000-03-01".parse::<DateTime>().unwrap().to_unix_seconds(), 951868800 );
+    }
+}
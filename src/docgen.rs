@@ -0,0 +1,312 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the documents of the tool e.g. the man page and the markdown, rendered by a DocRenderer from the CliDoc model.
+// other formats e.g. AsciiDoc implement DocRenderer on the same CliDoc
+
+use std::fmt::Write;
+
+use crate::IOptParse;
+use crate::OptParse;
+use crate::Visibility;
+use crate::REDACTED;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocOption
+{
+    pub flags : Vec<String>,        // e.g. [ "-s", "--samplingRate" ]
+    pub value_name : Option<String>,    // e.g. "VALUE" or "PCM16|PCM24". None for the flag
+    pub default : String,           // the secrets are masked
+    pub description : String,
+    pub long_description : String,
+    pub is_required : bool,
+    pub group : String,             // empty for the ungrouped
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DocEntry
+{
+    pub name : String,
+    pub description : String,
+}
+
+// the format independent model of the tool for the renderers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CliDoc
+{
+    pub name : String,
+    pub description : String,
+    pub arguments : Vec<DocEntry>,  // by add_positional()
+    pub options : Vec<DocOption>,
+    pub commands : Vec<DocEntry>,
+    pub environment : Vec<DocEntry>,    // the environment variables of the env only entries
+    pub required_groups : Vec<( Vec<String>, bool )>,   // the flags of add_required_one_of() e.g. [ "--input", "--device" ], and true for exactly one
+}
+
+impl CliDoc
+{
+    pub fn new( opt_parse : &OptParse, program : &str ) -> Self {
+        Self {
+            name : program.to_string(),
+            description : opt_parse.get_description().to_string(),
+            arguments : opt_parse.positionals.iter().map( |( name, description, _ )| DocEntry { name : name.clone(), description : description.clone() } ).collect(),
+            options : opt_parse.get_options().iter().filter( |option| !option.is_env_only() && option.get_visibility() != Visibility::Internal ).map( |option| DocOption {
+                flags : [ option.get_option(), option.get_full_option() ].into_iter().filter( |flag| !flag.is_empty() ).map( String::from ).collect(),
+                value_name : match ( option.is_arg_required(), option.get_choices().is_empty() ) {
+                    ( false, _ ) => None,
+                    ( true, true ) => Some( "VALUE".to_string() ),
+                    ( true, false ) => Some( option.get_choices().join( "|" ) ),
+                },
+                default : if option.is_secret() { REDACTED.to_string() } else { option.get_default_value().to_string() },
+                description : option.get_description().to_string(),
+                long_description : option.get_long_description().to_string(),
+                is_required : option.is_required(),
+                group : option.get_group().to_string(),
+            } ).collect(),
+            commands : opt_parse.commands.commands.iter().map( |command| DocEntry { name : command.name().to_string(), description : command.description().to_string() } ).collect(),
+            environment : opt_parse.get_options().iter().filter( |option| option.get_visibility() != Visibility::Internal ).filter_map( |option| Some( DocEntry { name : opt_parse.get_env_var( option.get_name() )?, description : option.get_description().to_string() } ) ).collect(),
+            required_groups : opt_parse.get_required_groups().iter().map( |( names, is_exclusive )| {
+                let flags = names.iter().filter_map( |name| opt_parse.get_option_item( name ) ).map( |option| if option.get_full_option().is_empty() { option.get_option() } else { option.get_full_option() } ).map( String::from ).collect();
+                ( flags, *is_exclusive )
+            } ).collect(),
+        }
+    }
+}
+
+pub trait DocRenderer
+{
+    fn render( &self, doc : &CliDoc ) -> String;
+}
+
+// e.g. docgen::render( &opt_parse, "mytool", &ManRenderer::default() )
+pub fn render<R : DocRenderer + ?Sized>( opt_parse : &OptParse, program : &str, renderer : &R ) -> String {
+    renderer.render( &CliDoc::new( opt_parse, program ) )
+}
+
+// " (--input|--device)" for exactly one of them, " (--output|--play)..." for at least one
+fn synopsis_groups( doc : &CliDoc ) -> String {
+    doc.required_groups.iter().map( |( flags, is_exclusive )| format!( " ({}){}", flags.join( "|" ), if *is_exclusive { "" } else { "..." } ) ).collect()
+}
+
+// the options of the ungrouped first, then per the group in the order of the first appearance
+fn grouped( options : &[DocOption] ) -> Vec<( &str, Vec<&DocOption> )> {
+    let mut result : Vec<( &str, Vec<&DocOption> )> = Vec::new();
+    for option in options {
+        match result.iter_mut().find( |( group, _ )| *group == option.group ) {
+            Some( ( _, options ) ) => options.push( option ),
+            None => result.push( ( &option.group, vec![ option ] ) ),
+        }
+    }
+    result.sort_by_key( |( group, _ )| !group.is_empty() );
+    result
+}
+
+// the roff man page
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManRenderer
+{
+    pub section : u32,
+}
+
+impl Default for ManRenderer
+{
+    fn default() -> Self {
+        Self { section : 1 }
+    }
+}
+
+fn roff_escape( text : &str ) -> String {
+    let text = text.replace( '\\', "\\e" ).replace( '-', "\\-" );
+    text.lines().map( |line| if line.starts_with( '.' ) || line.starts_with( '\'' ) { format!( "\\&{}", line ) } else { line.to_string() } ).collect::<Vec<_>>().join( "\n" )
+}
+
+impl DocRenderer for ManRenderer
+{
+    fn render( &self, doc : &CliDoc ) -> String {
+        let mut result = String::new();
+        let _ = writeln!( result, ".TH {} {}", doc.name.to_uppercase(), self.section );
+        let _ = writeln!( result, ".SH NAME" );
+        if doc.description.is_empty() {
+            let _ = writeln!( result, "{}", roff_escape( &doc.name ) );
+        } else {
+            let _ = writeln!( result, "{} \\- {}", roff_escape( &doc.name ), roff_escape( &doc.description ) );
+        }
+        let _ = writeln!( result, ".SH SYNOPSIS" );
+        let _ = write!( result, ".B {}\n[OPTIONS]{}", roff_escape( &doc.name ), roff_escape( &synopsis_groups( doc ) ) );
+        if !doc.commands.is_empty() {
+            let _ = write!( result, " COMMAND" );
+        }
+        for argument in &doc.arguments {
+            let _ = write!( result, " {}", roff_escape( &argument.name ) );
+        }
+        result.push( '\n' );
+        for ( title, entries ) in [ ( "ARGUMENTS", &doc.arguments ), ( "COMMANDS", &doc.commands ) ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!( result, ".SH {}", title );
+            for entry in entries {
+                let _ = writeln!( result, ".TP\n.B {}\n{}", roff_escape( &entry.name ), roff_escape( &entry.description ) );
+            }
+        }
+        for ( group, options ) in grouped( &doc.options ) {
+            let _ = writeln!( result, ".SH {}", if group.is_empty() { "OPTIONS".to_string() } else { roff_escape( &group.to_uppercase() ) } );
+            for option in options {
+                let flags : Vec<String> = option.flags.iter().map( |flag| format!( "\\fB{}\\fR", roff_escape( flag ) ) ).collect();
+                let _ = write!( result, ".TP\n{}", flags.join( ", " ) );
+                if let Some( value_name ) = &option.value_name {
+                    let _ = write!( result, " \\fI{}\\fR", roff_escape( value_name ) );
+                }
+                let _ = writeln!( result, "\n{}", roff_escape( &option.description ) );
+                if !option.long_description.is_empty() {
+                    let _ = writeln!( result, ".IP\n{}", roff_escape( &option.long_description ) );
+                }
+                if option.is_required {
+                    let _ = writeln!( result, ".IP\nRequired." );
+                } else if option.value_name.is_some() && !option.default.is_empty() {
+                    let _ = writeln!( result, ".IP\nDefault: {}", roff_escape( &option.default ) );
+                }
+            }
+        }
+        if !doc.environment.is_empty() {
+            let _ = writeln!( result, ".SH ENVIRONMENT" );
+            for entry in &doc.environment {
+                let _ = writeln!( result, ".TP\n.B {}\n{}", roff_escape( &entry.name ), roff_escape( &entry.description ) );
+            }
+        }
+        result
+    }
+}
+
+// the markdown e.g. for README.md
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkdownRenderer;
+
+impl DocRenderer for MarkdownRenderer
+{
+    fn render( &self, doc : &CliDoc ) -> String {
+        let mut result = String::new();
+        let _ = writeln!( result, "# {}\n", doc.name );
+        if !doc.description.is_empty() {
+            let _ = writeln!( result, "{}\n", doc.description );
+        }
+        let _ = write!( result, "```\n{} [OPTIONS]{}", doc.name, synopsis_groups( doc ) );
+        if !doc.commands.is_empty() {
+            let _ = write!( result, " COMMAND" );
+        }
+        for argument in &doc.arguments {
+            let _ = write!( result, " {}", argument.name );
+        }
+        let _ = writeln!( result, "\n```" );
+        for ( title, entries ) in [ ( "Arguments", &doc.arguments ), ( "Commands", &doc.commands ) ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!( result, "\n## {}\n", title );
+            for entry in entries {
+                let _ = writeln!( result, "- `{}`: {}", entry.name, entry.description );
+            }
+        }
+        for ( group, options ) in grouped( &doc.options ) {
+            let _ = writeln!( result, "\n## {}\n", if group.is_empty() { "Options" } else { group } );
+            for option in options {
+                let mut flags : Vec<String> = option.flags.iter().map( |flag| format!( "`{}`", flag ) ).collect();
+                if let ( Some( value_name ), Some( last ) ) = ( &option.value_name, flags.last_mut() ) {
+                    *last = format!( "`{} <{}>`", last.trim_matches( '`' ), value_name );
+                }
+                let _ = write!( result, "- {}: {}", flags.join( ", " ), option.description );
+                if option.is_required {
+                    let _ = write!( result, " (required)" );
+                } else if option.value_name.is_some() && !option.default.is_empty() {
+                    let _ = write!( result, " (default: `{}`)", option.default );
+                }
+                result.push( '\n' );
+                for line in option.long_description.lines() {
+                    let _ = writeln!( result, "  {}", line );
+                }
+            }
+        }
+        if !doc.environment.is_empty() {
+            let _ = writeln!( result, "\n## Environment\n" );
+            for entry in &doc.environment {
+                let _ = writeln!( result, "- `{}`: {}", entry.name, entry.description );
+            }
+        }
+        result
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::vec_init_then_push, clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::OptParseItem;
+
+    fn opt_parse() -> OptParse {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ).with_group( "Format" ) );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode").with_long_description( "Print the progress" ) );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "Convert audio" );
+        opt_parse.add_positional( "INPUT", "Input wav file" );
+        opt_parse
+    }
+
+    #[test]
+    fn test_docgen_markdown() {
+        assert_eq!( render( &opt_parse(), "mytool", &MarkdownRenderer ), "# mytool\n\nConvert audio\n\n```\nmytool [OPTIONS] INPUT\n```\n\n## Arguments\n\n- `INPUT`: Input wav file\n\n## Options\n\n- `-s`, `--samplingRate <VALUE>`: Set sampling rate (default: `48000`)\n- `-v`, `--verbose`: Enable verbose mode\n  Print the progress\n\n## Format\n\n- `-e`, `--encoding <PCM16|PCM24>`: Set encoding (default: `PCM16`)\n" );
+    }
+
+    #[test]
+    fn test_docgen_man() {
+        let man = render( &opt_parse(), "mytool", &ManRenderer::default() );
+        assert_eq!( man.starts_with( ".TH MYTOOL 1\n.SH NAME\nmytool \\- Convert audio\n.SH SYNOPSIS\n.B mytool\n[OPTIONS] INPUT\n" ), true );
+        assert_eq!( man.contains( ".TP\n\\fB\\-s\\fR, \\fB\\-\\-samplingRate\\fR \\fIVALUE\\fR\nSet sampling rate\n.IP\nDefault: 48000\n" ), true );
+        assert_eq!( man.contains( ".SH FORMAT\n" ), true );
+    }
+
+    #[test]
+    fn test_docgen_custom_renderer() {
+        struct NamesRenderer;
+        impl DocRenderer for NamesRenderer {
+            fn render( &self, doc : &CliDoc ) -> String {
+                doc.options.iter().map( |option| option.flags.join( "," ) ).collect::<Vec<_>>().join( " " )
+            }
+        }
+        assert_eq!( render( &opt_parse(), "mytool", &NamesRenderer ), "-s,--samplingRate -e,--encoding -v,--verbose" );
+    }
+
+    #[test]
+    fn test_docgen_environment() {
+        let mut opt_parse = opt_parse();
+        opt_parse.add_options( vec![ OptParseItem::env_only( "apiToken", "", "API token" ) ] );
+        opt_parse.set_env_prefix( "mytool" );
+        let markdown = render( &opt_parse, "mytool", &MarkdownRenderer );
+        assert_eq!( markdown.ends_with( "\n## Environment\n\n- `MYTOOL_API_TOKEN`: API token\n" ), true );
+        assert_eq!( render( &opt_parse, "mytool", &ManRenderer::default() ).ends_with( ".SH ENVIRONMENT\n.TP\n.B MYTOOL_API_TOKEN\nAPI token\n" ), true );
+    }
+
+    #[test]
+    fn test_docgen_required_groups() {
+        let mut opt_parse = opt_parse();
+        opt_parse.add_options( vec![ OptParseItem::new( "-d", "--device", true, "", "Input device" ), OptParseItem::new( "-g", "", false, "false", "Generate the sine wave" ) ] );
+        opt_parse.add_required_exactly_one_of( &[ "device", "g" ] );
+        opt_parse.add_required_one_of( &[ "samplingRate", "encoding" ] );
+        assert_eq!( render( &opt_parse, "mytool", &MarkdownRenderer ).contains( "```\nmytool [OPTIONS] (--device|-g) (--samplingRate|--encoding)... INPUT\n```" ), true );
+        assert_eq!( render( &opt_parse, "mytool", &ManRenderer::default() ).contains( ".B mytool\n[OPTIONS] (\\-\\-device|\\-g) (\\-\\-samplingRate|\\-\\-encoding)... INPUT\n" ), true );
+    }
+}
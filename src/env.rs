@@ -0,0 +1,182 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the options as the environment variables e.g. "samplingRate" of "mytool" is MYTOOL_SAMPLING_RATE
+
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::shell;
+use crate::Matches;
+use crate::REDACTED;
+
+// e.g. ( "mytool", "samplingRate" ) -> "MYTOOL_SAMPLING_RATE". the empty prefix gives "SAMPLING_RATE"
+pub fn var_name( prefix : &str, name : &str ) -> String {
+    let mut result = String::new();
+    for part in [ prefix, name ] {
+        if part.is_empty() {
+            continue;
+        }
+        if !result.is_empty() {
+            result.push( '_' );
+        }
+        let mut is_prev_lower = false;
+        for c in part.chars() {
+            if c.is_ascii_uppercase() && is_prev_lower {
+                result.push( '_' );
+            }
+            is_prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            result.push( if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' } );
+        }
+    }
+    result
+}
+
+// "export MYTOOL_SAMPLING_RATE=44100" lines of the resolved values for eval "$(mytool --print-env)". the secret options are excluded
+pub fn to_exports( matches : &Matches, prefix : &str ) -> String {
+    let mut result = String::new();
+    for entry in matches.entries.iter().filter( |entry| !entry.is_secret ) {
+        let _ = writeln!( result, "export {}={}", var_name( prefix, &entry.name ), shell::quote( &entry.value ) );
+    }
+    result
+}
+
+// the dotenv file e.g. MYTOOL_OUTPUT="my file.pcm" for the containers to take over the values.
+// the secret options are "********" when is_redacted, or excluded
+pub fn to_dotenv( matches : &Matches, prefix : &str, is_redacted : bool ) -> String {
+    let mut result = String::new();
+    for entry in &matches.entries {
+        let value = match ( entry.is_secret, is_redacted ) {
+            ( false, _ ) => entry.value.as_str(),
+            ( true, true ) => REDACTED,
+            ( true, false ) => continue,
+        };
+        let _ = write!( result, "{}=", var_name( prefix, &entry.name ) );
+        if !value.is_empty() && value.chars().all( |c| c.is_ascii_alphanumeric() || "_-./:,@+%".contains( c ) ) {
+            result.push_str( value );
+        } else {
+            result.push( '"' );
+            for c in value.chars() {
+                match c {
+                    '"' | '\\' | '$' | '`' => { result.push( '\\' ); result.push( c ); },
+                    '\n' => result.push_str( "\\n" ),
+                    c => result.push( c ),
+                }
+            }
+            result.push( '"' );
+        }
+        result.push( '\n' );
+    }
+    result
+}
+
+// ${VAR} -> the value by lookup( "VAR" ), or the empty for the unknown VAR as the shell does. $${VAR} is ${VAR} as is
+pub fn interpolate<'a>( value : &'a str, lookup : impl Fn( &str ) -> Option<String> ) -> Cow<'a, str> {
+    if !value.contains( "${" ) {
+        return Cow::Borrowed( value );
+    }
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some( pos ) = rest.find( '$' ) {
+        result.push_str( &rest[ ..pos ] );
+        rest = &rest[ pos..];
+        if let Some( escaped ) = rest.strip_prefix( "$$" ) {
+            result.push( '$' );
+            rest = escaped;
+            continue;
+        }
+        let name_end = rest.strip_prefix( "${" ).and_then( |name| name.find( '}' ) );
+        match name_end {
+            Some( end ) => {
+                if let Some( var ) = lookup( &rest[ 2..2 + end ] ) {
+                    result.push_str( &var );
+                }
+                rest = &rest[ 2 + end + 1..];
+            },
+            None => {
+                result.push( '$' );
+                rest = &rest[ 1..];
+            }
+        }
+    }
+    result.push_str( rest );
+    Cow::Owned( result )
+}
+
+#[cfg(feature = "std")]
+// ~/out.pcm -> /home/alice/out.pcm, and ~bob/out.pcm -> bob's home per /etc/passwd. unchanged when the home is unknown
+pub fn expand_tilde( value : &str ) -> Cow<'_, str> {
+    let rest = match value.strip_prefix( '~' ) {
+        Some( rest ) => rest,
+        None => return Cow::Borrowed( value ),
+    };
+    let ( user, path ) = rest.split_at( rest.find( ['/', '\\'] ).unwrap_or( rest.len() ) );
+    let home = if user.is_empty() {
+        std::env::var( "HOME" ).or_else( |_| std::env::var( "USERPROFILE" ) ).ok()
+    } else {
+        user_home( user )
+    };
+    match home {
+        Some( home ) => Cow::Owned( format!( "{}{}", home.trim_end_matches( '/' ), path ) ),
+        None => Cow::Borrowed( value ),
+    }
+}
+
+#[cfg(feature = "std")]
+fn user_home( user : &str ) -> Option<String> {
+    let passwd = std::fs::read_to_string( "/etc/passwd" ).ok()?;
+    // name:password:uid:gid:gecos:home:shell
+    passwd.lines().map( |line| line.split( ':' ).collect::<alloc::vec::Vec<_>>() ).find( |fields| fields.len() >= 6 && fields[0] == user ).map( |fields| fields[5].to_string() )
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_var_name() {
+        assert_eq!( var_name( "mytool", "samplingRate" ), "MYTOOL_SAMPLING_RATE" );
+        assert_eq!( var_name( "my-tool", "no-input" ), "MY_TOOL_NO_INPUT" );
+        assert_eq!( var_name( "", "encoding" ), "ENCODING" );
+        assert_eq!( var_name( "", "HTTPProxy" ), "HTTPPROXY" );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_expand_tilde() {
+        let home = std::env::var( "HOME" ).unwrap();
+        assert_eq!( expand_tilde( "~/out.pcm" ), format!( "{}/out.pcm", home.trim_end_matches( '/' ) ) );
+        assert_eq!( expand_tilde( "a~/out.pcm" ), "a~/out.pcm" );
+        assert_eq!( expand_tilde( "~no_such_user_for_test/out.pcm" ), "~no_such_user_for_test/out.pcm" );
+        #[cfg(unix)]
+        assert_eq!( expand_tilde( "~root/out.pcm" ).ends_with( "/out.pcm" ), true );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let lookup = |name : &str| if name == "HOME" { Some( "/home/alice".to_string() ) } else { None };
+        assert_eq!( interpolate( "${HOME}/renders", lookup ), "/home/alice/renders" );
+        assert_eq!( interpolate( "a${NO_SUCH}b", lookup ), "ab" );
+        assert_eq!( interpolate( "$${HOME} costs $5 ${HOME", lookup ), "${HOME} costs $5 ${HOME" );
+        assert_eq!( interpolate( "plain", lookup ), "plain" );
+    }
+}
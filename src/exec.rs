@@ -0,0 +1,66 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// run the other program with the args as given e.g. the wrapper tools running "mytool --verbose -- make -j4".
+// the args are passed as they are without the shell, then no quoting is needed
+
+use std::ffi::OsStr;
+use std::process::Command;
+use std::process::ExitStatus;
+
+// the program and its args e.g. [ "make", "-j4" ]. None for the empty
+pub fn command<S : AsRef<OsStr>>( args : &[S] ) -> Option<Command> {
+    let ( program, args ) = args.split_first()?;
+    let mut result = Command::new( program );
+    result.args( args );
+    Some( result )
+}
+
+// the exit code to return as the child's. 128 + the signal for the child killed by the signal as the shell does
+pub fn exit_code( status : ExitStatus ) -> i32 {
+    if let Some( code ) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some( signal ) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+// run the program with the args inheriting stdin, stdout and stderr, and wait for its exit code
+pub fn run<S : AsRef<OsStr>>( args : &[S] ) -> std::io::Result<i32> {
+    let mut command = command( args ).ok_or_else( || std::io::Error::new( std::io::ErrorKind::InvalidInput, "no program to run" ) )?;
+    Ok( exit_code( command.status()? ) )
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_run() {
+        assert_eq!( run( &[ "sh", "-c", "exit 3" ] ).unwrap(), 3 );
+        assert_eq!( run( &[ "sh", "-c", "kill -9 $$" ] ).unwrap(), 137 );
+        assert_eq!( run::<&str>( &[] ).is_err(), true );
+    }
+}
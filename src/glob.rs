@@ -0,0 +1,149 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the glob pattern values e.g. --inputs='*.pcm' expanded by ourselves since the shell on Windows doesn't
+// * matches any characters, ? matches one character and [abc], [a-z], [!a] match one of the set. the hidden files match only the patterns starting with .
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::MessageKey;
+use crate::ValueMessage;
+
+// what to do when the pattern matches no path
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoMatch
+{
+    #[default]
+    Error,      // fail the parse
+    Literal,    // use the pattern itself as the path like sh
+    Empty,      // no path
+}
+
+pub fn is_pattern( value : &str ) -> bool {
+    value.contains( ['*', '?', '['] )
+}
+
+// whether the name matches the pattern. the pattern is for one path component
+pub fn is_match( pattern : &str, name : &str ) -> bool {
+    let pattern : Vec<char> = pattern.chars().collect();
+    let name : Vec<char> = name.chars().collect();
+    is_match_chars( &pattern, &name )
+}
+
+fn is_match_chars( pattern : &[char], name : &[char] ) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some( '*' ) => ( 0..=name.len() ).any( |skip| is_match_chars( &pattern[ 1.. ], &name[ skip.. ] ) ),
+        Some( '?' ) => !name.is_empty() && is_match_chars( &pattern[ 1.. ], &name[ 1.. ] ),
+        Some( '[' ) if pattern.contains( &']' ) => {
+            let Some( &c ) = name.first() else { return false; };
+            let is_negated = matches!( pattern.get( 1 ), Some( '!' ) | Some( '^' ) );
+            let start = if is_negated { 2 } else { 1 };
+            // the ] just after [ is the member of the set
+            let end = match pattern[ start + 1.. ].iter().position( |c| *c == ']' ) {
+                Some( pos ) => start + 1 + pos,
+                None => return pattern[ 0 ] == c && is_match_chars( &pattern[ 1.. ], &name[ 1.. ] ),
+            };
+            let set = &pattern[ start..end ];
+            let mut is_member = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[ i + 1 ] == '-' {
+                    is_member |= set[ i ] <= c && c <= set[ i + 2 ];
+                    i += 3;
+                } else {
+                    is_member |= set[ i ] == c;
+                    i += 1;
+                }
+            }
+            is_member != is_negated && is_match_chars( &pattern[ end + 1.. ], &name[ 1.. ] )
+        },
+        Some( c ) => name.first() == Some( c ) && is_match_chars( &pattern[ 1.. ], &name[ 1.. ] ),
+    }
+}
+
+// the sorted paths matching the pattern. the value not being a pattern is the path as is
+pub fn expand( pattern : &str, no_match : NoMatch ) -> Result<Vec<PathBuf>, ValueMessage> {
+    if !is_pattern( pattern ) {
+        return Ok( vec![ PathBuf::from( pattern ) ] );
+    }
+    let mut paths = vec![ PathBuf::new() ];
+    for component in Path::new( pattern ).components() {
+        let part = component.as_os_str().to_string_lossy();
+        if matches!( component, Component::Normal( _ ) ) && is_pattern( &part ) {
+            let mut matched = Vec::new();
+            for path in &paths {
+                let dir = if path.as_os_str().is_empty() { Path::new( "." ) } else { path.as_path() };
+                let Ok( entries ) = std::fs::read_dir( dir ) else { continue; };
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if ( !name.starts_with( '.' ) || part.starts_with( '.' ) ) && is_match( &part, &name ) {
+                        matched.push( path.join( name ) );
+                    }
+                }
+            }
+            paths = matched;
+        } else {
+            paths = paths.into_iter().map( |path| path.join( component ) ).filter( |path| path.exists() ).collect();
+        }
+    }
+    paths.sort();
+    if paths.is_empty() {
+        return match no_match {
+            NoMatch::Error => Err( ValueMessage::new( MessageKey::NoPathMatch, &[ ( "pattern", pattern.to_string() ) ] ) ),
+            NoMatch::Literal => Ok( vec![ PathBuf::from( pattern ) ] ),
+            NoMatch::Empty => Ok( paths ),
+        };
+    }
+    Ok( paths )
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_is_match() {
+        assert_eq!( is_match( "*.pcm", "a.pcm" ), true );
+        assert_eq!( is_match( "*.pcm", "a.wav" ), false );
+        assert_eq!( is_match( "take?.pcm", "take1.pcm" ), true );
+        assert_eq!( is_match( "take[0-3].pcm", "take2.pcm" ), true );
+        assert_eq!( is_match( "take[!0-3].pcm", "take2.pcm" ), false );
+        assert_eq!( is_match( "a*b*c", "aXXbYc" ), true );
+    }
+
+    #[test]
+    fn test_glob_expand() {
+        let dir = std::env::temp_dir().join( format!( "rst_opt_parse_glob_{}", std::process::id() ) );
+        std::fs::create_dir_all( dir.join( "sub" ) ).unwrap();
+        for name in [ "b.pcm", "a.pcm", "c.wav", ".hidden.pcm", "sub/d.pcm" ] {
+            std::fs::write( dir.join( name ), "" ).unwrap();
+        }
+        let base = dir.to_string_lossy().to_string();
+
+        assert_eq!( expand( &format!( "{}/*.pcm", base ), NoMatch::Error ), Ok( vec![ dir.join( "a.pcm" ), dir.join( "b.pcm" ) ] ) );
+        assert_eq!( expand( &format!( "{}/*/*.pcm", base ), NoMatch::Error ), Ok( vec![ dir.join( "sub/d.pcm" ) ] ) );
+        assert_eq!( expand( &format!( "{}/*.mp3", base ), NoMatch::Error ).is_err(), true );
+        assert_eq!( expand( &format!( "{}/*.mp3", base ), NoMatch::Literal ), Ok( vec![ PathBuf::from( format!( "{}/*.mp3", base ) ) ] ) );
+        assert_eq!( expand( &format!( "{}/*.mp3", base ), NoMatch::Empty ), Ok( Vec::new() ) );
+
+        std::fs::remove_dir_all( &dir ).unwrap();
+    }
+}
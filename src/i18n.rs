@@ -0,0 +1,303 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the translation of the parser's messages e.g. OptParseError, by the templates with the placeholders e.g.
+//   opt_parse.set_message_catalog( |key| match key { MessageKey::UnknownOption => Some( "unbekannte Option {name}".to_string() ), _ => None } );
+//   eprintln!( "{}", opt_parse.format_error( &error ) );
+// the keys without the translation fall back to English. the {message} of InvalidValue by the built-in value types is also translated
+// by the keys from NotOneOf as ValueMessage carries them, and the messages of the custom parsers are as is
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::OptParseError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageKey
+{
+    InvalidSpec,        // {line} {message}
+    UnknownOption,      // {name}
+    InvalidValue,       // {name} {value} {message}
+    InvalidArgs,
+    MissingCommand,
+    UnknownCommand,     // {name}
+    DidYouMean,         // {suggestion}, appended to UnknownCommand
+    AvailableCommands,  // {available}, appended to UnknownCommand
+    CommandFailed,      // {name} {message}
+    UnterminatedQuote,
+    InvalidRecord,      // {line} {message}
+    DefaultCycle,       // {names}
+    MissingValue,       // {name}
+    MissingOption,      // {name}
+    ConflictingOptions, // {name} {other}
+    MissingOneOf,       // {names}
+    RenamedOption,      // {old} {new}, the warning for the flag of add_renamed_option()
+    FromOrigin,         // {origin}, appended to the error of the value from e.g. the config file
+    // the {message} of InvalidValue
+    NotOneOf,           // {choices}
+    NotInteger,
+    NotNumber,
+    NotBool,
+    NotExtension,       // {extensions}
+    UnknownFlag,
+    NotNumberWithUnit,  // {units}
+    OutOfRange,         // {min} {max}
+    ValueCount,         // {min} {max}
+    ValueCountAtLeast,  // {min}
+    DateTimeFormat,
+    InvalidDate,
+    InvalidTime,
+    InvalidOffset,
+    NoPathMatch,        // {pattern}
+}
+
+impl MessageKey
+{
+    // the English template
+    pub fn default_template( &self ) -> &'static str {
+        match self {
+            MessageKey::InvalidSpec => "invalid spec at line {line}: {message}",
+            MessageKey::UnknownOption => "unknown option {name}",
+            MessageKey::InvalidValue => "invalid value {value} for {name}: {message}",
+            MessageKey::InvalidArgs => "invalid arguments",
+            MessageKey::MissingCommand => "missing command",
+            MessageKey::UnknownCommand => "unknown command {name}",
+            MessageKey::DidYouMean => ", did you mean {suggestion}?",
+            MessageKey::AvailableCommands => " (available: {available})",
+            MessageKey::CommandFailed => "{name} failed: {message}",
+            MessageKey::UnterminatedQuote => "unterminated quote",
+            MessageKey::InvalidRecord => "invalid record at line {line}: {message}",
+            MessageKey::DefaultCycle => "the defaults refer to each other: {names}",
+            MessageKey::MissingValue => "missing value for {name}",
+            MessageKey::MissingOption => "missing required option {name}",
+            MessageKey::ConflictingOptions => "{name} can't be used with {other}",
+            MessageKey::MissingOneOf => "missing one of {names}",
+            MessageKey::RenamedOption => "warning: {old} is deprecated, use {new} instead",
+            MessageKey::FromOrigin => " (from {origin})",
+            MessageKey::NotOneOf => "not one of {choices}",
+            MessageKey::NotInteger => "not an integer",
+            MessageKey::NotNumber => "not a number",
+            MessageKey::NotBool => "not true or false",
+            MessageKey::NotExtension => "not a {extensions} file",
+            MessageKey::UnknownFlag => "unknown flag",
+            MessageKey::NotNumberWithUnit => "not a number with {units}",
+            MessageKey::OutOfRange => "out of the range {min} to {max}",
+            MessageKey::ValueCount => "expected {min} to {max} values",
+            MessageKey::ValueCountAtLeast => "expected at least {min} values",
+            MessageKey::DateTimeFormat => "expected YYYY-MM-DD[THH:MM[:SS]][Z|+HH:MM]",
+            MessageKey::InvalidDate => "invalid date",
+            MessageKey::InvalidTime => "invalid time",
+            MessageKey::InvalidOffset => "invalid offset",
+            MessageKey::NoPathMatch => "no path matches {pattern}",
+        }
+    }
+}
+
+pub trait MessageCatalog
+{
+    // the translated template of the key. None for the English
+    fn template( &self, key : MessageKey ) -> Option<String>;
+}
+
+impl<F : Fn( MessageKey ) -> Option<String>> MessageCatalog for F
+{
+    fn template( &self, key : MessageKey ) -> Option<String> {
+        self( key )
+    }
+}
+
+// the English only
+pub(crate) struct DefaultCatalog;
+
+impl MessageCatalog for DefaultCatalog
+{
+    fn template( &self, _key : MessageKey ) -> Option<String> {
+        None
+    }
+}
+
+// the catalog set by set_message_catalog()
+#[derive(Clone)]
+pub(crate) struct CatalogRef( pub(crate) Arc<dyn MessageCatalog + Send + Sync> );
+
+impl core::fmt::Debug for CatalogRef
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "MessageCatalog" )
+    }
+}
+
+// the catalogs can't be compared then only the same catalog is the same
+impl PartialEq for CatalogRef
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+// {key} in the template is the value of the key. the unknown {key} is as is and the values aren't substituted again
+fn fill( template : &str, values : &[( &str, String )] ) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some( pos ) = rest.find( '{' ) {
+        result.push_str( &rest[ ..pos ] );
+        rest = &rest[ pos..];
+        let value = rest.find( '}' ).and_then( |end| values.iter().find( |( key, _ )| *key == &rest[ 1..end ] ).map( |( _, value )| ( end, value ) ) );
+        match value {
+            Some( ( end, value ) ) => {
+                result.push_str( value );
+                rest = &rest[ end + 1..];
+            },
+            None => {
+                result.push( '{' );
+                rest = &rest[ 1..];
+            }
+        }
+    }
+    result.push_str( rest );
+    result
+}
+
+pub(crate) fn message( catalog : &dyn MessageCatalog, key : MessageKey, values : &[( &str, String )] ) -> String {
+    match catalog.template( key ) {
+        Some( template ) => fill( &template, values ),
+        None => fill( key.default_template(), values ),
+    }
+}
+
+// the {message} of InvalidValue. the built-in value types give the key and its values to be translated by the catalog,
+// and the custom parsers and the middlewares give the text as is e.g. "not supported".into()
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueMessage
+{
+    Key( MessageKey, Vec<( &'static str, String )> ),
+    Text( String ),
+}
+
+impl ValueMessage
+{
+    pub fn new( key : MessageKey, values : &[( &'static str, String )] ) -> Self {
+        ValueMessage::Key( key, values.to_vec() )
+    }
+
+    // the message by the catalog's template of the key. the text is as is
+    pub fn localize( &self, catalog : &dyn MessageCatalog ) -> String {
+        match self {
+            ValueMessage::Key( key, values ) => message( catalog, *key, values ),
+            ValueMessage::Text( text ) => text.clone(),
+        }
+    }
+}
+
+impl core::fmt::Display for ValueMessage
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( &self.localize( &DefaultCatalog ) )
+    }
+}
+
+impl From<String> for ValueMessage
+{
+    fn from( text : String ) -> Self {
+        ValueMessage::Text( text )
+    }
+}
+
+impl From<&str> for ValueMessage
+{
+    fn from( text : &str ) -> Self {
+        ValueMessage::Text( text.to_string() )
+    }
+}
+
+impl OptParseError
+{
+    // the message by the catalog's templates. Display is the same with the English
+    pub fn localize( &self, catalog : &dyn MessageCatalog ) -> String {
+        match self {
+            OptParseError::InvalidSpec { line, message : text } => message( catalog, MessageKey::InvalidSpec, &[ ( "line", format!( "{}", line ) ), ( "message", text.clone() ) ] ),
+            OptParseError::UnknownOption { name } => message( catalog, MessageKey::UnknownOption, &[ ( "name", name.clone() ) ] ),
+            OptParseError::InvalidValue { name, value, message : text } => message( catalog, MessageKey::InvalidValue, &[ ( "name", name.clone() ), ( "value", format!( "{:?}", value ) ), ( "message", text.localize( catalog ) ) ] ),
+            OptParseError::InvalidArgs => message( catalog, MessageKey::InvalidArgs, &[] ),
+            OptParseError::MissingCommand => message( catalog, MessageKey::MissingCommand, &[] ),
+            OptParseError::UnknownCommand { name, suggestion, available } => {
+                let mut result = message( catalog, MessageKey::UnknownCommand, &[ ( "name", name.clone() ) ] );
+                if let Some( suggestion ) = suggestion {
+                    result.push_str( &message( catalog, MessageKey::DidYouMean, &[ ( "suggestion", suggestion.clone() ) ] ) );
+                }
+                if !available.is_empty() {
+                    result.push_str( &message( catalog, MessageKey::AvailableCommands, &[ ( "available", available.join( ", " ) ) ] ) );
+                }
+                result
+            },
+            OptParseError::CommandFailed { name, message : text } => message( catalog, MessageKey::CommandFailed, &[ ( "name", name.clone() ), ( "message", text.clone() ) ] ),
+            OptParseError::UnterminatedQuote => message( catalog, MessageKey::UnterminatedQuote, &[] ),
+            OptParseError::InvalidRecord { line, message : text } => message( catalog, MessageKey::InvalidRecord, &[ ( "line", format!( "{}", line ) ), ( "message", text.clone() ) ] ),
+            OptParseError::DefaultCycle { names } => message( catalog, MessageKey::DefaultCycle, &[ ( "names", names.join( " -> " ) ) ] ),
+            OptParseError::MissingValue { name } => message( catalog, MessageKey::MissingValue, &[ ( "name", name.clone() ) ] ),
+            OptParseError::MissingOption { name } => message( catalog, MessageKey::MissingOption, &[ ( "name", name.clone() ) ] ),
+            OptParseError::ConflictingOptions { name, other } => message( catalog, MessageKey::ConflictingOptions, &[ ( "name", name.clone() ), ( "other", other.clone() ) ] ),
+            OptParseError::MissingOneOf { names } => message( catalog, MessageKey::MissingOneOf, &[ ( "names", names.join( ", " ) ) ] ),
+            OptParseError::WithOrigin { error, origin } => error.localize( catalog ) + &message( catalog, MessageKey::FromOrigin, &[ ( "origin", origin.clone() ) ] ),
+        }
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_i18n_localize() {
+        let catalog = |key| match key {
+            MessageKey::InvalidValue => Some( "valeur {value} invalide pour {name} : {message}".to_string() ),
+            MessageKey::UnknownCommand => Some( "commande inconnue {name}".to_string() ),
+            MessageKey::DidYouMean => Some( ", vouliez-vous dire {suggestion} ?".to_string() ),
+            _ => None
+        };
+        let error = OptParseError::InvalidValue { name : "samplingRate".to_string(), value : "fast".to_string(), message : "{name}".into() };
+        assert_eq!( error.localize( &catalog ), "valeur \"fast\" invalide pour samplingRate : {name}" );
+        let error = OptParseError::UnknownCommand { name : "covnert".to_string(), suggestion : Some( "convert".to_string() ), available : vec![ "convert".to_string() ] };
+        assert_eq!( error.localize( &catalog ), "commande inconnue covnert, vouliez-vous dire convert ? (available: convert)" );
+        assert_eq!( OptParseError::MissingCommand.localize( &catalog ), "missing command" );
+    }
+
+    #[test]
+    fn test_i18n_localize_value_message() {
+        let catalog = |key| match key {
+            MessageKey::InvalidValue => Some( "Wert {value} ungültig für {name}: {message}".to_string() ),
+            MessageKey::NotInteger => Some( "keine ganze Zahl".to_string() ),
+            MessageKey::OutOfRange => Some( "außerhalb von {min} bis {max}".to_string() ),
+            MessageKey::NotOneOf => Some( "keiner von {choices}".to_string() ),
+            _ => None
+        };
+        let invalid = |value : &str, message : ValueMessage| OptParseError::InvalidValue { name : "rate".to_string(), value : value.to_string(), message };
+        assert_eq!( invalid( "fast", ValueMessage::new( MessageKey::NotInteger, &[] ) ).localize( &catalog ), "Wert \"fast\" ungültig für rate: keine ganze Zahl" );
+        assert_eq!( invalid( "1Hz", ValueMessage::new( MessageKey::OutOfRange, &[ ( "min", "8000".to_string() ), ( "max", "192000".to_string() ) ] ) ).localize( &catalog ), "Wert \"1Hz\" ungültig für rate: außerhalb von 8000 bis 192000" );
+        assert_eq!( invalid( "PCM32", ValueMessage::new( MessageKey::NotOneOf, &[ ( "choices", "PCM8, PCM16".to_string() ) ] ) ).localize( &catalog ), "Wert \"PCM32\" ungültig für rate: keiner von PCM8, PCM16" );
+        // the untranslated keys and the custom messages are as is even if they read as the built-in ones
+        assert_eq!( invalid( "x", ValueMessage::new( MessageKey::InvalidDate, &[] ) ).localize( &catalog ), "Wert \"x\" ungültig für rate: invalid date" );
+        assert_eq!( invalid( "4:0", "not a ratio e.g. 16:9".into() ).localize( &catalog ), "Wert \"4:0\" ungültig für rate: not a ratio e.g. 16:9" );
+        assert_eq!( invalid( "x", "not an integer".into() ).localize( &catalog ), "Wert \"x\" ungültig für rate: not an integer" );
+        assert_eq!( ValueMessage::new( MessageKey::OutOfRange, &[ ( "min", "1".to_string() ), ( "max", "3".to_string() ) ] ).to_string(), "out of the range 1 to 3" );
+    }
+}
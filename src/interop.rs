@@ -0,0 +1,292 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// bridges from and to the other option parsers. clap::Command with the clap feature, and the getopts::Options style
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "clap")]
+use alloc::string::ToString;
+#[cfg(feature = "clap")]
+use crate::IOptParse;
+#[cfg(feature = "clap")]
+use crate::OptParse;
+use crate::OptParseItem;
+
+// the clap::Command equivalent to the spec e.g. for migrating to clap. the positionals and the commands are included
+#[cfg(feature = "clap")]
+pub fn to_clap_command( opt_parse : &OptParse ) -> clap::Command {
+    let name = std::env::args_os().next().as_deref().map( std::path::Path::new ).and_then( std::path::Path::file_stem ).map( |name| name.to_string_lossy().into_owned() ).unwrap_or_default();
+    let mut command = clap::Command::new( name ).about( opt_parse.get_description().to_string() );
+    if let Some( version ) = opt_parse.get_version() {
+        command = command.version( version.to_string() );
+    }
+    for option in opt_parse.get_options().iter().filter( |option| !option.is_env_only() ) {
+        command = command.arg( to_clap_arg( option, opt_parse.get_env_var( option.get_name() ) ) );
+    }
+    for ( name, description, _ ) in &opt_parse.positionals {
+        command = command.arg( clap::Arg::new( name.clone() ).help( description.clone() ) );
+    }
+    for sub in &opt_parse.commands.commands {
+        let mut subcommand = clap::Command::new( sub.name().to_string() ).about( sub.description().to_string() );
+        for option in sub.options() {
+            subcommand = subcommand.arg( to_clap_arg( &option, None ) );
+        }
+        command = command.subcommand( subcommand );
+    }
+    command
+}
+
+#[cfg(feature = "clap")]
+fn to_clap_arg( option : &OptParseItem, env : Option<String> ) -> clap::Arg {
+    let mut arg = clap::Arg::new( option.get_name().to_string() ).help( option.get_description().to_string() ).required( option.is_required() );
+    if let Some( short ) = option.get_option().strip_prefix( '-' ).filter( |short| short.chars().count() == 1 ) {
+        arg = arg.short( short.chars().next().unwrap_or_default() );
+    }
+    if let Some( long ) = option.get_full_option().strip_prefix( "--" ) {
+        arg = arg.long( long.to_string() );
+    }
+    if let Some( env ) = env {
+        arg = arg.env( env );
+    }
+    if option.get_visibility() != crate::Visibility::Standard {
+        arg = arg.hide( true );
+    }
+    if !option.is_arg_required() {
+        return arg.action( clap::ArgAction::SetTrue );
+    }
+    if !option.get_default_value().is_empty() {
+        arg = arg.default_value( option.get_default_value().to_string() );
+    }
+    if !option.get_choices().is_empty() {
+        arg = arg.value_parser( clap::builder::PossibleValuesParser::new( option.get_choices().iter().cloned() ) );
+    }
+    if let Some( value ) = option.get_missing_value() {
+        arg = arg.num_args( 0..=1 ).default_missing_value( value.to_string() );
+    }
+    if option.is_greedy() {
+        let ( min, max ) = option.get_value_count();
+        arg = arg.num_args( min..=max ).action( clap::ArgAction::Append );
+    } else if option.is_multiple() {
+        arg = arg.action( clap::ArgAction::Append );
+    } else {
+        arg = arg.action( clap::ArgAction::Set );
+    }
+    arg
+}
+
+// the OptParseItems of the clap::Command's args e.g. for migrating from clap. the positionals, -h and --version are skipped, see add_positional()
+#[cfg(feature = "clap")]
+pub fn from_clap_command( command : &clap::Command ) -> Vec<OptParseItem> {
+    let mut result = Vec::new();
+    for arg in command.get_arguments().filter( |arg| !arg.is_positional() ) {
+        if matches!( arg.get_action(), clap::ArgAction::Help | clap::ArgAction::HelpShort | clap::ArgAction::HelpLong | clap::ArgAction::Version ) {
+            continue;
+        }
+        let short = arg.get_short().map( |short| format!( "-{}", short ) ).unwrap_or_default();
+        let long = arg.get_long().map( |long| format!( "--{}", long ) ).unwrap_or_default();
+        let help = arg.get_help().map( |help| help.to_string() ).unwrap_or_default();
+        let is_flag = !arg.get_action().takes_values();
+        let default = match arg.get_default_values().first() {
+            Some( value ) => value.to_string_lossy().into_owned(),
+            None if is_flag => "false".to_string(),
+            None => String::new(),
+        };
+        let mut item = OptParseItem::new( &short, &long, !is_flag, &default, &help ).with_name( arg.get_id().as_str() ).with_required( arg.is_required_set() );
+        if arg.is_hide_set() {
+            item = item.with_visibility( crate::Visibility::Advanced );
+        }
+        let choices : Vec<String> = arg.get_possible_values().iter().map( |value| value.get_name().to_string() ).collect();
+        if !is_flag && !choices.is_empty() {
+            let choices : Vec<&str> = choices.iter().map( |choice| choice.as_str() ).collect();
+            item = item.with_choices( &choices );
+        }
+        if let Some( range ) = arg.get_num_args().filter( |range| range.max_values() > 1 ) {
+            item = item.with_value_count( range.min_values(), range.max_values() );
+        }
+        result.push( item );
+    }
+    result
+}
+
+
+// the getopts::Options style declarations. short and long names are without the leading dashes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GetoptsOptions
+{
+    items : Vec<OptParseItem>,
+}
+
+impl GetoptsOptions
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dashed( prefix : &str, name : &str ) -> String {
+        if name.is_empty() { String::new() } else { format!( "{}{}", prefix, name ) }
+    }
+
+    pub fn optflag( &mut self, short_name : &str, long_name : &str, desc : &str ) -> &mut Self {
+        self.items.push( OptParseItem::new( &Self::dashed( "-", short_name ), &Self::dashed( "--", long_name ), false, "false", desc ) );
+        self
+    }
+
+    // the counted flag e.g. -v -v. get the count by occurrences_of()
+    pub fn optflagmulti( &mut self, short_name : &str, long_name : &str, desc : &str ) -> &mut Self {
+        self.optflag( short_name, long_name, desc )
+    }
+
+    // the flag with the optional value e.g. --color or --color=always. it's "" without the value, check it by was_set()
+    pub fn optflagopt( &mut self, short_name : &str, long_name : &str, desc : &str, hint : &str ) -> &mut Self {
+        self.items.push( Self::option( short_name, long_name, desc, hint ).with_missing_value( "" ) );
+        self
+    }
+
+    // getopts has no default value then the hint is only shown in the description
+    pub fn optopt( &mut self, short_name : &str, long_name : &str, desc : &str, hint : &str ) -> &mut Self {
+        self.items.push( Self::option( short_name, long_name, desc, hint ) );
+        self
+    }
+
+    // the option given one or more times e.g. -I a -I b. get the values by get_values()
+    pub fn optmulti( &mut self, short_name : &str, long_name : &str, desc : &str, hint : &str ) -> &mut Self {
+        self.items.push( Self::option( short_name, long_name, desc, hint ).with_multiple( true ) );
+        self
+    }
+
+    // the option which must be given
+    pub fn reqopt( &mut self, short_name : &str, long_name : &str, desc : &str, hint : &str ) -> &mut Self {
+        self.items.push( Self::option( short_name, long_name, desc, hint ).with_required( true ) );
+        self
+    }
+
+    fn option( short_name : &str, long_name : &str, desc : &str, hint : &str ) -> OptParseItem {
+        let desc = if hint.is_empty() { String::from( desc ) } else { format!( "{} ({})", desc, hint ) };
+        OptParseItem::new( &Self::dashed( "-", short_name ), &Self::dashed( "--", long_name ), true, "", &desc )
+    }
+
+    pub fn into_items( self ) -> Vec<OptParseItem> {
+        self.items
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::vec_init_then_push, clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    #[cfg(feature = "clap")]
+    use alloc::vec;
+    use crate::IOptParse;
+    use crate::OptParse;
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_clap_command() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ) );
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_value_count( 1, 3 ).with_required( true ) );
+        options.push( OptParseItem::new( "", "--verbose", false, "false", "Enable verbose mode") );
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        opt_parse.add_positional( "OUTPUT", "Output file" );
+
+        let command = to_clap_command( &opt_parse );
+        assert_eq!( command.get_about().map( |about| about.to_string() ), Some( "rst_opt_parse_test".to_string() ) );
+        let matches = command.clone().try_get_matches_from( [ "mytool", "-s", "44100", "--encoding=PCM24", "-i", "a.wav", "b.wav", "--verbose", "out.wav" ] ).unwrap();
+        assert_eq!( matches.get_one::<String>( "samplingRate" ).map( |value| value.as_str() ), Some( "44100" ) );
+        assert_eq!( matches.get_one::<String>( "encoding" ).map( |value| value.as_str() ), Some( "PCM24" ) );
+        assert_eq!( matches.get_many::<String>( "inputs" ).map( |values| values.cloned().collect::<Vec<_>>() ), Some( vec![ "a.wav".to_string(), "b.wav".to_string() ] ) );
+        assert_eq!( matches.get_flag( "verbose" ), true );
+        assert_eq!( matches.get_one::<String>( "OUTPUT" ).map( |value| value.as_str() ), Some( "out.wav" ) );
+        assert_eq!( command.clone().try_get_matches_from( [ "mytool", "-i", "a.wav", "-e", "PCM8" ] ).is_err(), true );
+        assert_eq!( command.clone().try_get_matches_from( [ "mytool" ] ).is_err(), true );
+
+        assert_eq!( from_clap_command( &command ), options );
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_from_clap_command() {
+        let command = clap::Command::new( "mytool" )
+            .arg( clap::Arg::new( "input" ).required( true ) )
+            .arg( clap::Arg::new( "out" ).short( 'o' ).long( "output" ).required( true ) )
+            .arg( clap::Arg::new( "quiet" ).short( 'q' ).action( clap::ArgAction::SetTrue ).hide( true ) );
+        assert_eq!( from_clap_command( &command ), vec![
+            OptParseItem::new( "-o", "--output", true, "", "" ).with_name( "out" ).with_required( true ),
+            OptParseItem::new( "-q", "", false, "false", "" ).with_name( "quiet" ).with_visibility( crate::Visibility::Advanced ),
+        ] );
+        // -h and --version of the built command are skipped
+        let mut command = command.version( "1.0" );
+        command.build();
+        assert_eq!( from_clap_command( &command ).len(), 2 );
+    }
+
+    #[test]
+    fn test_getopts_options() {
+        let mut opts = GetoptsOptions::new();
+        opts.optopt( "o", "output", "set output file name", "NAME" )
+            .optflag( "v", "", "verbose mode" );
+        let options = opts.into_items();
+        assert_eq!( options[0], OptParseItem::new( "-o", "--output", true, "", "set output file name (NAME)") );
+        assert_eq!( options[1], OptParseItem::new( "-v", "", false, "false", "verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-o".to_string() );
+        argv.push( "out.wav".to_string() );
+        argv.push( "-v".to_string() );
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_options( false ), true );
+        assert_eq!( opt_parse.get_value( "output" ), "out.wav" );
+        assert_eq!( opt_parse.get_value( "-v" ), "true" );
+    }
+
+    #[test]
+    fn test_getopts_options_more() {
+        let mut opts = GetoptsOptions::new();
+        opts.optmulti( "I", "include", "add include directory", "DIR" )
+            .reqopt( "o", "output", "set output file name", "NAME" )
+            .optflagopt( "c", "color", "colorize the output", "WHEN" )
+            .optflagmulti( "v", "verbose", "more output" );
+        let options = opts.into_items();
+        assert_eq!( options[0].is_multiple(), true );
+        assert_eq!( options[1].is_required(), true );
+        assert_eq!( options[2].get_missing_value(), Some( "" ) );
+        assert_eq!( options[3].is_arg_required(), false );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_from_str( "-I inc -o out.wav -I src/inc in.wav -v -v --color", false ), true );
+        assert_eq!( opt_parse.get_values( "include" ), [ "inc", "src/inc" ] );
+        assert_eq!( opt_parse.get_value( "output" ), "out.wav" );
+        assert_eq!( opt_parse.was_set( "color" ), true );
+        assert_eq!( opt_parse.get_value( "color" ), "" );
+        assert_eq!( opt_parse.get_matches().occurrences_of( "verbose" ), 2 );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
+        assert_eq!( opt_parse.to_args(), [ "--include=inc", "--include=src/inc", "--output=out.wav", "--color=", "--verbose", "--verbose", "in.wav" ] );
+
+        assert_eq!( opt_parse.parse_from_str( "-o out.wav --color=always", false ), true );
+        assert_eq!( opt_parse.get_value( "color" ), "always" );
+        assert_eq!( opt_parse.get_values( "include" ).is_empty(), true );
+
+        // the required option
+        assert_eq!( opt_parse.parse_from_str( "-I inc", false ), false );
+    }
+}
@@ -14,17 +14,112 @@
    limitations under the License.
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cmp;
+use core::fmt::Write;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::cmp;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+#[cfg(feature = "std")]
+use std::ffi::OsStr;
+#[cfg(feature = "std")]
+use std::ffi::OsString;
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
-#[derive(Clone)]
+#[cfg(feature = "color")]
+pub mod color;
+pub mod command;
+#[cfg(feature = "completions")]
+pub mod completions;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "datetime")]
+pub mod datetime;
+#[cfg(feature = "docgen")]
+pub mod docgen;
+pub mod env;
+#[cfg(feature = "std")]
+pub mod exec;
+#[cfg(feature = "std")]
+pub mod glob;
+pub mod i18n;
+pub mod interop;
+pub mod prelude;
+pub mod provider;
+#[cfg(feature = "std")]
+pub mod record;
+pub mod shell;
+#[cfg(feature = "std")]
+pub mod tty;
+pub mod units;
+pub mod verbosity;
+#[cfg(feature = "derive")]
+mod macros;
+pub mod spec;
+
+#[cfg(feature = "derive")]
+pub use rst_opt_parse_macros::main;
+
+pub use command::Command;
+use command::CommandRegistry;
+pub use provider::OptionProvider;
+use provider::ProviderRegistry;
+pub use i18n::MessageCatalog;
+pub use i18n::MessageKey;
+pub use i18n::ValueMessage;
+use i18n::CatalogRef;
+
+#[doc(hidden)]
+pub use alloc::string::String as __String;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct OptParseItem
 {
-    option : String,        // e.g. "-h"
-    full_option : String,   // e.g. "--help"
+    name : String,          // e.g. "help", the stable key regardless of the flag spellings
+    option : Option<String>,    // e.g. "-h". None for the long only option
+    full_option : Option<String>,   // e.g. "--help". None for the short only option
     arg_required : bool,    // true: the value required / false: the value not required
     value : String,
     description : String,
+    choices : Vec<String>,  // the possible values. empty for any value
+    is_secret : bool,       // true: the value is masked in the dumps and the errors e.g. the password
+    is_required : bool,     // true: the parse fails when this isn't specified
+    is_password : bool,     // true: secret and asked without the echo when not specified
+    conflicts : Vec<String>,    // the names of the options which can't be specified with this
+    normalizers : Vec<Normalizer>,  // applied in the order to the given value before the validation
+    value_type : ValueType, // the values not of this type fail the parse
+    actions : Vec<OptionAction>,    // run with the value when this is matched
+    group : String,         // the help section e.g. "Audio format options". empty for the ungrouped
+    long_description : String,  // the details shown only in the detailed help e.g. --help
+    value_count : ( usize, usize ), // the min and the max of the values. ( 1, 1 ) for the single value
+    default_from : Option<String>,  // the option whose value is the default of this
+    is_env_only : bool,     // true: no flag and set by the environment variable or the config file
+    visibility : Visibility,    // which help shows this
+    extensions : Vec<String>,   // the accepted extensions of the path value e.g. ".wav". empty for any
+    examples : Vec<String>,     // the usages shown by --help of this option e.g. "mytool -s 44.1kHz in.wav"
+    is_multiple : bool,     // true: each occurrence takes one value and the values are accumulated e.g. -I a -I b
+    missing_value : Option<String>, // the value when the option is given without one. None: the value is required
 }
 
 impl OptParseItem
@@ -37,434 +132,6952 @@ impl OptParseItem
         description : &str    // this is displayed in the help
     ) -> Self
     {
+        let flag = if full_option.is_empty() { option } else { full_option };
         Self {
-            option : option.to_string(),
-            full_option : full_option.to_string(),
-            arg_required : arg_required,
+            name : flag.trim_start_matches( '-' ).to_string(), // e.g. "samplingRate" for "--samplingRate"
+            option : if option.is_empty() { None } else { Some( option.to_string() ) },
+            full_option : if full_option.is_empty() { None } else { Some( full_option.to_string() ) },
+            arg_required,
             value : value.to_string(),
-            description : description.to_string()
+            description : description.to_string(),
+            choices : Vec::new(),
+            is_secret : false,
+            is_required : false,
+            is_password : false,
+            conflicts : Vec::new(),
+            normalizers : Vec::new(),
+            value_type : ValueType::String,
+            actions : Vec::new(),
+            group : String::new(),
+            long_description : String::new(),
+            value_count : ( 1, 1 ),
+            default_from : None,
+            is_env_only : false,
+            visibility : Visibility::Standard,
+            extensions : Vec::new(),
+            examples : Vec::new(),
+            is_multiple : false,
+            missing_value : None
+        }
+    }
+
+    // the entry without the flag set by the environment variable or the config file
+    // e.g. OptParseItem::env_only( "apiToken", "", "API token" ) is set by MYTOOL_API_TOKEN per set_env_prefix( "mytool" )
+    pub fn env_only( name : &str, value : &str, description : &str ) -> Self {
+        let mut result = Self::new( "", "", true, value, description ).with_name( name );
+        result.is_env_only = true;
+        result
+    }
+
+    // the option without the short form e.g. OptParseItem::long_only( "--dry-run", false, "false", "Show what would be done" )
+    pub fn long_only( full_option : &str, arg_required : bool, value : &str, description : &str ) -> Self {
+        Self::new( "", full_option, arg_required, value, description )
+    }
+
+    // the option without the long form e.g. OptParseItem::short_only( "-1", false, "false", "Mix down to mono" )
+    pub fn short_only( option : &str, arg_required : bool, value : &str, description : &str ) -> Self {
+        Self::new( option, "", arg_required, value, description )
+    }
+
+    // validate the value as the type at the parse time e.g. ValueType::DateTime
+    pub fn with_value_type( mut self, value_type : ValueType ) -> Self {
+        self.value_type = value_type;
+        self
+    }
+
+    // the repeatable option whose values are ORed into the bitmask e.g. .with_flags( &[ ( "resample", 0x1 ), ( "dither", 0x2 ) ] )
+    // for --feature=resample --feature=dither or --feature=resample,dither. get the bitmask by get_flags()
+    pub fn with_flags( mut self, flags : &[( &str, u64 )] ) -> Self {
+        self.value_type = ValueType::Flags( flags.iter().map( |( name, bit )| ( name.to_string(), *bit ) ).collect() );
+        self
+    }
+
+    // take the following args as the values until the next option, "--" or the terminator of set_value_terminator() e.g.
+    // --inputs a.wav b.wav ';' -o out.wav. get the values by get_values()
+    pub fn with_greedy_values( self ) -> Self {
+        self.with_value_count( 1, usize::MAX )
+    }
+
+    // take min to max values e.g. .with_value_count( 1, 3 ) for up to 3 paths, .with_value_count( 2, 2 ) for exactly 2.
+    // the args after the max are the args with add_positional(), too many values otherwise. the parse fails when the values of the option are fewer than min or more than max in total
+    pub fn with_value_count( mut self, min : usize, max : usize ) -> Self {
+        let min = cmp::max( min, 1 );
+        self.value_count = ( min, cmp::max( min, max ) );
+        self
+    }
+
+    // the repeated option collects one value per occurrence e.g. -I include -I src/include. get the values by get_values()
+    pub fn with_multiple( mut self, is_multiple : bool ) -> Self {
+        self.is_multiple = is_multiple;
+        self
+    }
+
+    // the value can be omitted e.g. --color for --color=auto with .with_missing_value( "auto" ). the following arg is still the value unless it's an option
+    pub fn with_missing_value( mut self, value : &str ) -> Self {
+        self.missing_value = Some( value.to_string() );
+        self
+    }
+
+    // default to the value of the other option e.g. --outputRate defaults to --samplingRate. the chain is followed after the parse,
+    // and the cycle fails the parse with OptParseError::DefaultCycle
+    pub fn with_default_from( mut self, option : &str ) -> Self {
+        self.default_from = Some( option.to_string() );
+        self
+    }
+
+    // the details shown below the description only in the detailed help by --help, get_long_help()
+    pub fn with_long_description( mut self, long_description : &str ) -> Self {
+        self.long_description = long_description.to_string();
+        self
+    }
+
+    // shown under the section of the group in the help, see OptionGroup
+    pub fn with_group( mut self, group : &str ) -> Self {
+        self.group = group.to_string();
+        self
+    }
+
+    // run the closure with the value whenever this is matched in the args e.g. .with_action( |_| print_license() )
+    pub fn with_action( mut self, action : impl Fn( &str ) + Send + Sync + 'static ) -> Self {
+        self.actions.push( OptionAction( Arc::new( action ) ) );
+        self
+    }
+
+    // convert and validate the value by the closure e.g. .with_parser( |value| parse_ratio( value ) ). Err is why it's invalid. get it by get_parsed()
+    pub fn with_parser<T : Any + Send + Sync>( mut self, parser : impl Fn( &str ) -> Result<T, String> + Send + Sync + 'static ) -> Self {
+        self.value_type = ValueType::Custom( ValueParser( Arc::new( move |value| parser( value ).map( |value| CustomValue( Arc::new( value ) ) ) ) ) );
+        self
+    }
+
+    // clean up the given value e.g. .with_normalizer( Normalizer::Trim ).with_normalizer( Normalizer::Synonyms( vec![ ( "cd".to_string(), "44100".to_string() ) ] ) )
+    pub fn with_normalizer( mut self, normalizer : Normalizer ) -> Self {
+        self.normalizers.push( normalizer );
+        self
+    }
+
+    // the parse fails when this and one of these options are specified together e.g. --verbose and --quiet
+    pub fn with_conflicts( mut self, names : &[&str] ) -> Self {
+        self.conflicts = names.iter().map( |name| name.to_string() ).collect();
+        self
+    }
+
+    // the secret value asked without the echo when it's not specified, so that it needn't be in the shell history
+    pub fn with_password( mut self, is_password : bool ) -> Self {
+        self.is_password = is_password;
+        self.is_secret = self.is_secret || is_password;
+        self
+    }
+
+    // the option must be specified in the args (or answered to the prompt, see set_prompt_missing())
+    pub fn with_required( mut self, is_required : bool ) -> Self {
+        self.is_required = is_required;
+        self
+    }
+
+    // mask the value in to_json(), to_command_line(), the records and the errors. get_value() still returns it
+    pub fn with_secret( mut self, is_secret : bool ) -> Self {
+        self.is_secret = is_secret;
+        self
+    }
+
+    // restrict the value to one of these e.g. [ "PCM8", "PCM16" ]
+    pub fn with_choices( mut self, choices : &[&str] ) -> Self {
+        self.choices = choices.iter().map( |choice| choice.to_string() ).collect();
+        self
+    }
+
+    // restrict the value to the ValueEnum's possible values
+    pub fn with_value_enum<E : ValueEnum>( self ) -> Self {
+        self.with_choices( E::possible_values() )
+    }
+
+    // override the canonical name e.g. "sampling_rate"
+    pub fn with_name( mut self, name : &str ) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn get_name( &self ) -> &str {
+        &self.name
+    }
+
+    pub fn is_env_only( &self ) -> bool {
+        self.is_env_only
+    }
+
+    // Visibility::Advanced is shown only by --help-all and Visibility::Internal is never shown
+    pub fn with_visibility( mut self, visibility : Visibility ) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn get_visibility( &self ) -> Visibility {
+        self.visibility
+    }
+
+    // the path value must have one of the extensions e.g. with_extensions( &[ ".pcm", ".wav" ] ). the case is ignored
+    pub fn with_extensions( mut self, extensions : &[&str] ) -> Self {
+        self.extensions = to_extensions( extensions );
+        self
+    }
+
+    pub fn get_extensions( &self ) -> &[String] {
+        &self.extensions
+    }
+
+    // shown by the help of this option e.g. --help samplingRate, see get_option_help()
+    pub fn with_examples( mut self, examples : &[&str] ) -> Self {
+        self.examples = examples.iter().map( |example| example.to_string() ).collect();
+        self
+    }
+
+    pub fn get_examples( &self ) -> &[String] {
+        &self.examples
+    }
+
+    pub fn get_option( &self ) -> &str {
+        self.option.as_deref().unwrap_or( "" )
+    }
+
+    pub fn get_full_option( &self ) -> &str {
+        self.full_option.as_deref().unwrap_or( "" )
+    }
+
+    pub fn is_arg_required( &self ) -> bool {
+        self.arg_required
+    }
+
+    pub fn get_default_value( &self ) -> &str {
+        &self.value
+    }
+
+    pub fn get_description( &self ) -> &str {
+        &self.description
+    }
+
+    pub fn get_choices( &self ) -> &[String] {
+        &self.choices
+    }
+
+    pub fn is_secret( &self ) -> bool {
+        self.is_secret
+    }
+
+    pub fn is_required( &self ) -> bool {
+        self.is_required
+    }
+
+    pub fn is_password( &self ) -> bool {
+        self.is_password
+    }
+
+    pub fn get_conflicts( &self ) -> &[String] {
+        &self.conflicts
+    }
+
+    pub fn get_normalizers( &self ) -> &[Normalizer] {
+        &self.normalizers
+    }
+
+    pub fn get_value_type( &self ) -> &ValueType {
+        &self.value_type
+    }
+
+    pub fn get_group( &self ) -> &str {
+        &self.group
+    }
+
+    pub fn get_long_description( &self ) -> &str {
+        &self.long_description
+    }
+
+    // true: the option takes more than one value
+    pub fn is_greedy( &self ) -> bool {
+        self.value_count.1 > 1
+    }
+
+    pub fn get_value_count( &self ) -> ( usize, usize ) {
+        self.value_count
+    }
+
+    pub fn is_multiple( &self ) -> bool {
+        self.is_multiple
+    }
+
+    // true: the values of the occurrences are accumulated, by the greedy values or with_multiple()
+    pub fn is_accumulated( &self ) -> bool {
+        self.is_greedy() || self.is_multiple
+    }
+
+    // None if the value is required
+    pub fn get_missing_value( &self ) -> Option<&str> {
+        self.missing_value.as_deref()
+    }
+
+    // "" if the default isn't from the other option
+    pub fn get_default_from( &self ) -> &str {
+        self.default_from.as_deref().unwrap_or( "" )
+    }
+
+    // the bitmask of the comma separated flag names. None if any of them is unknown
+    fn to_flags( &self, value : &str ) -> Option<u64> {
+        self.value_type.to_flags( value )
+    }
+
+    fn normalize<'a>( &self, value : &'a str ) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed( value );
+        for normalizer in &self.normalizers {
+            result = match normalizer {
+                Normalizer::Trim => match result {
+                    Cow::Borrowed( value ) => Cow::Borrowed( value.trim() ),
+                    Cow::Owned( value ) => Cow::Owned( value.trim().to_string() ),
+                },
+                Normalizer::Lowercase => Cow::Owned( result.to_lowercase() ),
+                Normalizer::Uppercase => Cow::Owned( result.to_uppercase() ),
+                Normalizer::Synonyms( synonyms ) => match synonyms.iter().find( |( synonym, _ )| *synonym == result ) {
+                    Some( ( _, value ) ) => Cow::Owned( value.clone() ),
+                    None => result,
+                },
+                #[cfg(feature = "std")]
+                Normalizer::ExpandTilde => match env::expand_tilde( &result ) {
+                    Cow::Borrowed( _ ) => result,
+                    Cow::Owned( value ) => Cow::Owned( value ),
+                },
+                #[cfg(not(feature = "std"))]
+                Normalizer::ExpandTilde => result,
+            };
+        }
+        result
+    }
+
+    fn is_valid_value( &self, value : &str ) -> bool {
+        self.convert( value ).is_ok()
+    }
+
+    // the value converted per the value type, None for the types not cached e.g. ValueType::String. Err is why it's invalid
+    fn convert( &self, value : &str ) -> Result<Option<TypedValue>, ValueMessage> {
+        if !self.choices.is_empty() && !self.choices.iter().any( |choice| choice == value ) {
+            return Err( ValueMessage::new( MessageKey::NotOneOf, &[ ( "choices", self.choices.join( ", " ) ) ] ) );
         }
+        if !value.is_empty() {
+            check_extension( value, &self.extensions )?;
+        }
+        self.value_type.convert( value )
     }
 }
 
+// the public fields of OptParseItem for the struct update syntax e.g.
+//   OptParseItem::from( OptParseItemSpec { full_option : "--samplingRate".to_string(), arg_required : true, is_required : true, ..Default::default() } )
+// the rest are set by the with_*() of the result
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptParseItemSpec
+{
+    pub name : String,          // the canonical name. empty for the one from the flag e.g. "samplingRate" for "--samplingRate"
+    pub option : String,        // e.g. "-s". empty for the long only option
+    pub full_option : String,   // e.g. "--samplingRate". empty for the short only option
+    pub arg_required : bool,    // true: the value required / false: the flag
+    pub value : String,         // the default value. "false" is usual for the flag
+    pub description : String,
+    pub choices : Vec<String>,  // the possible values. empty for any value
+    pub is_required : bool,
+    pub is_secret : bool,
+    pub value_type : ValueType,
+    pub group : String,         // the help section. empty for the ungrouped
+    pub visibility : Visibility,
+}
 
-pub trait IOptParse
+impl From<OptParseItemSpec> for OptParseItem
 {
-    fn new( args : Vec<String>, options : Vec<OptParseItem>, description : &str ) -> Self;
-    fn parse_options_with_required_args( &mut self, is_finish_if_help : bool, num_of_min_required_args : i32, num_of_max_required_args : i32 ) -> bool;
-    fn parse_options( &mut self, is_finish_if_help : bool ) -> bool;
-    fn parse_option( &mut self, option : &OptParseItem ) -> bool;
-    fn print_help( &self );
-    fn get_value( &self, option : &str ) -> String;
-    fn get_args_count( &self ) -> usize;
-    fn get_args(&self, index : usize ) -> String;
+    fn from( spec : OptParseItemSpec ) -> Self {
+        let mut result = OptParseItem::new( &spec.option, &spec.full_option, spec.arg_required, &spec.value, &spec.description );
+        if !spec.name.is_empty() {
+            result.name = spec.name;
+        }
+        result.choices = spec.choices;
+        result.is_required = spec.is_required;
+        result.is_secret = spec.is_secret;
+        result.value_type = spec.value_type;
+        result.group = spec.group;
+        result.visibility = spec.visibility;
+        result
+    }
 }
 
-pub struct OptParse
+
+// the type of the value, see OptParseItem::with_value_type(). get the typed value by get_value_as()
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ValueType
 {
-    args : Vec<String>,
-    options : Vec<OptParseItem>,
-    values : HashMap<String, String>,
-    alias : HashMap<String, String>,
-    arg_values : Vec<String>,
-    description : String,
+    #[default]
+    String,         // any value
+    Integer,        // i64, get it by get_int()
+    Float,          // f64, get it by get_float()
+    Bool,           // true/false, yes/no, on/off or 1/0, get it by get_bool()
+    #[cfg(feature = "std")]
+    Path,           // get it by get_typed()
+    #[cfg(feature = "datetime")]
+    DateTime,       // datetime::DateTime e.g. 2022-10-01T12:30:00Z
+    #[cfg(feature = "std")]
+    Glob( glob::NoMatch ),  // the glob pattern e.g. '*.pcm'. get the matched paths by get_paths()
+    Flags( Vec<( String, u64 )> ),  // the flag names and their bits, see OptParseItem::with_flags()
+    Unit( units::UnitTable ),   // the number with the suffix e.g. 44.1kHz in the base unit, get it by get_float()
+    Custom( ValueParser ),      // converted by the closure, see OptParseItem::with_parser()
 }
 
-impl IOptParse for OptParse
+impl ValueType
 {
-    fn new( args : Vec<String>, options : Vec<OptParseItem>, description : &str ) -> Self {
+    // the bits of the comma separated flag names e.g. read,write. None for the unknown flag or the other types
+    fn to_flags( &self, value : &str ) -> Option<u64> {
+        let ValueType::Flags( flags ) = self else { return None; };
+        value.split( ',' ).filter( |name| !name.is_empty() ).try_fold( 0, |result, name| {
+            flags.iter().find( |( flag, _ )| flag == name ).map( |( _, bit )| result | bit )
+        })
+    }
+
+    // the value converted per the type regardless of the choices and the extensions of the option
+    fn convert( &self, value : &str ) -> Result<Option<TypedValue>, ValueMessage> {
+        match self {
+            ValueType::String => Ok( None ),
+            ValueType::Integer => value.parse().map( |value| Some( TypedValue::Integer( value ) ) ).map_err( |_| ValueMessage::new( MessageKey::NotInteger, &[] ) ),
+            ValueType::Float => value.parse().map( |value| Some( TypedValue::Float( value ) ) ).map_err( |_| ValueMessage::new( MessageKey::NotNumber, &[] ) ),
+            ValueType::Bool => match value {
+                "true" | "yes" | "on" | "1" => Ok( Some( TypedValue::Bool( true ) ) ),
+                "false" | "no" | "off" | "0" => Ok( Some( TypedValue::Bool( false ) ) ),
+                _ => Err( ValueMessage::new( MessageKey::NotBool, &[] ) ),
+            },
+            #[cfg(feature = "std")]
+            ValueType::Path => Ok( Some( TypedValue::Path( PathBuf::from( value ) ) ) ),
+            #[cfg(feature = "datetime")]
+            ValueType::DateTime => value.parse::<datetime::DateTime>().map( |_| None ),
+            #[cfg(feature = "std")]
+            ValueType::Glob( no_match ) => glob::expand( value, *no_match ).map( |_| None ),
+            ValueType::Flags( _ ) => self.to_flags( value ).map( |_| None ).ok_or_else( || ValueMessage::new( MessageKey::UnknownFlag, &[] ) ),
+            ValueType::Unit( units ) => units.parse( value ).map( |value| Some( TypedValue::Float( value ) ) ),
+            ValueType::Custom( parser ) => ( parser.0 )( value ).map( |value| Some( TypedValue::Custom( value ) ) ).map_err( ValueMessage::Text ),
+        }
+    }
+}
+
+
+// the option or the arg in the order of the args, see get_ordered_matches()
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchEvent
+{
+    Option { name : String, value : String, position : usize },  // the value of the option. each value of the greedy option is one
+    Arg { value : String, position : usize },   // the arg of get_args()
+}
+
+
+// the value converted once at the parse per ValueType, see Matches::get_typed()
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypedValue
+{
+    Integer( i64 ),
+    Float( f64 ),
+    Bool( bool ),
+    #[cfg(feature = "std")]
+    Path( PathBuf ),
+    Custom( CustomValue ),  // get it by get_parsed()
+}
+
+
+type ValueParserFn = dyn Fn( &str ) -> Result<CustomValue, String> + Send + Sync;
+
+// the converter of OptParseItem::with_parser()
+#[derive(Clone)]
+pub struct ValueParser( Arc<ValueParserFn> );
+
+impl core::fmt::Debug for ValueParser
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "ValueParser" )
+    }
+}
+
+// the closures can't be compared then only the same closure is the same
+impl PartialEq for ValueParser
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+// the value of the user type converted by ValueParser
+#[derive(Clone)]
+pub struct CustomValue( Arc<dyn Any + Send + Sync> );
+
+impl CustomValue
+{
+    // None for the other type than the parser's
+    pub fn downcast_ref<T : Any>( &self ) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+}
+
+impl core::fmt::Debug for CustomValue
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "CustomValue" )
+    }
+}
+
+// the values can't be compared then only the same value is the same
+impl PartialEq for CustomValue
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+
+// "wav" and ".wav" are ".wav"
+fn to_extensions( extensions : &[&str] ) -> Vec<String> {
+    extensions.iter().map( |extension| format!( ".{}", extension.trim_start_matches( '.' ) ) ).collect()
+}
+
+// Err is why the path doesn't have any of the extensions. the empty extensions accept any
+fn check_extension( path : &str, extensions : &[String] ) -> Result<(), ValueMessage> {
+    let path = path.to_ascii_lowercase();
+    let Some( ( last, rest ) ) = extensions.split_last() else { return Ok( () ); };
+    if extensions.iter().any( |extension| path.ends_with( &extension.to_ascii_lowercase() ) ) {
+        return Ok( () );
+    }
+    let extensions = match rest.is_empty() {
+        true => last.clone(),
+        false => format!( "{} or {}", rest.join( ", " ), last ),
+    };
+    Err( ValueMessage::new( MessageKey::NotExtension, &[ ( "extensions", extensions ) ] ) )
+}
+
+
+// which help shows the option, see OptParseItem::with_visibility()
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility
+{
+    #[default]
+    Standard,       // -h and --help
+    Advanced,       // --help-all
+    Internal,       // none of the helps nor the documents, e.g. the debug switches
+}
+
+
+// how the long options take the values, see set_long_value_syntax()
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LongValueSyntax
+{
+    #[default]
+    Both,           // --name=value and --name value
+    EqualsOnly,     // --name=value. --name value is the missing value and the arg
+    SeparateOnly,   // --name value. --name=value is the missing value
+}
+
+
+// the help in the columns, see set_help_layout() e.g. HelpLayout { gap : 4, ..Default::default() }
+#[derive(Clone, Debug, PartialEq)]
+pub struct HelpLayout
+{
+    pub indent : usize,             // the spaces before the flags
+    pub gap : usize,                // the spaces between the flags and the descriptions
+    pub max_option_width : usize,   // the wider flags have the description on the next line. 0 for no limit
+    pub width : usize,              // the descriptions are wrapped at this column. 0 for the terminal's width, see set_terminal_width()
+}
+
+impl Default for HelpLayout
+{
+    fn default() -> Self {
         Self {
-            args : args,
-            options : options,
-            values : HashMap::new(),
-            alias : HashMap::new(),
-            arg_values : Vec::new(),
-            description : description.to_string(),
+            indent : 2,
+            gap : 2,
+            max_option_width : 24,
+            width : 0,
         }
     }
+}
 
-    fn parse_options_with_required_args( &mut self, is_finish_if_help : bool, num_of_min_required_args : i32, num_of_max_required_args : i32 ) -> bool {
-        let mut result = self.parse_options( is_finish_if_help );
-        let current_num_of_args : i32 = ( self.get_args_count() - 1 ).try_into().unwrap();
 
-        if ( current_num_of_args < num_of_min_required_args ) || ( ( num_of_max_required_args >= 0 ) && ( current_num_of_args > num_of_max_required_args ) ) {
-            result = false;
-            self.print_help();
-            if is_finish_if_help == true {
-                std::process::exit(0);
-            }
+// the bundle of the related options defined once and added to many binaries by add_group(), shown under its own help section e.g.
+//   fn audio_format_options() -> OptionGroup {
+//       OptionGroup::new( "Audio format options", vec![ OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate"), ... ] )
+//   }
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionGroup
+{
+    title : String,
+    options : Vec<OptParseItem>,
+}
+
+impl OptionGroup
+{
+    pub fn new( title : &str, options : Vec<OptParseItem> ) -> Self {
+        Self {
+            title : title.to_string(),
+            options,
         }
+    }
 
-        result
+    pub fn get_title( &self ) -> &str {
+        &self.title
+    }
+
+    pub fn get_options( &self ) -> &[OptParseItem] {
+        &self.options
+    }
+}
+
+
+// the closure run when the option is matched, see OptParseItem::with_action()
+#[derive(Clone)]
+pub struct OptionAction( Arc<dyn Fn( &str ) + Send + Sync> );
+
+impl core::fmt::Debug for OptionAction
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "OptionAction" )
+    }
+}
+
+// the closures can't be compared then only the same closure is the same
+impl PartialEq for OptionAction
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+
+// how to_args_with() gives the secret values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SecretArgs
+{
+    Plain,      // as is to parse them again
+    Masked,     // "********" to show them
+    Excluded,   // left out e.g. for the record to replay
+}
+
+
+// the rewrite of the args before the parse, see add_preprocessor()
+#[derive(Clone)]
+struct Preprocessor( Arc<dyn Fn( Vec<String> ) -> Vec<String> + Send + Sync> );
+
+impl core::fmt::Debug for Preprocessor
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "Preprocessor" )
+    }
+}
+
+impl PartialEq for Preprocessor
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+
+type MiddlewareFn = dyn Fn( &mut Matches ) -> Result<(), OptParseError> + Send + Sync;
+
+// the hook run after the parse, see add_middleware()
+#[derive(Clone)]
+struct Middleware( Arc<MiddlewareFn> );
+
+impl core::fmt::Debug for Middleware
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( "Middleware" )
+    }
+}
+
+impl PartialEq for Middleware
+{
+    fn eq( &self, other : &Self ) -> bool {
+        Arc::ptr_eq( &self.0, &other.0 )
+    }
+}
+
+
+// the clean up of the given value, see OptParseItem::with_normalizer()
+#[derive(Clone, Debug, PartialEq)]
+pub enum Normalizer
+{
+    Trim,                                   // the surrounding white spaces
+    Lowercase,
+    Uppercase,
+    Synonyms( Vec<( String, String )> ),    // ( synonym, value ) e.g. ( "cd", "44100" )
+    ExpandTilde,                            // ~/out.pcm for the path when the shell didn't expand it. only with std
+}
+
+
+// the enum used as the option's value. value_enum! (the derive feature) implements this and FromStr, Display
+pub trait ValueEnum : Sized
+{
+    fn possible_values() -> &'static [&'static str];
+    fn from_value( value : &str ) -> Option<Self>;
+    fn as_value( &self ) -> &'static str;
+
+    fn parse_value( value : &str ) -> Result<Self, String> {
+        Self::from_value( value ).ok_or_else( || format!( "expected one of {}", Self::possible_values().join( ", " ) ) )
+    }
+}
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ValueSource
+{
+    #[default]
+    Default,        // the value of the OptParseItem
+    CommandLine,    // specified in the args
+    Prompt,         // answered to the prompt for the missing required option
+    Derived,        // set by the middleware after the parse, see add_middleware()
+    Profile,        // from the preset selected by --profile, see add_profile()
+    Environment,    // the environment variable of the env only entry, see OptParseItem::env_only()
+    Config,         // from the config file, see add_config_from_str()
+}
+
+impl ValueSource
+{
+    // e.g. "command line" for the dumps and the diffs
+    pub fn label( &self ) -> &'static str {
+        match self {
+            ValueSource::Default => "default",
+            ValueSource::CommandLine => "command line",
+            ValueSource::Prompt => "prompt",
+            ValueSource::Derived => "derived",
+            ValueSource::Profile => "profile",
+            ValueSource::Environment => "environment",
+            ValueSource::Config => "config",
+        }
+    }
+}
+
+
+// the option whose value differs between the two results, see Matches::diff()
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueDiff
+{
+    pub name : String,
+    pub before : Option<( String, ValueSource )>,  // the value and its source. None: not in the former
+    pub after : Option<( String, ValueSource )>,   // None: not in the latter
+}
+
+// e.g. "samplingRate: 48000 (default) -> 44100 (command line)"
+impl core::fmt::Display for ValueDiff
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        let side = |value : &Option<( String, ValueSource )>| match value {
+            Some( ( value, source ) ) => format!( "{} ({})", shell::quote( value ), source.label() ),
+            None => "(none)".to_string(),
+        };
+        write!( f, "{}: {} -> {}", self.name, side( &self.before ), side( &self.after ) )
+    }
+}
+
+// the ( name, value, source ) differing in the values in the order of the former, then the ones only in the latter
+pub fn diff_values<'a, 'b>( before : impl IntoIterator<Item = ( &'a str, &'a str, &'a ValueSource )>, after : impl IntoIterator<Item = ( &'b str, &'b str, &'b ValueSource )> ) -> Vec<ValueDiff> {
+    let after : Vec<_> = after.into_iter().collect();
+    let mut result = Vec::new();
+    let mut names = HashSet::new();
+    for ( name, value, source ) in before {
+        names.insert( name );
+        let latter = after.iter().find( |( other, _, _ )| *other == name );
+        if latter.is_some_and( |( _, other, _ )| *other == value ) {
+            continue;
+        }
+        result.push( ValueDiff { name : name.to_string(), before : Some( ( value.to_string(), source.clone() ) ), after : latter.map( |( _, value, source )| ( value.to_string(), ( *source ).clone() ) ) } );
+    }
+    for ( name, value, source ) in after.iter().filter( |( name, _, _ )| !names.contains( name ) ) {
+        result.push( ValueDiff { name : name.to_string(), before : None, after : Some( ( value.to_string(), ( *source ).clone() ) ) } );
+    }
+    result
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptParseError
+{
+    InvalidSpec { line : usize, message : String },    // the declarative spec can't be read
+    UnknownOption { name : String },                    // no such option in the OptParseItems
+    InvalidValue { name : String, value : String, message : ValueMessage },    // the value can't be converted to the type
+    InvalidArgs,                                        // the args don't match the options e.g. missing value
+    MissingCommand,                                     // no subcommand in the args
+    UnknownCommand { name : String, suggestion : Option<String>, available : Vec<String> },    // no such subcommand registered
+    CommandFailed { name : String, message : String },  // Command::run() failed
+    UnterminatedQuote,                                  // the quote isn't closed in the shell style string
+    InvalidRecord { line : usize, message : String },  // the invocation record can't be read
+    DefaultCycle { names : Vec<String> },               // the options default to each other by with_default_from()
+    MissingValue { name : String },                     // the option requiring the value is the last arg
+    MissingOption { name : String },                    // the required option isn't specified
+    ConflictingOptions { name : String, other : String },  // the options of with_conflicts() or add_required_exactly_one_of() are specified together
+    MissingOneOf { names : Vec<String> },               // none of the options of add_required_one_of() is specified
+    WithOrigin { error : Box<OptParseError>, origin : String },    // the error of the value from e.g. "env MYTOOL_SAMPLING_RATE" or "config file ~/.mytool.toml"
+}
+
+impl core::fmt::Display for OptParseError
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( &self.localize( &i18n::DefaultCatalog ) )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OptParseError {}
+
+
+// builds the user's configuration struct from the parsed results e.g.
+//   impl FromOptParse for Config {
+//       fn from_matches( matches : &Matches ) -> Result<Self, OptParseError> {
+//           Ok( Config { sampling_rate : matches.get_value_as( "samplingRate" )?, verbose : matches.get_value_as( "verbose" )? } )
+//       }
+//   }
+pub trait FromOptParse : Sized
+{
+    fn from_matches( matches : &Matches ) -> Result<Self, OptParseError>;
+
+    // the options to parse the args with, used by parse_from() and run_main()
+    fn options() -> Vec<OptParseItem> {
+        Vec::new()
+    }
+
+    fn description() -> String {
+        String::new()
+    }
+
+    // parse the args (without the program name) with options(). -h prints the help and exits
+    fn parse_from<I : IntoIterator<Item = String>>( args : I ) -> Result<Self, OptParseError> {
+        let mut opt_parse = OptParse::new( Vec::new(), Self::options(), &Self::description() );
+        if !opt_parse.parse_options_from_iter( args, true ) {
+            return Err( OptParseError::InvalidArgs );
+        }
+        opt_parse.extract()
+    }
+}
+
+#[cfg(feature = "std")]
+// parse the process args into T and call main with it. on the error, print it with the help and exit with 2
+// this is what #[main] expands to
+pub fn run_main<T : FromOptParse, R>( main : impl FnOnce( T ) -> R ) -> R {
+    match T::parse_from( std::env::args().skip( 1 ) ) {
+        Ok( opts ) => main( opts ),
+        Err( err ) => {
+            let opt_parse = OptParse::new( Vec::new(), T::options(), &T::description() );
+            eprintln!( "{}", opt_parse.styled_error( &err ) );
+            eprint!( "{}", opt_parse.styled_help( opt_parse.get_help(), true ) );
+            std::process::exit( 2 );
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+// the three line main: parse the process args without the program name. -h prints the help and exits,
+// and the invalid args print the errors with the help and exit with 2. get the typed values by e.g. get_int()
+pub fn parse( options : Vec<OptParseItem>, description : &str ) -> Matches {
+    parse_args_or_exit( std::env::args().skip( 1 ), options, description, None )
+}
+
+#[cfg(feature = "std")]
+// parse() with --version e.g. parse_with_version( options, "mytool", env!( "CARGO_PKG_VERSION" ) )
+pub fn parse_with_version( options : Vec<OptParseItem>, description : &str, version : &str ) -> Matches {
+    parse_args_or_exit( std::env::args().skip( 1 ), options, description, Some( version ) )
+}
+
+#[cfg(feature = "std")]
+fn parse_args_or_exit<I : IntoIterator<Item = String>>( args : I, options : Vec<OptParseItem>, description : &str, version : Option<&str> ) -> Matches {
+    let mut opt_parse = OptParse::new( Vec::new(), options, description );
+    if let Some( version ) = version {
+        opt_parse.set_version( version );
+    }
+    if !opt_parse.parse_options_from_iter( args, true ) {
+        match opt_parse.get_violations() {
+            [] => eprintln!( "{}", opt_parse.styled_error( &OptParseError::InvalidArgs ) ),
+            violations => violations.iter().for_each( |violation| eprintln!( "{}", opt_parse.styled_error( violation ) ) ),
+        }
+        eprint!( "{}", opt_parse.styled_help( opt_parse.get_help(), true ) );
+        exit_process( 2 );
+    }
+    opt_parse.into_matches()
+}
+
+
+pub trait IOptParse
+{
+    fn new( args : Vec<String>, options : Vec<OptParseItem>, description : &str ) -> Self;
+    fn parse_options_with_required_args( &mut self, is_finish_if_help : bool, num_of_min_required_args : i32, num_of_max_required_args : i32 ) -> bool;
+    fn parse_options( &mut self, is_finish_if_help : bool ) -> bool;
+    fn parse_option( &mut self, option : &OptParseItem ) -> bool;
+    fn parse_early<S : AsRef<str>>( &self, args : &[S], options : &[OptParseItem] ) -> Matches;
+    fn parse_options_from_iter<I : IntoIterator<Item = String>>( &mut self, args : I, is_finish_if_help : bool ) -> bool;
+    fn parse_from_str( &mut self, line : &str, is_finish_if_help : bool ) -> bool;
+    #[cfg(feature = "std")]
+    fn parse_options_os<I : IntoIterator<Item = OsString>>( &mut self, args : I, is_finish_if_help : bool ) -> bool;
+    fn parse_options_borrowed<'a, S : AsRef<str>>( &'a self, args : &'a [S] ) -> BorrowedMatches<'a>;
+    fn take_args( &mut self ) -> Vec<String>;
+    fn reset( &mut self );
+    fn parse_again( &mut self, args : Vec<String>, is_finish_if_help : bool ) -> bool;
+    #[cfg(feature = "std")]
+    fn print_help( &self );
+    #[cfg(feature = "std")]
+    fn write_help<W : std::io::Write>( &self, writer : &mut W ) -> std::io::Result<()>;
+    fn set_auto_help( &mut self, enable : bool );
+    fn set_help_layout( &mut self, layout : HelpLayout );
+    fn set_terminal_width( &mut self, width : usize );
+    fn get_terminal_width( &self ) -> usize;
+    fn set_help_sections( &mut self, enable : bool );
+    fn set_version( &mut self, version : &str );
+    fn get_version( &self ) -> Option<&str>;
+    fn get_command_version( &self, name : &str ) -> Option<&str>;
+    fn add_positional( &mut self, name : &str, description : &str );
+    fn add_positional_with_extensions( &mut self, name : &str, description : &str, extensions : &[&str] );
+    fn get_help( &self ) -> String;
+    fn get_long_help( &self ) -> String;
+    fn get_help_all( &self ) -> String;
+    fn get_option_help( &self, option : &str ) -> Option<String>;
+    fn is_help_requested( &self ) -> bool;
+    fn get_options( &self ) -> &[OptParseItem];
+    fn get_option_item( &self, option : &str ) -> Option<&OptParseItem>;
+    fn get_description( &self ) -> &str;
+    fn set_auto_short_option( &mut self, enable : bool );
+    fn set_normalize_full_option( &mut self, enable : bool );
+    fn set_ignore_case_full_option( &mut self, enable : bool );
+    fn set_windows_style_option( &mut self, enable : bool );
+    fn set_posixly_correct( &mut self, enable : bool );
+    fn set_prompt_missing( &mut self, enable : bool );
+    fn set_strip_quotes( &mut self, enable : bool );
+    fn set_expand_tilde_args( &mut self, enable : bool );
+    fn set_interpolate_env( &mut self, enable : bool );
+    fn set_plus_toggle_option( &mut self, enable : bool );
+    fn set_single_dash_long_option( &mut self, enable : bool );
+    fn set_colon_value_separator( &mut self, enable : bool );
+    fn set_value_terminator( &mut self, terminator : &str );
+    fn set_long_value_syntax( &mut self, syntax : LongValueSyntax );
+    fn add_renamed_option( &mut self, old : &str, new : &str );
+    fn add_required_one_of( &mut self, names : &[&str] );
+    fn add_required_exactly_one_of( &mut self, names : &[&str] );
+    fn get_required_groups( &self ) -> &[( Vec<String>, bool )];
+    fn set_env_prefix( &mut self, prefix : &str );
+    fn get_env_var( &self, option : &str ) -> Option<String>;
+    #[cfg(feature = "config")]
+    fn add_config_from_str( &mut self, text : &str ) -> Result<(), OptParseError>;
+    #[cfg(feature = "config")]
+    fn add_config_from_source( &mut self, text : &str, path : &str ) -> Result<(), OptParseError>;
+    fn get_warnings( &self ) -> Vec<String>;
+    #[cfg(feature = "std")]
+    fn prompt_missing<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W ) -> std::io::Result<()>;
+    fn add_yes_option( &mut self );
+    #[cfg(feature = "color")]
+    fn add_color_option( &mut self );
+    #[cfg(feature = "color")]
+    fn get_color_choice( &self ) -> color::ColorChoice;
+    fn add_verbosity_options( &mut self );
+    fn get_verbosity( &self ) -> verbosity::LevelFilter;
+    fn add_no_input_option( &mut self );
+    fn add_print_defaults_option( &mut self );
+    fn get_defaults( &self ) -> String;
+    fn add_dump_args_option( &mut self );
+    fn set_exit_after_dump_args( &mut self, enable : bool );
+    fn get_dump_args( &self ) -> String;
+    fn add_profile_option( &mut self );
+    fn add_profile( &mut self, name : &str, values : &[( &str, &str )] );
+    fn add_profiles_from_str( &mut self, text : &str ) -> Result<(), OptParseError>;
+    #[cfg(feature = "completions")]
+    fn add_install_completions_option( &mut self );
+    fn set_interactive( &mut self, enable : bool );
+    #[cfg(feature = "std")]
+    fn is_interactive( &self ) -> bool;
+    #[cfg(feature = "std")]
+    fn confirm( &self, message : &str ) -> bool;
+    #[cfg(feature = "std")]
+    fn confirm_with<R : std::io::BufRead, W : std::io::Write>( &self, message : &str, input : &mut R, output : &mut W ) -> std::io::Result<bool>;
+    #[cfg(feature = "std")]
+    fn run_wizard( &mut self ) -> std::io::Result<String>;
+    #[cfg(feature = "std")]
+    fn run_wizard_with<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W ) -> std::io::Result<String>;
+    fn add_command<C : Command + Send + Sync + 'static>( &mut self, command : C );
+    fn set_command_categories( &mut self, categories : &[&str] );
+    fn add_provider<P : OptionProvider + Send + Sync + 'static>( &mut self, provider : P );
+    fn add_group( &mut self, group : OptionGroup );
+    fn add_options( &mut self, options : Vec<OptParseItem> );
+    fn add_namespace( &mut self, prefix : &str, options : Vec<OptParseItem> );
+    fn set_message_catalog<C : MessageCatalog + Send + Sync + 'static>( &mut self, catalog : C );
+    fn add_preprocessor( &mut self, preprocessor : impl Fn( Vec<String> ) -> Vec<String> + Send + Sync + 'static );
+    fn add_middleware( &mut self, middleware : impl Fn( &mut Matches ) -> Result<(), OptParseError> + Send + Sync + 'static );
+    fn get_error( &self ) -> Option<&OptParseError>;
+    fn get_violations( &self ) -> &[OptParseError];
+    fn add_check_option( &mut self );
+    fn format_error( &self, error : &OptParseError ) -> String;
+    fn run_command( &mut self, is_finish_if_help : bool ) -> Result<i32, OptParseError>;
+    fn run_multi_call( &mut self, program : &str, is_finish_if_help : bool ) -> Result<i32, OptParseError>;
+    fn get_value( &self, option : &str ) -> String;
+    fn get_value_ref( &self, option : &str ) -> &str;
+    fn get_value_or( &self, option : &str, fallback : &str ) -> String;
+    fn get_value_as<T>( &self, option : &str ) -> Result<T, OptParseError> where T : core::str::FromStr, T::Err : core::fmt::Display;
+    fn extract<T : FromOptParse>( &self ) -> Result<T, OptParseError>;
+    #[cfg(feature = "std")]
+    fn get_value_os( &self, option : &str ) -> &OsStr;
+    #[cfg(feature = "std")]
+    fn get_value_path( &self, option : &str ) -> &Path;
+    #[cfg(feature = "std")]
+    fn get_paths( &self, option : &str ) -> Vec<PathBuf>;
+    fn get_flags( &self, option : &str ) -> u64;
+    fn get_values( &self, option : &str ) -> Vec<String>;
+    fn get_typed( &self, option : &str ) -> Option<&TypedValue>;
+    fn get_int( &self, option : &str ) -> Option<i64>;
+    fn get_float( &self, option : &str ) -> Option<f64>;
+    fn get_bool( &self, option : &str ) -> Option<bool>;
+    fn get_parsed<T : Any>( &self, option : &str ) -> Option<&T>;
+    fn occurrences_of( &self, option : &str ) -> usize;
+    fn get_level_filter( &self, verbose : &str, quiet : &str ) -> verbosity::LevelFilter;
+    fn get_source( &self, option : &str ) -> Option<&ValueSource>;
+    fn was_set( &self, option : &str ) -> bool;
+    fn iter_values<'a>( &'a self ) -> impl Iterator<Item = ( &'a str, &'a str, &'a ValueSource )> + 'a;
+    fn diff( &self, other : &Matches ) -> Vec<ValueDiff>;
+    #[cfg(feature = "std")]
+    fn into_map( self ) -> HashMap<String, String>;
+    fn to_btreemap( &self ) -> BTreeMap<String, String>;
+    fn to_json( &self ) -> String;
+    fn to_env_exports( &self, prefix : &str ) -> String;
+    fn to_dotenv( &self, prefix : &str, is_redacted : bool ) -> String;
+    #[cfg(feature = "std")]
+    fn write_dotenv<P : AsRef<Path>>( &self, path : P, prefix : &str, is_redacted : bool ) -> std::io::Result<()>;
+    fn to_args( &self ) -> Vec<String>;
+    fn to_command_line( &self ) -> String;
+    fn get_matches( &self ) -> &Matches;
+    fn into_matches( self ) -> Matches;
+    fn get_args_count( &self ) -> usize;
+    fn get_ignored_args( &self ) -> &[String];
+    fn set_keep_raw_args( &mut self, enable : bool );
+    fn get_raw_args( &self ) -> &[String];
+    fn get_passthrough_args( &self ) -> &[String];
+    #[cfg(feature = "std")]
+    fn get_passthrough_args_os( &self ) -> &[OsString];
+    fn get_ordered_matches( &self ) -> &[MatchEvent];
+    #[cfg(feature = "std")]
+    fn run_passthrough( &self ) -> std::io::Result<i32>;
+    fn get_option_position( &self, option : &str ) -> Option<usize>;
+    fn get_arg_position( &self, index : usize ) -> Option<usize>;
+    fn get_args(&self, index : usize ) -> String;
+    #[cfg(feature = "std")]
+    fn get_arg_os( &self, index : usize ) -> &OsStr;
+    #[cfg(feature = "std")]
+    fn get_arg_path( &self, index : usize ) -> &Path;
+}
+
+// there is no process to exit in the browser hosts
+#[cfg(feature = "std")]
+fn exit_process( code : i32 ) {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    std::process::exit( code );
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    let _ = code;
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+struct ParseSettings
+{
+    auto_short_option : bool,       // true: derive the missing short options from the full options
+    normalize_full_option : bool,   // true: --sampling-rate and --samplingRate are the same
+    ignore_case_full_option : bool, // true: --ENCODING and --encoding are the same
+    windows_style_option : bool,    // true: /s 44100 and /encoding:PCM32 are also accepted
+    posixly_correct : bool,         // true: the options end at the first arg or "--". the default is per POSIXLY_CORRECT
+    auto_help : bool,               // true: print the help to stdout on -h or --help
+    prompt_missing : bool,          // true: ask the missing required options when stdin is a terminal
+    interactive : bool,             // false: never ask anything even on the terminal
+    strip_quotes : bool,            // true: --name="my file" is my file when the quotes survived
+    expand_tilde_args : bool,       // true: the args are the paths and ~ is expanded
+    interpolate_env : bool,         // true: ${VAR} in the values is the environment variable
+    plus_toggle_option : bool,      // true: +x turns off the flag -x like sh's set
+    single_dash_long_option : bool, // true: -encoding PCM16 is --encoding PCM16 like java and find
+    colon_value_separator : bool,   // true: --level:5 and -s:44100 are --level=5 and -s 44100 like javac and msbuild
+    help_layout : Option<HelpLayout>,   // None: the tab separated help
+    help_sections : bool,           // true: the help lists the flags and the options with the values apart
+    exit_after_dump_args : bool,    // true: --dump-args exits as -h does instead of continuing
+    value_terminator : String,      // ends the values of the greedy option e.g. ";"
+    long_value_syntax : LongValueSyntax,    // --name=value and/or --name value
+    terminal_width : Option<usize>, // the forced wrap width of the help. None: HelpLayout::width, COLUMNS or the terminal's
+    renamed_flags : Vec<( String, String )>,    // the retired flag and its replacement e.g. ( "--rate", "--samplingRate" )
+    env_prefix : String,            // the prefix of the environment variables of the env only entries e.g. "mytool" for MYTOOL_API_TOKEN
+    version : Option<String>,       // printed by --version. None: no --version
+    required_groups : Vec<( Vec<String>, bool )>,   // the option names of which at least one is specified, and true for exactly one
+    keep_raw_args : bool,           // true: Matches keeps the copy of the args for get_raw_args()
+    check_option : bool,            // true: add_check_option() and --check validates only
+    print_defaults_option : bool,   // true: add_print_defaults_option() and --print-defaults prints them
+    dump_args_option : bool,        // true: add_dump_args_option() and --dump-args prints them
+    yes_option : bool,              // true: add_yes_option() and --yes answers the confirmations
+    no_input_option : bool,         // true: add_no_input_option() and --no-input disables the prompts
+    profile_option : bool,          // true: add_profile_option() and --profile selects the preset
+    positional_args : bool,         // true: add_positional() and the greedy values stop at their max to leave the args
+}
+
+impl Default for ParseSettings
+{
+    fn default() -> Self {
+        Self {
+            auto_short_option : false,
+            normalize_full_option : false,
+            ignore_case_full_option : false,
+            windows_style_option : false,
+            posixly_correct : false,
+            auto_help : true,
+            prompt_missing : false,
+            interactive : true,
+            strip_quotes : false,
+            expand_tilde_args : false,
+            interpolate_env : false,
+            plus_toggle_option : false,
+            single_dash_long_option : false,
+            colon_value_separator : false,
+            help_layout : None,
+            help_sections : false,
+            exit_after_dump_args : false,
+            value_terminator : ";".to_string(),
+            long_value_syntax : LongValueSyntax::Both,
+            terminal_width : None,
+            renamed_flags : Vec::new(),
+            env_prefix : String::new(),
+            version : None,
+            required_groups : Vec::new(),
+            keep_raw_args : false,
+            check_option : false,
+            print_defaults_option : false,
+            dump_args_option : false,
+            yes_option : false,
+            no_input_option : false,
+            profile_option : false,
+            positional_args : false,
+        }
+    }
+}
+
+impl ParseSettings
+{
+    // ${VAR} in the value is the environment variable with set_interpolate_env(). only with std
+    fn interpolate<'a>( &self, value : &'a str ) -> Cow<'a, str> {
+        #[cfg(feature = "std")]
+        if self.interpolate_env {
+            return env::interpolate( value, |name| std::env::var( name ).ok() );
+        }
+        Cow::Borrowed( value )
+    }
+
+    // the comparable form of "--flag" or "--flag=value" per the matching modes. the value part is kept as is
+    fn normalize_flag<'a>( &self, arg : &'a str ) -> Cow<'a, str> {
+        if !arg.starts_with( "--" ) || !( self.normalize_full_option || self.ignore_case_full_option ) {
+            return Cow::Borrowed( arg );
+        }
+        let ( flag, value ) = match arg.find( '=' ) {
+            Some( pos ) => arg.split_at( pos ),
+            None => ( arg, "" )
+        };
+
+        let mut result = String::from( "--" );
+        if self.normalize_full_option {
+            // --samplingRate -> --sampling-rate. the run of the capitals is one word e.g. --ENCODING, --HTTPServer -> --http-server
+            let chars : Vec<char> = flag[2..].chars().collect();
+            for ( i, &c ) in chars.iter().enumerate() {
+                if c.is_uppercase() {
+                    let is_after_lower = i > 0 && !chars[ i - 1 ].is_uppercase();
+                    let is_acronym_end = i > 0 && chars[ i - 1 ].is_uppercase() && chars.get( i + 1 ).is_some_and( |next| next.is_lowercase() );
+                    if ( is_after_lower || is_acronym_end ) && !result.ends_with( '-' ) {
+                        result.push( '-' );
+                    }
+                    result.extend( c.to_lowercase() );
+                } else {
+                    result.push( c );
+                }
+            }
+        } else {
+            result.push_str( &flag[2..] );
+        }
+        if self.ignore_case_full_option {
+            result = result.to_lowercase();
+        }
+        result.push_str( value );
+        Cow::Owned( result )
+    }
+}
+
+
+// "-s", the normalized "--samplingRate" and the name -> the index of the OptParseItem
+#[derive(Clone, Debug, Default, PartialEq)]
+struct FlagIndex
+{
+    settings : ParseSettings,
+    flags : HashMap<String, usize>,
+    names : HashMap<String, usize>,
+    renamed : HashSet<String>,      // the retired flags in the flags
+}
+
+// ( the index, the short option ) for the options without the short one e.g. -s for --samplingRate by set_auto_short_option( true ).
+// the first letter of the full option not used yet
+fn auto_short_options( options : &[OptParseItem] ) -> Vec<( usize, String )> {
+    let mut used : HashSet<String> = options.iter().filter_map( |option| option.option.clone() ).collect();
+    used.insert( "-h".to_string() );
+
+    let mut result = Vec::new();
+    for ( index, option ) in options.iter().enumerate() {
+        let Some( long ) = &option.full_option else { continue; };
+        if option.option.is_some() {
+            continue;
+        }
+        let candidates = long.trim_start_matches( '-' ).chars().filter( |c| c.is_ascii_alphanumeric() );
+        for c in candidates {
+            let short = format!( "-{}", c );
+            if !used.contains( &short ) {
+                used.insert( short.clone() );
+                result.push( ( index, short ) );
+                break;
+            }
+        }
+    }
+    result
+}
+
+impl FlagIndex
+{
+    // the derived short options are indexed too, so that the lookup doesn't depend on derive_short_options() having run
+    fn new( options : &[OptParseItem], settings : &ParseSettings ) -> Self {
+        let mut result = Self {
+            settings : settings.clone(),
+            ..Default::default()
+        };
+        for ( index, option ) in options.iter().enumerate() {
+            if let Some( short ) = &option.option {
+                result.flags.insert( short.clone(), index );
+            }
+            if let Some( long ) = &option.full_option {
+                result.flags.insert( settings.normalize_flag( long ).into_owned(), index );
+            }
+            result.names.insert( option.name.clone(), index );
+        }
+        if settings.auto_short_option {
+            for ( index, short ) in auto_short_options( options ) {
+                result.flags.insert( short, index );
+            }
+        }
+        for ( old, new ) in &settings.renamed_flags {
+            let old = settings.normalize_flag( old ).into_owned();
+            if let Some( index ) = result.find( new ).filter( |_| !result.flags.contains_key( &old ) ) {
+                result.flags.insert( old.clone(), index );
+                result.renamed.insert( old );
+            }
+        }
+        result
+    }
+
+    fn is_renamed( &self, flag : &str ) -> bool {
+        self.renamed.contains( self.settings.normalize_flag( flag ).as_ref() )
+    }
+
+    // the index for the flag in the args
+    fn find_flag( &self, flag : &str ) -> Option<usize> {
+        self.flags.get( self.settings.normalize_flag( flag ).as_ref() ).copied()
+    }
+
+    // the index for any of "-s", "--samplingRate" or the name
+    fn find( &self, option : &str ) -> Option<usize> {
+        match self.names.get( option ){
+            Some( index ) => Some( *index ),
+            None => self.find_flag( option )
+        }
+    }
+}
+
+
+// what the Scanner found in the args
+enum Token<'a>
+{
+    Help( bool, bool ),             // -h or --help. true: the detailed help by --help, and the advanced options by --help-all
+    Value( usize, Option<&'a str>, usize ), // the index of the option, its value and the position of the option in the args. None: the required value is missing
+    MoreValue( usize, &'a str ),    // the following value of the greedy option
+    Arg( &'a str ),                 // not an option
+    Ignored( &'a str ),             // the unknown option
+    Renamed( usize, String ),       // the retired flag of the option, followed by its value
+    HelpTopic( &'a str ),           // the option of --help samplingRate or --help=-s, following Help
+    Passthrough( &'a str ),         // the arg after "--", neither the option nor the arg of this tool
+}
+
+// left-to-right state machine fed one arg at a time, so that the args can be a slice or a stream
+struct Scanner<'i>
+{
+    options : &'i [OptParseItem],
+    index : &'i FlagIndex,
+    pending : Option<( usize, usize )>, // the option waiting for its value in the next arg and its position
+    greedy : Option<( usize, usize )>,  // the greedy option taking the following args as its values and the values so far
+    position : usize,               // the position of the arg being fed
+    is_end_of_options : bool,       // true: the rest are args
+    is_passthrough : bool,          // true: "--" was given and the rest are Token::Passthrough
+    is_help_topic : bool,           // true: --help was the last arg and this can be the option to show the help of
+}
+
+impl<'i> Scanner<'i>
+{
+    fn new( options : &'i [OptParseItem], index : &'i FlagIndex ) -> Self {
+        Self {
+            options,
+            index,
+            pending : None,
+            greedy : None,
+            position : 0,
+            is_end_of_options : false,
+            is_passthrough : false,
+            is_help_topic : false,
+        }
+    }
+
+    fn feed<'t>( &mut self, arg : &'t str, on_token : &mut impl FnMut( Token<'t> ) ) {
+        let position = self.position;
+        self.position += 1;
+        if self.is_passthrough {
+            on_token( Token::Passthrough( arg ) );
+            return;
+        }
+        if core::mem::take( &mut self.is_help_topic ) && self.index.find( arg ).is_some() {
+            on_token( Token::HelpTopic( arg ) );
+            return;
+        }
+        if let Some( ( index, count ) ) = self.greedy.take() {
+            if arg == self.index.settings.value_terminator {
+                return;
+            }
+            if !self.is_option( arg ) {
+                on_token( Token::MoreValue( index, arg ) );
+                // the values beyond the max are the error unless they can be the positional args
+                if count + 1 < self.options[ index ].value_count.1 || !self.index.settings.positional_args {
+                    self.greedy = Some( ( index, count + 1 ) );
+                }
+                return;
+            }
+        }
+        if let Some( ( index, flag_position ) ) = self.pending.take() {
+            let is_terminator = self.options[ index ].is_greedy() && arg == self.index.settings.value_terminator;
+            if !self.is_option( arg ) && !is_terminator {
+                // this is arg required case and this arg is the value for the option
+                on_token( Token::Value( index, Some( arg ), flag_position ) );
+                self.start_greedy( index );
+                return;
+            }
+            on_token( Token::Value( index, None, flag_position ) );
+            if is_terminator {
+                return;
+            }
+        }
+
+        if arg.eq( "--" ) {
+            // the rest are for the command to run, see get_passthrough_args()
+            self.is_end_of_options = true;
+            self.is_passthrough = true;
+            return;
+        }
+        if self.is_end_of_options {
+            on_token( Token::Arg( arg ) );
+            return;
+        }
+        if self.index.settings.posixly_correct && !self.is_option( arg ) {
+            // stop at the first operand like getopt does
+            self.is_end_of_options = true;
+            on_token( Token::Arg( arg ) );
+            return;
+        }
+
+        let is_long_help = arg.starts_with( "--help" ) || ( self.index.settings.single_dash_long_option && arg.eq( "-help" ) );
+        let is_help_all = arg.eq( "--help-all" ) || ( self.index.settings.single_dash_long_option && arg.eq( "-help-all" ) );
+        if is_long_help || is_help_all || arg.eq( "-h" ) || ( self.index.settings.windows_style_option && ( arg.eq( "/?" ) || arg.eq( "/h" ) ) ) {
+            on_token( Token::Help( is_long_help || is_help_all, is_help_all ) );
+            match arg.strip_prefix( "--help=" ) {
+                Some( topic ) => on_token( Token::HelpTopic( topic ) ),
+                None => self.is_help_topic = arg == "--help" || ( self.index.settings.single_dash_long_option && arg == "-help" ),
+            }
+        } else if let Some( index ) = self.find_plus_toggle( arg ) {
+            on_token( Token::Value( index, Some( "false" ), position ) );
+        } else if let Some( ( flag, inline_value ) ) = self.split_option( arg ) {
+            let is_long = arg.starts_with( '-' ) && flag.starts_with( "--" );
+            match self.index.find_flag( &flag ) {
+                Some( index ) => {
+                    if self.index.is_renamed( &flag ) {
+                        on_token( Token::Renamed( index, flag.to_string() ) );
+                    }
+                    if !self.options[ index ].arg_required {
+                        // --flag=false turns off the flag turned on before. the other values are the missing value
+                        let value = match inline_value {
+                            None | Some( "true" ) => Some( "true" ),
+                            Some( "false" ) => Some( "false" ),
+                            Some( _ ) => None
+                        };
+                        on_token( Token::Value( index, value, position ) );
+                    } else if inline_value.is_some() {
+                        if is_long && self.index.settings.long_value_syntax == LongValueSyntax::SeparateOnly {
+                            on_token( Token::Value( index, None, position ) );
+                            return;
+                        }
+                        on_token( Token::Value( index, inline_value, position ) );
+                        self.start_greedy( index );
+                    } else if is_long && self.index.settings.long_value_syntax == LongValueSyntax::EqualsOnly {
+                        on_token( Token::Value( index, None, position ) );
+                    } else {
+                        self.pending = Some( ( index, position ) );
+                    }
+                },
+                None => {
+                    // /something unknown is rather a path than an option, and -5 unknown is the negative number
+                    if !arg.starts_with( "-" ) || is_negative_number( arg ) {
+                        on_token( Token::Arg( arg ) );
+                    } else {
+                        on_token( Token::Ignored( arg ) );
+                    }
+                }
+            }
+        } else {
+            on_token( Token::Arg( arg ) );
+        }
+    }
+
+    // ( "-s" or "--something", the value ) for the option style arg
+    // --something=value or -s value, --something value, and /s value, /something:value in the windows style
+    fn split_option<'t>( &self, arg : &'t str ) -> Option<( Cow<'t, str>, Option<&'t str> )> {
+        if self.index.settings.single_dash_long_option && arg.len() > 2 && !arg.starts_with( "--" ) && arg.starts_with( "-" ) && self.index.find_flag( arg ).is_none() {
+            // -samplingRate as --samplingRate
+            return match self.find_value_separator( arg, true ) {
+                Some( pos ) => Some( ( Cow::Owned( format!( "-{}", &arg[..pos] ) ), Some( &arg[pos+1..] ) ) ),
+                None => Some( ( Cow::Owned( format!( "-{}", arg ) ), None ) )
+            };
+        }
+        if arg.starts_with( "-" ) {
+            return match self.find_value_separator( arg, arg.starts_with( "--" ) ) {
+                Some( pos ) => Some( ( Cow::Borrowed( &arg[..pos] ), Some( &arg[pos+1..] ) ) ),
+                None => Some( ( Cow::Borrowed( arg ), None ) )
+            };
+        }
+        if self.index.settings.windows_style_option && arg.len() > 1 && arg.starts_with( "/" ) {
+            let ( name, value ) = match arg.find( [ ':', '=' ] ) {
+                Some( pos ) => ( &arg[1..pos], Some( &arg[pos+1..] ) ),
+                None => ( &arg[1..], None )
+            };
+            let flag = if name.chars().count() == 1 { format!( "-{}", name ) } else { format!( "--{}", name ) };
+            return Some( ( Cow::Owned( flag ), value ) );
+        }
+        None
+    }
+
+    // the position of '=' of the long option, or of the earlier ':' with set_colon_value_separator()
+    fn find_value_separator( &self, arg : &str, is_long : bool ) -> Option<usize> {
+        let equals = arg.find( '=' ).filter( |_| is_long );
+        match arg.find( ':' ) {
+            Some( colon ) if self.index.settings.colon_value_separator && equals.is_none_or( |equals| colon < equals ) => Some( colon ),
+            _ => equals
+        }
+    }
+
+    // the following args are the values of the option if it takes more
+    fn start_greedy( &mut self, index : usize ) {
+        if self.options[ index ].is_greedy() {
+            self.greedy = Some( ( index, 1 ) );
+        }
+    }
+
+    // the index of the flag -x for +x
+    fn find_plus_toggle( &self, arg : &str ) -> Option<usize> {
+        if !self.index.settings.plus_toggle_option || !arg.starts_with( '+' ) || arg.chars().count() != 2 {
+            return None;
+        }
+        self.index.find_flag( &format!( "-{}", &arg[1..] ) ).filter( |index| !self.options[ *index ].arg_required )
+    }
+
+    // true: the arg is an option rather than the value for the pending option.
+    // the negative number is the value unless it's registered as the flag like -1
+    fn is_option( &self, arg : &str ) -> bool {
+        if is_negative_number( arg ) {
+            return self.index.find_flag( arg ).is_some();
+        }
+        if arg.starts_with( "-" ) || self.find_plus_toggle( arg ).is_some() {
+            return true;
+        }
+        match self.split_option( arg ) {
+            Some( ( flag, _ ) ) => self.index.find_flag( &flag ).is_some(),
+            None => false
+        }
+    }
+
+    // no more args
+    fn finish<'t>( &mut self, on_token : &mut impl FnMut( Token<'t> ) ) {
+        if let Some( ( index, position ) ) = self.pending.take() {
+            // this is arg required case but the value isn't present
+            on_token( Token::Value( index, None, position ) );
+        }
+    }
+}
+
+// -5, -0.5 or -1e3
+fn is_negative_number( arg : &str ) -> bool {
+    match arg.strip_prefix( '-' ) {
+        Some( number ) => number.starts_with( |c : char| c.is_ascii_digit() || c == '.' ) && number.parse::<f64>().is_ok(),
+        None => false
+    }
+}
+
+// single left-to-right pass over the args, borrowing the tokens and the values from the args
+fn scan_args<'a, S : AsRef<str>>( args : &'a [S], options : &[OptParseItem], index : &FlagIndex, mut on_token : impl FnMut( Token<'a> ) ) {
+    let mut scanner = Scanner::new( options, index );
+    for arg in args {
+        scanner.feed( arg.as_ref(), &mut on_token );
+    }
+    scanner.finish( &mut on_token );
+}
+
+
+#[cfg(feature = "std")]
+// the part of the original arg for the value found in its lossy conversion e.g. -s value, --something=value, --level:5 or /encoding:PCM16
+fn original_value( lossy : &str, original : &OsStr, value : &str ) -> Option<OsString> {
+    let offset = ( value.as_ptr() as usize ).wrapping_sub( lossy.as_ptr() as usize );
+    if offset == 0 && value.len() == lossy.len() {
+        return Some( original.to_os_string() );
+    }
+    if offset >= lossy.len() || offset + value.len() != lossy.len() {
+        // not the tail of the arg e.g. "true" for the value-less option
+        return None;
+    }
+    // the flag part before the value is valid UTF-8 then it's the same bytes in the original
+    let bytes = original.as_encoded_bytes();
+    let prefix = &lossy[ ..offset ];
+    if !bytes.starts_with( prefix.as_bytes() ) {
+        return None;
+    }
+    // SAFETY: the bytes are split immediately after the valid UTF-8 prefix
+    Some( unsafe { OsStr::from_encoded_bytes_unchecked( &bytes[ offset.. ] ) }.to_os_string() )
+}
+
+
+// the value of a registered option. Debug masks the secret
+#[derive(Clone, Default, PartialEq)]
+struct MatchEntry
+{
+    name : String,
+    value : String,
+    #[cfg(feature = "std")]
+    value_os : Option<OsString>,    // the original value if it isn't valid UTF-8
+    source : ValueSource,
+    occurrences : usize,
+    position : Option<usize>,       // the position in the raw args of the last occurrence
+    is_secret : bool,
+    values : Vec<String>,           // all the values of the greedy option. value is the last one
+    typed : Option<TypedValue>,     // value converted per the value type
+    value_type : ValueType,         // the declared type for to_json() and Serialize
+    is_flag : bool,                 // true: the value-less option, true or false
+    is_greedy : bool,               // true: the values are the array
+}
+
+const REDACTED : &str = "********";
+
+impl core::fmt::Debug for MatchEntry
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        let values : Vec<&str> = self.values.iter().map( |value| if self.is_secret { REDACTED } else { value.as_str() } ).collect();
+        let mut result = f.debug_struct( "MatchEntry" );
+        result.field( "name", &self.name ).field( "value", &self.display_value() );
+        #[cfg(feature = "std")]
+        result.field( "value_os", &self.value_os.as_deref().map( |value| if self.is_secret { OsStr::new( REDACTED ) } else { value } ) );
+        result.field( "source", &self.source )
+            .field( "occurrences", &self.occurrences )
+            .field( "position", &self.position )
+            .field( "is_secret", &self.is_secret )
+            .field( "values", &values );
+        match self.is_secret {
+            true => result.field( "typed", &REDACTED ),
+            false => result.field( "typed", &self.typed ),
+        };
+        result.field( "value_type", &self.value_type )
+            .field( "is_flag", &self.is_flag )
+            .field( "is_greedy", &self.is_greedy )
+            .finish()
+    }
+}
+
+// set the ( option, value, origin ) of the source to the options not set yet. the value is validated as the args are and the error tells the origin
+fn apply_values( matches : &mut Matches, options : &[OptParseItem], index : &FlagIndex, values : &[( String, String, String )], source : ValueSource ) -> Result<(), OptParseError> {
+    for ( option, value, origin ) in values {
+        let with_origin = |error : OptParseError| OptParseError::WithOrigin { error : Box::new( error ), origin : origin.clone() };
+        let item = index.find( option ).map( |index| &options[ index ] ).ok_or_else( || with_origin( OptParseError::UnknownOption { name : option.clone() } ) )?;
+        if matches.was_set( &item.name ) {
+            continue;
+        }
+        let Some( &entry ) = matches.keys.get( &item.name ) else { continue; };
+        let value = item.normalize( &matches.settings.interpolate( value ) ).into_owned();
+        let typed = item.convert( &value ).map_err( |message| with_origin( invalid_value( &matches.entries[ entry ], &value, message ) ) )?;
+        matches.set_value( &item.name, &value, typed, source.clone() );
+    }
+    Ok( () )
+}
+
+// the error for the value of the entry. the secret is masked
+fn invalid_value( entry : &MatchEntry, value : &str, message : ValueMessage ) -> OptParseError {
+    OptParseError::InvalidValue { name : entry.name.clone(), value : if entry.is_secret { REDACTED.to_string() } else { value.to_string() }, message }
+}
+
+impl MatchEntry
+{
+    // the value to show
+    fn display_value( &self ) -> &str {
+        if self.is_secret { REDACTED } else { &self.value }
+    }
+
+    // the JSON of the value by the declared type, not by the looks of the value e.g. "1.10" of the string option is the string
+    fn json_value( &self ) -> JsonValue<'_> {
+        if self.is_secret {
+            return JsonValue::Str( REDACTED );
+        }
+        if self.is_flag {
+            return JsonValue::Bool( self.value == "true" );
+        }
+        if self.is_greedy {
+            return JsonValue::Array( self.values.iter().map( |value| JsonValue::of( &self.value_type, None, value ) ).collect() );
+        }
+        JsonValue::of( &self.value_type, self.typed.as_ref(), &self.value )
+    }
+}
+
+enum JsonValue<'a>
+{
+    Null,
+    Bool( bool ),
+    Integer( i64 ),
+    Float( f64 ),
+    Str( &'a str ),
+    Array( Vec<JsonValue<'a>> ),
+}
+
+impl<'a> JsonValue<'a>
+{
+    // the cached typed value, or the value converted per the type. the empty value of the non string types is null
+    fn of( value_type : &ValueType, typed : Option<&TypedValue>, value : &'a str ) -> Self {
+        let converted = match ( typed, value_type ) {
+            ( Some( typed ), _ ) => Some( typed.clone() ),
+            ( None, ValueType::Integer ) => value.parse().ok().map( TypedValue::Integer ),
+            ( None, ValueType::Float ) => value.parse().ok().map( TypedValue::Float ),
+            ( None, ValueType::Bool ) => match value {
+                "true" | "yes" | "on" | "1" => Some( TypedValue::Bool( true ) ),
+                "false" | "no" | "off" | "0" => Some( TypedValue::Bool( false ) ),
+                _ => None,
+            },
+            _ => None,
+        };
+        match converted {
+            Some( TypedValue::Integer( value ) ) => JsonValue::Integer( value ),
+            Some( TypedValue::Float( value ) ) if value.is_finite() => JsonValue::Float( value ),
+            Some( TypedValue::Bool( value ) ) => JsonValue::Bool( value ),
+            _ if value.is_empty() && matches!( value_type, ValueType::Integer | ValueType::Float | ValueType::Bool ) => JsonValue::Null,
+            _ => JsonValue::Str( value ),
+        }
+    }
+
+    fn write( &self, result : &mut String ) {
+        match self {
+            JsonValue::Null => result.push_str( "null" ),
+            JsonValue::Bool( value ) => { let _ = write!( result, "{}", value ); },
+            JsonValue::Integer( value ) => { let _ = write!( result, "{}", value ); },
+            JsonValue::Float( value ) => { let _ = write!( result, "{:?}", value ); },
+            JsonValue::Str( value ) => write_json_str( result, value ),
+            JsonValue::Array( values ) => {
+                result.push( '[' );
+                for ( i, value ) in values.iter().enumerate() {
+                    if i != 0 {
+                        result.push( ',' );
+                    }
+                    value.write( result );
+                }
+                result.push( ']' );
+            },
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for JsonValue<'_>
+{
+    fn serialize<S : serde::Serializer>( &self, serializer : S ) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_none(),
+            JsonValue::Bool( value ) => serializer.serialize_bool( *value ),
+            JsonValue::Integer( value ) => serializer.serialize_i64( *value ),
+            JsonValue::Float( value ) => serializer.serialize_f64( *value ),
+            JsonValue::Str( value ) => serializer.serialize_str( value ),
+            JsonValue::Array( values ) => serializer.collect_seq( values ),
+        }
+    }
+}
+
+
+// the parsed state, kept apart from the OptParseItems so that parsing doesn't need to clone them.
+// the entries are kept in the registered order so that the iteration is reproducible.
+// this owns everything, so that it's Send + Sync and can be shared by Arc after parsing
+#[derive(Clone, Default, PartialEq)]
+pub struct Matches
+{
+    settings : ParseSettings,
+    entries : Vec<MatchEntry>,
+    keys : HashMap<String, usize>,  // the name, "-s", "--samplingRate" and the normalized one -> the index of the entries
+    arg_values : Vec<String>,
+    arg_positions : Vec<usize>,     // the position in the raw args of arg_values[i]
+    raw_args : Vec<String>,         // the args as given with set_keep_raw_args(). lossy for the ones not valid UTF-8
+    arg_count : usize,              // the number of the parsed args, the position of the next one
+    passthrough_args : Vec<String>, // the args after "--"
+    ignored_args : Vec<String>,     // the unknown options skipped by the parse
+    events : Vec<MatchEvent>,       // the options and the args in the order of the args
+    #[cfg(feature = "std")]
+    arg_values_os : Vec<Option<OsString>>, // the original of arg_values[i] if it isn't valid UTF-8
+    #[cfg(feature = "std")]
+    passthrough_args_os : Vec<OsString>,   // passthrough_args as given, not lossy
+    is_help : bool,
+    is_long_help : bool,            // --help rather than -h
+    is_help_all : bool,             // --help-all for the advanced options too
+    help_topic : Option<String>,    // the option of --help samplingRate
+    error : Option<OptParseError>,  // the error of the middleware or the provider which failed the parse
+    violations : Vec<OptParseError>,    // all the reasons of the failed parse, see get_violations()
+    renamed : Vec<( String, String )>,  // the retired flags in the args and their replacements, see add_renamed_option()
+}
+
+impl Matches
+{
+    fn new( settings : &ParseSettings ) -> Self {
+        Self {
+            settings : settings.clone(),
+            ..Default::default()
+        }
+    }
+
+    // set the default value and the aliases of the option
+    fn register_option( &mut self, option : &OptParseItem ) {
+        let value = self.settings.interpolate( &option.value ).into_owned();
+        let entry = MatchEntry {
+            name : option.name.clone(),
+            #[cfg(feature = "std")]
+            value_os : None,
+            source : ValueSource::Default,
+            occurrences : 0,
+            position : None,
+            is_secret : option.is_secret,
+            values : if value.is_empty() { Vec::new() } else { vec![ value.clone() ] },
+            typed : option.convert( &value ).ok().flatten(),
+            value_type : option.value_type.clone(),
+            is_flag : !option.arg_required,
+            is_greedy : option.is_accumulated(),
+            value,
+        };
+        let index = match self.keys.get( &option.name ) {
+            Some( &index ) => { self.entries[ index ] = entry; index },
+            None => { self.entries.push( entry ); self.entries.len() - 1 }
+        };
+        for key in [ option.name.as_str(), option.get_option(), option.get_full_option(), &self.settings.normalize_flag( option.get_full_option() ) ] {
+            if !key.is_empty() {
+                let _ = &self.keys.insert( key.to_string(), index );
+            }
+        }
+    }
+
+    // count the arg for the positions, and copy it only if set_keep_raw_args()
+    fn push_raw_arg( &mut self, arg : impl FnOnce() -> String ) {
+        self.arg_count += 1;
+        if self.settings.keep_raw_args {
+            self.raw_args.push( arg() );
+        }
+    }
+
+    // single left-to-right pass over the args. returns ( no error, -h or --help found )
+    fn parse_args<S : AsRef<str>>( &mut self, args : &[S], options : &[OptParseItem], index : &FlagIndex, collect_args : bool ) -> ( bool, bool ) {
+        self.parse_iter( args.iter(), options, index, collect_args )
+    }
+
+    // same as parse_args() but pulling the args one by one from the iterator
+    fn parse_iter<S : AsRef<str>>( &mut self, args : impl Iterator<Item = S>, options : &[OptParseItem], index : &FlagIndex, collect_args : bool ) -> ( bool, bool ) {
+        let mut state = ( true, false );
+        let mut scanner = Scanner::new( options, index );
+        for arg in args {
+            if collect_args {
+                self.push_raw_arg( || arg.as_ref().to_string() );
+            }
+            scanner.feed( arg.as_ref(), &mut |token| self.on_token( token, options, collect_args, &mut state ) );
+        }
+        scanner.finish( &mut |token| self.on_token( token, options, collect_args, &mut state ) );
+        self.is_help = state.1;
+        state
+    }
+
+    // same as parse_iter() for the args which may not be valid UTF-8. such args are matched by the lossy conversion
+    // and the originals are kept for the values and the args
+    #[cfg(feature = "std")]
+    fn parse_iter_os( &mut self, args : impl Iterator<Item = OsString>, options : &[OptParseItem], index : &FlagIndex, collect_args : bool ) -> ( bool, bool ) {
+        let mut state = ( true, false );
+        let mut scanner = Scanner::new( options, index );
+        for arg in args {
+            if collect_args {
+                self.push_raw_arg( || arg.to_string_lossy().into_owned() );
+            }
+            match arg.to_str() {
+                Some( arg ) => scanner.feed( arg, &mut |token| self.on_token( token, options, collect_args, &mut state ) ),
+                None => {
+                    let lossy = arg.to_string_lossy();
+                    scanner.feed( &lossy, &mut |token| {
+                        let ( option, original ) = match &token {
+                            Token::Value( index, Some( value ), _ ) => ( Some( *index ), original_value( &lossy, &arg, value ) ),
+                            Token::Arg( value ) => ( None, original_value( &lossy, &arg, value ) ),
+                            _ => ( None, None )
+                        };
+                        let is_arg = matches!( token, Token::Arg( _ ) );
+                        let is_passthrough = matches!( token, Token::Passthrough( _ ) );
+                        self.on_token( token, options, collect_args, &mut state );
+                        if let Some( index ) = option {
+                            if let Some( &index ) = self.keys.get( &options[ index ].name ) {
+                                self.entries[ index ].value_os = original;
+                            }
+                        } else if is_arg && collect_args {
+                            if let Some( last ) = self.arg_values_os.last_mut() {
+                                *last = original;
+                            }
+                        } else if is_passthrough && collect_args {
+                            if let Some( last ) = self.passthrough_args_os.last_mut() {
+                                *last = arg.clone();
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        scanner.finish( &mut |token| self.on_token( token, options, collect_args, &mut state ) );
+        self.is_help = state.1;
+        state
+    }
+
+    fn on_token( &mut self, token : Token, options : &[OptParseItem], collect_args : bool, ( result, is_help ) : &mut ( bool, bool ) ) {
+        match token {
+            Token::Help( is_long_help, is_help_all ) => {
+                *is_help = true;
+                self.is_long_help |= is_long_help;
+                self.is_help_all |= is_help_all;
+            },
+            Token::HelpTopic( topic ) => {
+                self.help_topic = Some( topic.to_string() );
+            },
+            Token::Value( index, value, position ) => {
+                let entry = match self.keys.get( &options[ index ].name ) {
+                    Some( &index ) => &mut self.entries[ index ],
+                    None => return
+                };
+                let mut error = None;
+                let mut event = None;
+                match value.or( options[ index ].missing_value.as_deref() ) {
+                    Some( value ) => {
+                        let value = if self.settings.strip_quotes { strip_quotes( value ) } else { value };
+                        let interpolated = self.settings.interpolate( value );
+                        let value = options[ index ].normalize( &interpolated );
+                        event = Some( MatchEvent::Option { name : entry.name.clone(), value : value.to_string(), position } );
+                        match options[ index ].convert( &value ) {
+                            Ok( typed ) => {
+                                entry.typed = typed;
+                                for action in &options[ index ].actions {
+                                    ( action.0 )( &value );
+                                }
+                            },
+                            Err( message ) => {
+                                *result = false;
+                                let invalid = invalid_value( entry, &value, message );
+                                self.violations.push( invalid.clone() );
+                                error = Some( invalid );
+                            }
+                        }
+                        if options[ index ].is_accumulated() {
+                            // the values of the repeated option are accumulated
+                            if entry.source != ValueSource::CommandLine {
+                                entry.values.clear();
+                            }
+                            entry.values.push( value.to_string() );
+                        }
+                        if matches!( options[ index ].value_type, ValueType::Flags( _ ) ) && entry.source == ValueSource::CommandLine {
+                            // the repeated flags are accumulated instead of overwriting the former
+                            entry.value = format!( "{},{}", entry.value, value );
+                        } else {
+                            entry.value = value.into_owned();
+                        }
+                        #[cfg(feature = "std")]
+                        {
+                            entry.value_os = None;
+                        }
+                    },
+                    None => {
+                        *result = false;
+                        self.violations.push( OptParseError::MissingValue { name : entry.name.clone() } );
+                    }
+                }
+                entry.source = ValueSource::CommandLine;
+                entry.occurrences += 1;
+                entry.position = Some( position );
+                self.error = self.error.take().or( error );
+                if let Some( event ) = event.filter( |_| collect_args ) {
+                    self.events.push( event );
+                }
+            },
+            Token::MoreValue( index, value ) => {
+                let entry = match self.keys.get( &options[ index ].name ) {
+                    Some( &index ) => &mut self.entries[ index ],
+                    None => return
+                };
+                let value = if self.settings.strip_quotes { strip_quotes( value ) } else { value };
+                let interpolated = self.settings.interpolate( value );
+                let value = options[ index ].normalize( &interpolated );
+                match options[ index ].convert( &value ) {
+                    Ok( typed ) => {
+                        entry.typed = typed;
+                        for action in &options[ index ].actions {
+                            ( action.0 )( &value );
+                        }
+                    },
+                    Err( message ) => {
+                        *result = false;
+                        let error = invalid_value( entry, &value, message );
+                        self.violations.push( error.clone() );
+                        self.error.get_or_insert( error );
+                        return;
+                    }
+                }
+                entry.values.push( value.to_string() );
+                if collect_args {
+                    self.events.push( MatchEvent::Option { name : entry.name.clone(), value : value.to_string(), position : self.arg_count.saturating_sub( 1 ) } );
+                }
+                entry.value = value.into_owned();
+                #[cfg(feature = "std")]
+                {
+                    entry.value_os = None;
+                }
+            },
+            Token::Arg( arg ) => {
+                if collect_args {
+                    #[cfg(feature = "std")]
+                    let arg = if self.settings.expand_tilde_args { env::expand_tilde( arg ) } else { Cow::Borrowed( arg ) };
+                    self.arg_values.push( arg.to_string() );
+                    self.arg_positions.push( self.arg_count.saturating_sub( 1 ) );
+                    self.events.push( MatchEvent::Arg { value : arg.to_string(), position : self.arg_count.saturating_sub( 1 ) } );
+                    #[cfg(feature = "std")]
+                    self.arg_values_os.push( None );
+                }
+            },
+            Token::Ignored( arg ) => {
+                if collect_args {
+                    self.ignored_args.push( arg.to_string() );
+                }
+            },
+            Token::Passthrough( arg ) => {
+                if collect_args {
+                    self.passthrough_args.push( arg.to_string() );
+                    #[cfg(feature = "std")]
+                    self.passthrough_args_os.push( OsString::from( arg ) );
+                }
+            },
+            Token::Renamed( index, flag ) => {
+                let new = options[ index ].full_option.clone().unwrap_or_else( || options[ index ].get_option().to_string() );
+                if !self.renamed.iter().any( |( old, _ )| *old == flag ) {
+                    self.renamed.push( ( flag, new ) );
+                }
+            }
+        }
+    }
+
+    // the entry for any of "-s", "--samplingRate" or the name
+    fn find( &self, option : &str ) -> Option<&MatchEntry> {
+        let index = match self.keys.get( option ) {
+            Some( index ) => Some( index ),
+            None => self.keys.get( self.settings.normalize_flag( option ).as_ref() )
+        };
+        index.map( |index| &self.entries[ *index ] )
+    }
+
+    pub fn get_value( &self, option : &str ) -> String {
+        self.get_value_ref( option ).to_string()
+    }
+
+    // all the values of the greedy option in the order. the default is the only value if not specified
+    pub fn get_values( &self, option : &str ) -> Vec<String> {
+        self.find( option ).map( |entry| entry.values.clone() ).unwrap_or_default()
+    }
+
+    // same as get_value() without allocating
+    pub fn get_value_ref( &self, option : &str ) -> &str {
+        match self.find( option ){
+            Some( entry ) => entry.value.as_str(),
+            None => ""
+        }
+    }
+
+    // the value converted by OptParseItem::convert() or ValueType::convert()
+    fn set_value( &mut self, option : &str, value : &str, typed : Option<TypedValue>, source : ValueSource ) {
+        if let Some( &index ) = self.keys.get( option ) {
+            let entry = &mut self.entries[ index ];
+            entry.value = value.to_string();
+            entry.values = vec![ value.to_string() ];
+            entry.typed = typed;
+            #[cfg(feature = "std")]
+            {
+                entry.value_os = None;
+            }
+            entry.source = source;
+        }
+    }
+
+    // set the value from the middleware e.g. the frames from the duration and the sampling rate. the source is ValueSource::Derived
+    // the value is converted per the option's ValueType
+    pub fn set_derived_value( &mut self, option : &str, value : &str ) -> Result<(), OptParseError> {
+        let entry = self.find( option ).ok_or_else( || OptParseError::UnknownOption { name : option.to_string() } )?;
+        let typed = entry.value_type.convert( value ).map_err( |message| invalid_value( entry, value, message ) )?;
+        let name = entry.name.clone();
+        self.set_value( &name, value, typed, ValueSource::Derived );
+        Ok( () )
+    }
+
+    // why the parse failed: the first invalid value, or the error of the middleware or the provider. None for the other failures
+    pub fn get_error( &self ) -> Option<&OptParseError> {
+        self.error.as_ref()
+    }
+
+    // all the reasons why the parse failed e.g. the invalid values, the missing and the conflicting options and the errors of the middlewares
+    pub fn get_violations( &self ) -> &[OptParseError] {
+        &self.violations
+    }
+
+    // the value converted at the parse per the option's ValueType. None for ValueType::String
+    pub fn get_typed( &self, option : &str ) -> Option<&TypedValue> {
+        self.find( option ).and_then( |entry| entry.typed.as_ref() )
+    }
+
+    // the value of ValueType::Integer without parsing it again
+    pub fn get_int( &self, option : &str ) -> Option<i64> {
+        match self.get_typed( option ) {
+            Some( TypedValue::Integer( value ) ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    pub fn get_float( &self, option : &str ) -> Option<f64> {
+        match self.get_typed( option ) {
+            Some( TypedValue::Float( value ) ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    pub fn get_bool( &self, option : &str ) -> Option<bool> {
+        match self.get_typed( option ) {
+            Some( TypedValue::Bool( value ) ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    // the value converted by OptParseItem::with_parser() e.g. get_parsed::<Ratio>( "aspect" ). None for the other type
+    pub fn get_parsed<T : Any>( &self, option : &str ) -> Option<&T> {
+        match self.get_typed( option ) {
+            Some( TypedValue::Custom( value ) ) => value.downcast_ref::<T>(),
+            _ => None
+        }
+    }
+
+    // the value converted by FromStr e.g. get_value_as::<u32>( "samplingRate" )
+    pub fn get_value_as<T>( &self, option : &str ) -> Result<T, OptParseError> where T : core::str::FromStr, T::Err : core::fmt::Display {
+        let entry = self.find( option ).ok_or_else( || OptParseError::UnknownOption { name : option.to_string() } )?;
+        entry.value.parse::<T>().map_err( |err| OptParseError::InvalidValue { name : entry.name.clone(), value : entry.display_value().to_string(), message : err.to_string().into() } )
+    }
+
+    #[cfg(feature = "std")]
+    // the value as given. this differs from get_value() only when the value isn't valid UTF-8
+    pub fn get_value_os( &self, option : &str ) -> &OsStr {
+        match self.find( option ){
+            Some( entry ) => match &entry.value_os {
+                Some( value ) => value.as_os_str(),
+                None => OsStr::new( &entry.value )
+            },
+            None => OsStr::new( "" )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn get_value_path( &self, option : &str ) -> &Path {
+        Path::new( self.get_value_os( option ) )
+    }
+
+    pub fn get_value_or( &self, option : &str, fallback : &str ) -> String {
+        if self.was_set( option ) {
+            self.get_value( option )
+        } else {
+            fallback.to_string()
+        }
+    }
+
+    // how many times the option appeared in the args. 0 for absent
+    pub fn occurrences_of( &self, option : &str ) -> usize {
+        match self.find( option ){
+            Some( entry ) => entry.occurrences,
+            None => 0
+        }
+    }
+
+    pub fn get_source( &self, option : &str ) -> Option<&ValueSource> {
+        self.find( option ).map( |entry| &entry.source )
+    }
+
+    // true: the value was specified explicitly rather than the default
+    pub fn was_set( &self, option : &str ) -> bool {
+        match self.get_source( option ){
+            Some( source ) => *source != ValueSource::Default,
+            None => false
+        }
+    }
+
+    // ( name, value, source ) of the parsed options in the registered order, stable across runs
+    pub fn iter_values<'a>( &'a self ) -> impl Iterator<Item = ( &'a str, &'a str, &'a ValueSource )> + 'a {
+        self.entries.iter().map( |entry| ( entry.name.as_str(), entry.value.as_str(), &entry.source ) )
+    }
+
+    // the options whose values differ from the other e.g. this run and the baseline. the secrets are compared as masked
+    pub fn diff( &self, other : &Matches ) -> Vec<ValueDiff> {
+        diff_values( self.iter_display_values(), other.iter_display_values() )
+    }
+
+    // iter_values() with the secrets masked
+    pub(crate) fn iter_display_values( &self ) -> impl Iterator<Item = ( &str, &str, &ValueSource )> {
+        self.entries.iter().map( |entry| ( entry.name.as_str(), entry.display_value(), &entry.source ) )
+    }
+
+    #[cfg(feature = "std")]
+    // name -> value. the secrets are masked, get them by get_value()
+    pub fn into_map( self ) -> HashMap<String, String> {
+        self.entries.into_iter().map( |entry| {
+            let value = if entry.is_secret { REDACTED.to_string() } else { entry.value };
+            ( entry.name, value )
+        }).collect()
+    }
+
+    // name -> value in the sorted order. the secrets are masked, get them by get_value()
+    pub fn to_btreemap( &self ) -> BTreeMap<String, String> {
+        self.entries.iter().map( |entry| ( entry.name.clone(), entry.display_value().to_string() ) ).collect()
+    }
+
+    // { "name" : value, ... } in the registered order. the flags and the Integer, Float and Bool options are the JSON types, the greedy ones are the arrays
+    // and the others are the strings. the secrets are masked. same as the Serialize of the serde feature
+    pub fn to_json( &self ) -> String {
+        let mut result = String::from( "{" );
+        for ( i, entry ) in self.entries.iter().enumerate() {
+            if i != 0 {
+                result.push( ',' );
+            }
+            write_json_str( &mut result, &entry.name );
+            result.push( ':' );
+            entry.json_value().write( &mut result );
+        }
+        result.push( '}' );
+        result
+    }
+
+    // true: -h or --help was found
+    pub fn is_help_requested( &self ) -> bool {
+        self.is_help
+    }
+
+    // --help for the detailed help rather than -h
+    pub fn is_long_help_requested( &self ) -> bool {
+        self.is_long_help
+    }
+
+    // --help-all for the help with the advanced options
+    pub fn is_help_all_requested( &self ) -> bool {
+        self.is_help_all
+    }
+
+    // the option of --help samplingRate or --help=-s. None for the help of everything
+    pub fn get_help_topic( &self ) -> Option<&str> {
+        self.help_topic.as_deref()
+    }
+
+    pub fn get_args_count(&self) -> usize {
+        self.arg_values.len()
+    }
+
+    // the options and the args interleaved in the order given e.g. to associate the options with the file they followed
+    //   -i a.pcm --gain 3 -i b.pcm --gain 6
+    pub fn get_ordered_matches( &self ) -> &[MatchEvent] {
+        &self.events
+    }
+
+    // the args as given to the parse. empty without set_keep_raw_args( true )
+    pub fn get_raw_args( &self ) -> &[String] {
+        &self.raw_args
+    }
+
+    // the raw args after the first "--" e.g. [ "make", "-j4" ] for "--verbose -- make -j4", to run them by exec::run()
+    pub fn get_passthrough_args( &self ) -> &[String] {
+        &self.passthrough_args
+    }
+
+    #[cfg(feature = "std")]
+    // get_passthrough_args() as given. this differs only when the arg isn't valid UTF-8
+    pub fn get_passthrough_args_os( &self ) -> &[OsString] {
+        &self.passthrough_args_os
+    }
+
+    // the position in the args where the option was given last e.g. for the diagnostics. None if it wasn't given
+    pub fn get_option_position( &self, option : &str ) -> Option<usize> {
+        self.find( option ).and_then( |entry| entry.position )
+    }
+
+    // the position in the args of the arg get_args( index )
+    pub fn get_arg_position( &self, index : usize ) -> Option<usize> {
+        self.arg_positions.get( index ).copied()
+    }
+
+    // the unknown options which were neither the options nor the args e.g. -x in "-x 1 in.wav". the 1 is an arg
+    pub fn get_ignored_args( &self ) -> &[String] {
+        &self.ignored_args
+    }
+
+    pub fn get_args(&self, index : usize ) -> String {
+        let mut result = String::from("");
+        if index < self.get_args_count() {
+            result = self.arg_values[ index ].to_string();
+        }
+        result
+    }
+
+    #[cfg(feature = "std")]
+    // the arg as given. this differs from get_args() only when the arg isn't valid UTF-8
+    pub fn get_arg_os( &self, index : usize ) -> &OsStr {
+        match ( self.arg_values_os.get( index ), self.arg_values.get( index ) ) {
+            ( Some( Some( arg ) ), _ ) => arg.as_os_str(),
+            ( _, Some( arg ) ) => OsStr::new( arg ),
+            _ => OsStr::new( "" )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn get_arg_path( &self, index : usize ) -> &Path {
+        Path::new( self.get_arg_os( index ) )
+    }
+}
+
+// the secrets are masked, and the raw args are omitted as they can have the secrets
+impl core::fmt::Debug for Matches
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        let events : Vec<MatchEvent> = self.events.iter().map( |event| match event {
+            MatchEvent::Option { name, position, .. } if self.find( name ).is_some_and( |entry| entry.is_secret ) => MatchEvent::Option { name : name.clone(), value : REDACTED.to_string(), position : *position },
+            event => event.clone(),
+        }).collect();
+        let mut result = f.debug_struct( "Matches" );
+        result.field( "settings", &self.settings )
+            .field( "entries", &self.entries )
+            .field( "keys", &self.keys )
+            .field( "arg_values", &self.arg_values )
+            .field( "arg_positions", &self.arg_positions )
+            .field( "arg_count", &self.arg_count )
+            .field( "passthrough_args", &self.passthrough_args )
+            .field( "ignored_args", &self.ignored_args )
+            .field( "events", &events );
+        #[cfg(feature = "std")]
+        result.field( "arg_values_os", &self.arg_values_os )
+            .field( "passthrough_args_os", &self.passthrough_args_os );
+        result.field( "is_help", &self.is_help )
+            .field( "is_long_help", &self.is_long_help )
+            .field( "is_help_all", &self.is_help_all )
+            .field( "help_topic", &self.help_topic )
+            .field( "error", &self.error )
+            .field( "violations", &self.violations )
+            .field( "renamed", &self.renamed )
+            .finish_non_exhaustive()
+    }
+}
+
+// name -> the typed value in the registered order, same as to_json() e.g. serde_json::to_string( opt_parse.get_matches() )
+#[cfg(feature = "serde")]
+impl serde::Serialize for Matches
+{
+    fn serialize<S : serde::Serializer>( &self, serializer : S ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map( Some( self.entries.len() ) )?;
+        for entry in &self.entries {
+            map.serialize_entry( &entry.name, &entry.json_value() )?;
+        }
+        map.end()
+    }
+}
+
+
+// "value" or 'value' -> value
+fn strip_quotes( value : &str ) -> &str {
+    for quote in [ '"', '\'' ] {
+        if value.len() >= 2 && value.starts_with( quote ) && value.ends_with( quote ) {
+            return &value[ 1..value.len() - 1 ];
+        }
+    }
+    value
+}
+
+// value # comment -> value, the # in the quotes is kept
+pub(crate) fn strip_comment( line : &str ) -> &str {
+    let mut quote = None;
+    let mut is_escaped = false;
+    for ( i, c ) in line.char_indices() {
+        match ( quote, c ) {
+            ( Some( '"' ), '\\' ) if !is_escaped => { is_escaped = true; continue; },
+            ( Some( q ), c ) if c == q && !is_escaped => quote = None,
+            ( None, '"' ) | ( None, '\'' ) => quote = Some( c ),
+            ( None, '#' ) => return &line[..i],
+            _ => {},
+        }
+        is_escaped = false;
+    }
+    line
+}
+
+fn write_json_str( result : &mut String, value : &str ) {
+    result.push( '"' );
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str( "\\\"" ),
+            '\\' => result.push_str( "\\\\" ),
+            '\n' => result.push_str( "\\n" ),
+            '\r' => result.push_str( "\\r" ),
+            '\t' => result.push_str( "\\t" ),
+            c if ( c as u32 ) < 0x20 => { let _ = write!( result, "\\u{:04x}", c as u32 ); },
+            c => result.push( c ),
+        }
+    }
+    result.push( '"' );
+}
+
+// the parsed result borrowing the values from the args and the OptParseItems instead of copying them
+// the normalizers are not applied as those would need the owned values, and the actions are not run
+#[derive(Clone, Debug, PartialEq)]
+pub struct BorrowedMatches<'a>
+{
+    index : &'a FlagIndex,
+    values : Vec<&'a str>,          // the value of options[i]
+    occurrences : Vec<usize>,
+    arg_values : Vec<&'a str>,
+    is_success : bool,
+}
+
+impl<'a> BorrowedMatches<'a>
+{
+    fn new<S : AsRef<str>>( args : &'a [S], options : &'a [OptParseItem], index : &'a FlagIndex ) -> Self {
+        let mut result = Self {
+            index,
+            values : options.iter().map( |option| option.value.as_str() ).collect(),
+            occurrences : vec![ 0; options.len() ],
+            arg_values : Vec::new(),
+            is_success : true,
+        };
+        scan_args( args, options, index, |token| {
+            match token {
+                Token::Help( _, _ ) => {},
+                Token::Value( index, value, _ ) => {
+                    match value.or( options[ index ].missing_value.as_deref() ) {
+                        Some( value ) => {
+                            if !options[ index ].is_valid_value( value ) {
+                                result.is_success = false;
+                            }
+                            result.values[ index ] = value;
+                        },
+                        None => { result.is_success = false; }
+                    }
+                    result.occurrences[ index ] += 1;
+                },
+                Token::MoreValue( index, value ) => {
+                    if !options[ index ].is_valid_value( value ) {
+                        result.is_success = false;
+                    }
+                    result.values[ index ] = value;
+                },
+                Token::Arg( arg ) => { result.arg_values.push( arg ); },
+                Token::Ignored( _ ) | Token::Renamed( _, _ ) | Token::HelpTopic( _ ) | Token::Passthrough( _ ) => {}
+            }
+        });
+        result
+    }
+
+    pub fn is_success( &self ) -> bool {
+        self.is_success
+    }
+
+    pub fn get_value( &self, option : &str ) -> &'a str {
+        match self.index.find( option ){
+            Some( index ) => self.values[ index ],
+            None => ""
+        }
+    }
+
+    pub fn was_set( &self, option : &str ) -> bool {
+        match self.index.find( option ){
+            Some( index ) => self.occurrences[ index ] > 0,
+            None => false
+        }
+    }
+
+    pub fn get_args_count( &self ) -> usize {
+        self.arg_values.len()
+    }
+
+    pub fn get_args( &self, index : usize ) -> &'a str {
+        match self.arg_values.get( index ){
+            Some( arg ) => arg,
+            None => ""
+        }
+    }
+}
+
+
+#[cfg(feature = "std")]
+// ask the value e.g. "Set encoding (PCM8, PCM16) [PCM16]: " until one of the choices. the empty answer is returned as is, None at the end of the input
+fn ask_option<R : std::io::BufRead, W : std::io::Write>( option : &OptParseItem, input : &mut R, output : &mut W, set_echo : &mut dyn FnMut( bool ) -> std::io::Result<()> ) -> std::io::Result<Option<String>> {
+    loop {
+        write!( output, "{}", option.description )?;
+        if !option.choices.is_empty() {
+            write!( output, " ({})", option.choices.join( ", " ) )?;
+        }
+        if !option.value.is_empty() && !option.is_password {
+            write!( output, " [{}]", option.value )?;
+        }
+        write!( output, ": " )?;
+        output.flush()?;
+        let mut answer = String::new();
+        if option.is_password {
+            // never read the password with the echo
+            set_echo( false )?;
+            let result = input.read_line( &mut answer );
+            let restored = set_echo( true );
+            // the enter wasn't echoed
+            writeln!( output )?;
+            result?;
+            restored?;
+        } else {
+            input.read_line( &mut answer )?;
+        }
+        if answer.is_empty() {
+            return Ok( None );
+        }
+        let answer = option.normalize( answer.trim_end_matches( ['\r', '\n'] ) );
+        if answer.is_empty() {
+            return Ok( Some( answer.into_owned() ) );
+        }
+        match option.convert( &answer ) {
+            Ok( _ ) => return Ok( Some( answer.into_owned() ) ),
+            Err( message ) => writeln!( output, "{}", message )?,
+        }
+    }
+}
+
+// the row of the help layout. the description starts on the next line when the flags are wider than max_option_width,
+// and its continuation lines are indented to the description column
+fn write_help_row( result : &mut String, layout : &HelpLayout, flags : &str, max_flags_len : usize, description : &str ) {
+    let width = if layout.max_option_width > 0 { cmp::min( max_flags_len, layout.max_option_width ) } else { max_flags_len };
+    let column = layout.indent + width + layout.gap;
+    let flags_len = flags.chars().count();
+    let _ = write!( result, "{:indent$}{}", "", flags, indent = layout.indent );
+    if flags_len > width {
+        let _ = write!( result, "\n{:indent$}", "", indent = column );
+    } else {
+        let _ = write!( result, "{:pad$}", "", pad = width - flags_len + layout.gap );
+    }
+    // too narrow to wrap is rather not wrapped
+    let wrap_width = if layout.width > column + 10 { layout.width - column } else { 0 };
+    for ( i, line ) in wrap_lines( description, wrap_width ).iter().enumerate() {
+        if i > 0 {
+            let _ = write!( result, "{:indent$}", "", indent = column );
+        }
+        let _ = writeln!( result, "{}", line );
+    }
+}
+
+// the lines of the text split at \n and wrapped at the spaces within the width. 0 for no wrap
+fn wrap_lines( text : &str, width : usize ) -> Vec<String> {
+    let mut result = Vec::new();
+    for paragraph in text.split( '\n' ) {
+        if width == 0 {
+            result.push( paragraph.to_string() );
+            continue;
+        }
+        let mut line = String::new();
+        for word in paragraph.split( ' ' ) {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                result.push( core::mem::take( &mut line ) );
+            } else if !line.is_empty() {
+                line.push( ' ' );
+            }
+            line.push_str( word );
+        }
+        result.push( line );
+    }
+    result
+}
+
+#[cfg(all(feature = "std", unix))]
+// by termios of stdin. nothing to hide when stdin isn't the terminal e.g. piped
+fn set_terminal_echo( enable : bool ) -> std::io::Result<()> {
+    let fd = libc::STDIN_FILENO;
+    // SAFETY: termios is plain data filled by tcgetattr() before it's read
+    unsafe {
+        if libc::isatty( fd ) == 0 {
+            return Ok( () );
+        }
+        let mut termios : libc::termios = core::mem::zeroed();
+        if libc::tcgetattr( fd, &mut termios ) != 0 {
+            return Err( std::io::Error::last_os_error() );
+        }
+        if enable {
+            termios.c_lflag |= libc::ECHO;
+        } else {
+            termios.c_lflag &= !libc::ECHO;
+        }
+        if libc::tcsetattr( fd, libc::TCSANOW, &termios ) != 0 {
+            return Err( std::io::Error::last_os_error() );
+        }
+    }
+    Ok( () )
+}
+
+#[cfg(all(feature = "std", windows))]
+// by the console mode of stdin. nothing to hide when stdin isn't the console e.g. piped
+fn set_terminal_echo( enable : bool ) -> std::io::Result<()> {
+    const STD_INPUT_HANDLE : u32 = -10i32 as u32;
+    const ENABLE_ECHO_INPUT : u32 = 0x0004;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle( std_handle : u32 ) -> *mut core::ffi::c_void;
+        fn GetConsoleMode( console : *mut core::ffi::c_void, mode : *mut u32 ) -> i32;
+        fn SetConsoleMode( console : *mut core::ffi::c_void, mode : u32 ) -> i32;
+    }
+    // SAFETY: the handle is only passed back to the console APIs which validate it
+    unsafe {
+        let handle = GetStdHandle( STD_INPUT_HANDLE );
+        let mut mode = 0;
+        if GetConsoleMode( handle, &mut mode ) == 0 {
+            return Ok( () );
+        }
+        let mode = if enable { mode | ENABLE_ECHO_INPUT } else { mode & !ENABLE_ECHO_INPUT };
+        if SetConsoleMode( handle, mode ) == 0 {
+            return Err( std::io::Error::last_os_error() );
+        }
+    }
+    Ok( () )
+}
+
+#[cfg(all(feature = "std", not(any(unix, windows))))]
+// no terminal API here, so the password isn't read rather than echoed
+fn set_terminal_echo( enable : bool ) -> std::io::Result<()> {
+    match enable {
+        true => Ok( () ),
+        false => Err( std::io::Error::new( std::io::ErrorKind::Unsupported, "can't turn off the echo to read the password" ) ),
+    }
+}
+
+// the conflict once per pair, as the both options can declare it e.g. --verbose and --quiet
+fn push_conflict( violations : &mut Vec<OptParseError>, name : &str, other : &str ) {
+    let is_reported = violations.iter().any( |violation| matches!( violation, OptParseError::ConflictingOptions { name : a, other : b } if ( a == name && b == other ) || ( a == other && b == name ) ) );
+    if !is_reported {
+        violations.push( OptParseError::ConflictingOptions { name : name.to_string(), other : other.to_string() } );
+    }
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct OptParse
+{
+    args : Vec<String>,
+    options : Vec<OptParseItem>,
+    matches : Matches,
+    description : String,
+    settings : ParseSettings,
+    index : FlagIndex,
+    commands : CommandRegistry,
+    providers : ProviderRegistry,
+    positionals : Vec<( String, String, Vec<String> )>,  // the names, the descriptions for the help and the accepted extensions of the positional args
+    catalog : Option<CatalogRef>,   // the translations of the messages. None for the English
+    preprocessors : Vec<Preprocessor>,  // applied to the args in the order before the parse
+    middlewares : Vec<Middleware>,  // run in the order after the parse
+    profiles : Vec<( String, Vec<( String, String )> )>,   // the names and the option values of the presets for --profile
+    config_values : Vec<( String, String, String )>,    // the option, its value and the origin e.g. "config file ~/.mytool.toml" from the config files
+}
+
+// the raw args are omitted as they can have the secrets, the matches mask them
+impl core::fmt::Debug for OptParse
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.debug_struct( "OptParse" )
+            .field( "options", &self.options )
+            .field( "matches", &self.matches )
+            .field( "description", &self.description )
+            .field( "settings", &self.settings )
+            .field( "index", &self.index )
+            .field( "commands", &self.commands )
+            .field( "providers", &self.providers )
+            .field( "positionals", &self.positionals )
+            .field( "catalog", &self.catalog )
+            .field( "preprocessors", &self.preprocessors )
+            .field( "middlewares", &self.middlewares )
+            .field( "profiles", &self.profiles )
+            .field( "config_values", &self.config_values )
+            .finish_non_exhaustive()
+    }
+}
+
+impl IOptParse for OptParse
+{
+    fn new( args : Vec<String>, options : Vec<OptParseItem>, description : &str ) -> Self {
+        let settings = ParseSettings {
+            #[cfg(feature = "std")]
+            posixly_correct : std::env::var_os( "POSIXLY_CORRECT" ).is_some(),
+            ..Default::default()
+        };
+        Self {
+            args,
+            index : FlagIndex::new( &options, &settings ),
+            options,
+            matches : Matches::default(),
+            description : description.to_string(),
+            settings,
+            commands : CommandRegistry::default(),
+            providers : ProviderRegistry::default(),
+            positionals : Vec::new(),
+            catalog : None,
+            preprocessors : Vec::new(),
+            middlewares : Vec::new(),
+            profiles : Vec::new(),
+            config_values : Vec::new(),
+        }
+    }
+
+    fn parse_options_with_required_args( &mut self, is_finish_if_help : bool, num_of_min_required_args : i32, num_of_max_required_args : i32 ) -> bool {
+        let mut result = self.parse_options( is_finish_if_help );
+        let current_num_of_args : i32 = ( self.get_args_count() - 1 ).try_into().unwrap();
+
+        if ( current_num_of_args < num_of_min_required_args ) || ( ( num_of_max_required_args >= 0 ) && ( current_num_of_args > num_of_max_required_args ) ) {
+            result = false;
+            #[cfg(feature = "std")]
+            {
+                if self.settings.auto_help {
+                    self.print_help();
+                }
+                if is_finish_if_help {
+                    exit_process( 0 );
+                }
+            }
+        }
+
+        result
+    }
+
+
+    fn parse_options( &mut self, is_finish_if_help : bool ) -> bool {
+        self.prepare_parse();
+        let state = if self.preprocessors.is_empty() {
+            self.matches.parse_args( &self.args, &self.options, &self.index, true )
+        } else {
+            let args = self.preprocess( self.args.clone() );
+            self.matches.parse_args( &args, &self.options, &self.index, true )
+        };
+        self.finish_parse( state, is_finish_if_help )
+    }
+
+    // same as parse_options() but consuming the args as they arrive instead of the args given to new().
+    // the args are collected first when add_preprocessor() is used
+    fn parse_options_from_iter<I : IntoIterator<Item = String>>( &mut self, args : I, is_finish_if_help : bool ) -> bool {
+        self.prepare_parse();
+        let state = if self.preprocessors.is_empty() {
+            self.matches.parse_iter( args.into_iter(), &self.options, &self.index, true )
+        } else {
+            let args = self.preprocess( args.into_iter().collect() );
+            self.matches.parse_args( &args, &self.options, &self.index, true )
+        };
+        self.finish_parse( state, is_finish_if_help )
+    }
+
+    // the args in one shell style string split by shell::split(). false for the unterminated quote
+    fn parse_from_str( &mut self, line : &str, is_finish_if_help : bool ) -> bool {
+        match shell::split( line ) {
+            Ok( args ) => self.parse_options_from_iter( args, is_finish_if_help ),
+            Err( _ ) => false
+        }
+    }
+
+    #[cfg(feature = "std")]
+    // same as parse_options_from_iter() for e.g. env::args_os(). the args don't need to be valid UTF-8
+    // with add_preprocessor(), the preprocessors see the lossy conversion of the args not valid UTF-8
+    fn parse_options_os<I : IntoIterator<Item = OsString>>( &mut self, args : I, is_finish_if_help : bool ) -> bool {
+        if !self.preprocessors.is_empty() {
+            let args = args.into_iter().map( |arg| arg.to_string_lossy().into_owned() ).collect();
+            return self.parse_options_from_iter::<Vec<String>>( args, is_finish_if_help );
+        }
+        self.prepare_parse();
+        let state = self.matches.parse_iter_os( args.into_iter(), &self.options, &self.index, true );
+        self.finish_parse( state, is_finish_if_help )
+    }
+
+    fn parse_option( &mut self, option : &OptParseItem ) -> bool {
+        self.matches.settings = self.settings.clone();
+        self.matches.register_option( option );
+        let options = core::slice::from_ref( option );
+        let ( result, _ ) = self.matches.parse_args( &self.args, options, &FlagIndex::new( options, &self.settings ), false );
+        result
+    }
+
+    // the first phase of the two-phase parse: only the options given here are parsed from the args e.g. --config, --verbose and --color,
+    // so that their values can decide the rest of the options before the full parse. the other args are skipped and -h isn't handled.
+    // the early options need to be in the full options too, or their values would be the args of the full parse
+    fn parse_early<S : AsRef<str>>( &self, args : &[S], options : &[OptParseItem] ) -> Matches {
+        let index = FlagIndex::new( options, &self.settings );
+        let mut matches = Matches::new( &self.settings );
+        for option in options {
+            matches.register_option( option );
+        }
+        let args = self.preprocess( args.iter().map( |arg| arg.as_ref().to_string() ).collect() );
+        let _ = matches.parse_args( &args, options, &index, false );
+        matches
+    }
+
+    // forget the args and the parsed state. the values are back to the defaults and the options, the settings stay
+    fn reset( &mut self ) {
+        self.args.clear();
+        self.prepare_parse();
+    }
+
+    // reuse this configured parser for the other args e.g. the next line of the REPL
+    fn parse_again( &mut self, args : Vec<String>, is_finish_if_help : bool ) -> bool {
+        self.args = args;
+        self.parse_options( is_finish_if_help )
+    }
+
+    // hand the raw args back once parsed. the parsed values and args stay available
+    fn take_args( &mut self ) -> Vec<String> {
+        core::mem::take( &mut self.args )
+    }
+
+    // parse the given args without copying them. the help isn't handled
+    fn parse_options_borrowed<'a, S : AsRef<str>>( &'a self, args : &'a [S] ) -> BorrowedMatches<'a> {
+        BorrowedMatches::new( args, &self.options, &self.index )
+    }
+
+    #[cfg(feature = "std")]
+    fn print_help(&self){
+        print!( "{}", self.styled_help( self.get_help(), false ) );
+    }
+
+    // e.g. the console of the WASI host instead of stdout
+    #[cfg(feature = "std")]
+    fn write_help<W : std::io::Write>( &self, writer : &mut W ) -> std::io::Result<()> {
+        writer.write_all( self.get_help().as_bytes() )
+    }
+
+    // false: parsing doesn't print the help for -h or --help
+    fn set_auto_help( &mut self, enable : bool ) {
+        self.settings.auto_help = enable;
+    }
+
+    // the help in the columns of the spaces instead of the tabs
+    fn set_help_layout( &mut self, layout : HelpLayout ) {
+        self.settings.help_layout = Some( layout );
+    }
+
+    // wrap the help of the layout at the width regardless of HelpLayout::width, COLUMNS and the terminal e.g. for the help snapshots. 0 for no wrap
+    fn set_terminal_width( &mut self, width : usize ) {
+        self.settings.terminal_width = Some( width );
+    }
+
+    // the wrap width of the printed help: set_terminal_width(), HelpLayout::width, then COLUMNS or the terminal's. 0 for no wrap
+    fn get_terminal_width( &self ) -> usize {
+        if let Some( width ) = self.settings.terminal_width {
+            return width;
+        }
+        match self.settings.help_layout.as_ref().map( |layout| layout.width ) {
+            Some( width ) if width > 0 => width,
+            #[cfg(feature = "std")]
+            _ => tty::terminal_width().unwrap_or( 0 ),
+            #[cfg(not(feature = "std"))]
+            _ => 0,
+        }
+    }
+
+    // --version prints the version e.g. set_version( "mytool 1.2.0" ) and exits as -h does. the subcommands print theirs, see Command::version()
+    fn set_version( &mut self, version : &str ) {
+        self.settings.version = Some( version.to_string() );
+        if self.get_option_item( "version" ).is_none() {
+            self.options.push( OptParseItem::new( "", "--version", false, "false", "Print the version" ) );
+            self.index = FlagIndex::new( &self.options, &self.settings );
+        }
+    }
+
+    fn get_version( &self ) -> Option<&str> {
+        self.settings.version.as_deref()
+    }
+
+    // the version printed by "mytool convert --version". the tool's one unless the command has its own
+    fn get_command_version( &self, name : &str ) -> Option<&str> {
+        self.commands.find( name ).and_then( |command| command.version() ).or( self.get_version() )
+    }
+
+    // the help has Flags: for the value-less options and Options: for the value-taking ones
+    fn set_help_sections( &mut self, enable : bool ) {
+        self.settings.help_sections = enable;
+    }
+
+    // the positional arg shown in Arguments: of the help e.g. add_positional( "INPUT", "Input wav file" ).
+    // the parse is the same except the values of with_value_count() beyond the max are the args instead of the error
+    fn add_positional( &mut self, name : &str, description : &str ) {
+        self.positionals.push( ( name.to_string(), description.to_string(), Vec::new() ) );
+        self.settings.positional_args = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the positional arg of the path with one of the extensions e.g. add_positional_with_extensions( "INPUT", "Input file", &[ ".pcm", ".wav" ] ).
+    // the last positional also checks the following args
+    fn add_positional_with_extensions( &mut self, name : &str, description : &str, extensions : &[&str] ) {
+        self.positionals.push( ( name.to_string(), description.to_string(), to_extensions( extensions ) ) );
+        self.settings.positional_args = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // wrapped at set_terminal_width() or HelpLayout::width. the printed help by -h is also wrapped at COLUMNS or the terminal's
+    fn get_help( &self ) -> String {
+        self.help_text( false, Visibility::Standard, self.settings.terminal_width.unwrap_or( self.settings.help_layout.as_ref().map_or( 0, |layout| layout.width ) ) )
+    }
+
+    // get_help() with the long descriptions, printed for --help
+    fn get_long_help( &self ) -> String {
+        self.help_text( true, Visibility::Standard, self.settings.terminal_width.unwrap_or( self.settings.help_layout.as_ref().map_or( 0, |layout| layout.width ) ) )
+    }
+
+    // get_long_help() with the advanced options, printed for --help-all
+    fn get_help_all( &self ) -> String {
+        self.help_text( true, Visibility::Advanced, self.settings.terminal_width.unwrap_or( self.settings.help_layout.as_ref().map_or( 0, |layout| layout.width ) ) )
+    }
+
+    // all the details of the option, printed for --help samplingRate. None for the unknown option
+    fn get_option_help( &self, option : &str ) -> Option<String> {
+        let option = self.get_option_item( option )?;
+        let mut result = String::new();
+        match self.get_env_var( &option.name ) {
+            Some( var ) => { let _ = writeln!( result, "{}", var ); },
+            None if option.arg_required => { let _ = writeln!( result, "{} <VALUE>", self.help_flags( option ).trim_start() ); },
+            None => { let _ = writeln!( result, "{}", self.help_flags( option ).trim_start() ); },
+        }
+        for line in [ option.description.as_str(), option.long_description.as_str() ].iter().flat_map( |text| text.lines() ) {
+            let _ = writeln!( result, "  {}", line );
+        }
+        if option.is_required {
+            let _ = writeln!( result, "  Required" );
+        } else if option.arg_required && !option.value.is_empty() {
+            let _ = writeln!( result, "  Default: {}", if option.is_secret { REDACTED } else { option.value.as_str() } );
+        }
+        if !option.choices.is_empty() {
+            let _ = writeln!( result, "  Possible values: {}", option.choices.join( ", " ) );
+        }
+        if let ValueType::Unit( units ) = &option.value_type {
+            let _ = writeln!( result, "  Units: {}", units.suffixes() );
+        }
+        if !option.extensions.is_empty() {
+            let _ = writeln!( result, "  Extensions: {}", option.extensions.join( ", " ) );
+        }
+        if !option.conflicts.is_empty() {
+            let _ = writeln!( result, "  Conflicts with: {}", option.conflicts.join( ", " ) );
+        }
+        if !option.examples.is_empty() {
+            let _ = writeln!( result, "  Examples:" );
+            for example in &option.examples {
+                let _ = writeln!( result, "    {}", example );
+            }
+        }
+        Some( result )
+    }
+
+    fn is_help_requested( &self ) -> bool {
+        self.matches.is_help_requested()
+    }
+
+    fn get_options( &self ) -> &[OptParseItem] {
+        &self.options
+    }
+
+    // the item for any of "-s", "--samplingRate" or the name
+    fn get_option_item( &self, option : &str ) -> Option<&OptParseItem> {
+        match self.index.find( option ){
+            Some( index ) => self.options.get( index ),
+            None => None
+        }
+    }
+
+    fn get_description( &self ) -> &str {
+        &self.description
+    }
+
+    fn set_auto_short_option( &mut self, enable : bool ) {
+        self.settings.auto_short_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_normalize_full_option( &mut self, enable : bool ) {
+        self.settings.normalize_full_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_ignore_case_full_option( &mut self, enable : bool ) {
+        self.settings.ignore_case_full_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_windows_style_option( &mut self, enable : bool ) {
+        self.settings.windows_style_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_posixly_correct( &mut self, enable : bool ) {
+        self.settings.posixly_correct = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // ${VAR} in the given and the default values, e.g. the default "${HOME}/renders". only with std
+    fn set_interpolate_env( &mut self, enable : bool ) {
+        self.settings.interpolate_env = enable;
+    }
+
+    // -x sets "true" and +x sets "false" to the flag -x
+    fn set_plus_toggle_option( &mut self, enable : bool ) {
+        self.settings.plus_toggle_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // -samplingRate 44100 and -encoding=PCM16 for --samplingRate and --encoding. the short options are still matched first
+    fn set_single_dash_long_option( &mut self, enable : bool ) {
+        self.settings.single_dash_long_option = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // --level:5 and -s:44100, and -encoding:PCM16 with set_single_dash_long_option(). the value is after the first ':' or '='
+    fn set_colon_value_separator( &mut self, enable : bool ) {
+        self.settings.colon_value_separator = enable;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the arg ending the values of OptParseItem::with_greedy_values(). ";" by default, which needs the quotes in the shell as find -exec's
+    fn set_value_terminator( &mut self, terminator : &str ) {
+        self.settings.value_terminator = terminator.to_string();
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // enforce --name=value or --name value for the long options taking the values. the short options and the windows style are as before
+    fn set_long_value_syntax( &mut self, syntax : LongValueSyntax ) {
+        self.settings.long_value_syntax = syntax;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the retired flag still works as the new one with the warning e.g. add_renamed_option( "--rate", "--samplingRate" ). the value is of the new one
+    fn add_renamed_option( &mut self, old : &str, new : &str ) {
+        self.settings.renamed_flags.push( ( old.to_string(), new.to_string() ) );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // at least one of the options e.g. add_required_one_of( &[ "input", "device", "generate" ] ) or the parse fails with OptParseError::MissingOneOf
+    fn add_required_one_of( &mut self, names : &[&str] ) {
+        self.settings.required_groups.push( ( names.iter().map( |name| name.to_string() ).collect(), false ) );
+    }
+
+    // add_required_one_of() and more than one are OptParseError::ConflictingOptions
+    fn add_required_exactly_one_of( &mut self, names : &[&str] ) {
+        self.settings.required_groups.push( ( names.iter().map( |name| name.to_string() ).collect(), true ) );
+    }
+
+    // ( the option names, true for exactly one ) of add_required_one_of() and add_required_exactly_one_of()
+    fn get_required_groups( &self ) -> &[( Vec<String>, bool )] {
+        &self.settings.required_groups
+    }
+
+    fn set_env_prefix( &mut self, prefix : &str ) {
+        self.settings.env_prefix = prefix.to_string();
+    }
+
+    // the environment variable of the env only entry e.g. "MYTOOL_API_TOKEN". None for the options with the flags
+    fn get_env_var( &self, option : &str ) -> Option<String> {
+        self.get_option_item( option ).filter( |item| item.is_env_only ).map( |item| env::var_name( &self.settings.env_prefix, &item.name ) )
+    }
+
+    #[cfg(feature = "config")]
+    // the values of the config file, see config::read_str(). the args and the environment variables override these and these override --profile
+    fn add_config_from_str( &mut self, text : &str ) -> Result<(), OptParseError> {
+        self.add_config_from_source( text, "" )
+    }
+
+    #[cfg(feature = "config")]
+    // add_config_from_str() of the file at the path, which the errors tell
+    // e.g. add_config_from_source( &std::fs::read_to_string( &path )?, "~/.mytool.toml" )
+    fn add_config_from_source( &mut self, text : &str, path : &str ) -> Result<(), OptParseError> {
+        let origin = if path.is_empty() { "config file".to_string() } else { format!( "config file {}", path ) };
+        let with_origin = |error : OptParseError| OptParseError::WithOrigin { error : Box::new( error ), origin : origin.clone() };
+        for ( key, value ) in config::read_str( text ).map_err( with_origin )? {
+            if self.index.find( &key ).is_none() {
+                return Err( with_origin( OptParseError::UnknownOption { name : key } ) );
+            }
+            self.config_values.push( ( key, value, origin.clone() ) );
+        }
+        Ok( () )
+    }
+
+    // the deprecation warnings of the parse e.g. "warning: --rate is deprecated, use --samplingRate instead". these are printed to stderr
+    fn get_warnings( &self ) -> Vec<String> {
+        let catalog : &dyn MessageCatalog = match &self.catalog {
+            Some( catalog ) => catalog.0.as_ref(),
+            None => &i18n::DefaultCatalog,
+        };
+        self.matches.renamed.iter().map( |( old, new )| i18n::message( catalog, MessageKey::RenamedOption, &[ ( "old", old.clone() ), ( "new", new.clone() ) ] ) ).collect()
+    }
+
+    // for the option values, use Normalizer::ExpandTilde
+    fn set_expand_tilde_args( &mut self, enable : bool ) {
+        self.settings.expand_tilde_args = enable;
+    }
+
+    fn set_strip_quotes( &mut self, enable : bool ) {
+        self.settings.strip_quotes = enable;
+    }
+
+    fn set_prompt_missing( &mut self, enable : bool ) {
+        self.settings.prompt_missing = enable;
+    }
+
+    #[cfg(feature = "std")]
+    // ask the required options and the passwords not specified e.g. "Set sampling rate [48000]: ". the empty answer takes the default
+    fn prompt_missing<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W ) -> std::io::Result<()> {
+        self.prompt_missing_with( input, output, &mut |_| Ok( () ) )
+    }
+
+    // -y or --yes answers yes to confirm() without asking
+    fn add_yes_option( &mut self ) {
+        self.options.push( OptParseItem::new( "-y", "--yes", false, "false", "Assume yes to the confirmations" ) );
+        self.settings.yes_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // --color=auto|always|never for the help and the errors printed by this crate. see get_color_choice() for the application's output
+    #[cfg(feature = "color")]
+    fn add_color_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--color", true, "auto", "Color the output" ).with_value_enum::<color::ColorChoice>() );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the choice of --color. auto without add_color_option()
+    #[cfg(feature = "color")]
+    fn get_color_choice( &self ) -> color::ColorChoice {
+        color::ColorChoice::from_value( self.matches.get_value_ref( "color" ) ).unwrap_or_default()
+    }
+
+    // -v/--verbose and -q/--quiet, counted and exclusive. see get_verbosity()
+    fn add_verbosity_options( &mut self ) {
+        self.options.push( OptParseItem::new( "-v", "--verbose", false, "false", "More output, repeatable e.g. -v -v" ).with_conflicts( &[ "quiet" ] ) );
+        self.options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Less output, repeatable" ).with_conflicts( &[ "verbose" ] ) );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the level by add_verbosity_options(). error by default
+    fn get_verbosity( &self ) -> verbosity::LevelFilter {
+        self.get_level_filter( "verbose", "quiet" )
+    }
+
+    // --no-input disables the prompts and the confirmations as set_interactive( false )
+    fn add_no_input_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--no-input", false, "false", "Never ask for the input" ) );
+        self.settings.no_input_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_interactive( &mut self, enable : bool ) {
+        self.settings.interactive = enable;
+    }
+
+    // --print-defaults prints get_defaults() to stdout and exits as -h does
+    fn add_print_defaults_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--print-defaults", false, "false", "Print the default values and exit" ) );
+        self.settings.print_defaults_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // name=default per line in the order of the options, with the possible values e.g.
+    //   samplingRate=48000
+    //   encoding=PCM16	# possible values: PCM16, PCM24
+    // the defaults of the secrets are masked
+    fn get_defaults( &self ) -> String {
+        let mut result = String::new();
+        for option in self.options.iter().filter( |option| !self.is_builtin_option( &option.name ) ) {
+            let value = if option.is_secret { REDACTED.to_string() } else { shell::quote( &option.value ) };
+            let _ = write!( result, "{}={}", option.name, value );
+            if !option.choices.is_empty() {
+                let _ = write!( result, "\t# possible values: {}", option.choices.join( ", " ) );
+            }
+            result.push( '\n' );
+        }
+        result
+    }
+
+    // --dump-args prints get_dump_args() to stderr and continues, or exits by set_exit_after_dump_args( true )
+    fn add_dump_args_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--dump-args", false, "false", "Print the resolved values and their sources" ) );
+        self.settings.dump_args_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    fn set_exit_after_dump_args( &mut self, enable : bool ) {
+        self.settings.exit_after_dump_args = enable;
+    }
+
+    // name=value per line of the parsed results with where the value came from e.g.
+    //   samplingRate=44100	# command line
+    //   encoding=PCM16	# default
+    // the secrets are masked
+    fn get_dump_args( &self ) -> String {
+        let mut result = String::new();
+        for entry in self.matches.entries.iter().filter( |entry| !self.is_builtin_option( &entry.name ) ) {
+            let value = if entry.is_secret { REDACTED.to_string() } else { shell::quote( &entry.value ) };
+            let _ = writeln!( result, "{}={}\t# {}", entry.name, value, entry.source.label() );
+        }
+        result
+    }
+
+    // --profile <name> selects the preset of add_profile(). its values override the defaults and are overridden by the args
+    fn add_profile_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--profile", true, "", "Use the preset of the values" ) );
+        self.settings.profile_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the preset for --profile name e.g. add_profile( "cd-quality", &[ ( "samplingRate", "44100" ), ( "encoding", "PCM16" ) ] )
+    fn add_profile( &mut self, name : &str, values : &[( &str, &str )] ) {
+        let values = values.iter().map( |( option, value )| ( option.to_string(), value.to_string() ) ).collect();
+        match self.profiles.iter_mut().find( |( profile, _ )| profile == name ) {
+            Some( ( _, profile ) ) => *profile = values,
+            None => self.profiles.push( ( name.to_string(), values ) ),
+        }
+    }
+
+    // the presets in the sections of the config file e.g.
+    //   [cd-quality]
+    //   samplingRate = 44100
+    //   encoding = "PCM16"   # the quotes are optional
+    fn add_profiles_from_str( &mut self, text : &str ) -> Result<(), OptParseError> {
+        let mut profile : Option<( String, Vec<( String, String )> )> = None;
+        let mut profiles = Vec::new();
+        for ( i, line ) in text.lines().enumerate() {
+            let line = strip_comment( line ).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some( name ) = line.strip_prefix( '[' ).and_then( |line| line.strip_suffix( ']' ) ) {
+                profiles.extend( profile.take() );
+                profile = Some( ( name.trim().to_string(), Vec::new() ) );
+                continue;
+            }
+            let invalid = |message : &str| OptParseError::InvalidSpec { line : i + 1, message : message.to_string() };
+            let ( option, value ) = line.split_once( '=' ).ok_or_else( || invalid( "expected option = value" ) )?;
+            let ( _, values ) = profile.as_mut().ok_or_else( || invalid( "the value is outside of [profile]" ) )?;
+            values.push( ( option.trim().to_string(), strip_quotes( value.trim() ).to_string() ) );
+        }
+        profiles.extend( profile );
+        for ( name, values ) in profiles {
+            let values : Vec<( &str, &str )> = values.iter().map( |( option, value )| ( option.as_str(), value.as_str() ) ).collect();
+            self.add_profile( &name, &values );
+        }
+        Ok( () )
+    }
+
+    #[cfg(feature = "completions")]
+    // --install-completions=bash|zsh|fish writes the script by completions::install(), prints the instructions and exits as -h does
+    fn add_install_completions_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--install-completions", true, "", "Install the shell completion script and exit" ).with_value_enum::<completions::Shell>() );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    #[cfg(feature = "std")]
+    // true: stdin is a terminal and neither set_interactive( false ) nor --no-input
+    fn is_interactive( &self ) -> bool {
+        self.settings.interactive && !( self.settings.no_input_option && self.matches.get_value_ref( "no-input" ) == "true" ) && tty::is_stdin_terminal()
+    }
+
+    #[cfg(feature = "std")]
+    // ask e.g. "really overwrite output.pcm? [y/N]: " on the terminal. true for --yes, false when not is_interactive()
+    fn confirm( &self, message : &str ) -> bool {
+        if self.settings.yes_option && self.matches.get_value_ref( "yes" ) == "true" {
+            return true;
+        }
+        if !self.is_interactive() {
+            return false;
+        }
+        self.confirm_with( message, &mut std::io::stdin().lock(), &mut std::io::stderr() ).unwrap_or( false )
+    }
+
+    #[cfg(feature = "std")]
+    // same as confirm() but always asks to the given input and output unless --yes
+    fn confirm_with<R : std::io::BufRead, W : std::io::Write>( &self, message : &str, input : &mut R, output : &mut W ) -> std::io::Result<bool> {
+        if self.settings.yes_option && self.matches.get_value_ref( "yes" ) == "true" {
+            return Ok( true );
+        }
+        write!( output, "{} [y/N]: ", message )?;
+        output.flush()?;
+        let mut answer = String::new();
+        input.read_line( &mut answer )?;
+        Ok( matches!( answer.trim().to_ascii_lowercase().as_str(), "y" | "yes" ) )
+    }
+
+    #[cfg(feature = "std")]
+    // ask all the options one by one on the terminal instead of the args, and return the equivalent command line
+    fn run_wizard( &mut self ) -> std::io::Result<String> {
+        self.prepare_parse();
+        self.run_wizard_inner( &mut std::io::stdin().lock(), &mut std::io::stderr(), &mut set_terminal_echo )
+    }
+
+    #[cfg(feature = "std")]
+    // same as run_wizard() with the given input and output
+    fn run_wizard_with<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W ) -> std::io::Result<String> {
+        self.prepare_parse();
+        self.run_wizard_inner( input, output, &mut |_| Ok( () ) )
+    }
+
+    fn add_command<C : Command + Send + Sync + 'static>( &mut self, command : C ) {
+        self.commands.commands.push( Arc::new( command ) );
+    }
+
+    // the order of Command::category() in the help e.g. set_command_categories( &[ "Conversion", "Analysis" ] ). the others follow
+    fn set_command_categories( &mut self, categories : &[&str] ) {
+        self.commands.categories = categories.iter().map( |category| category.to_string() ).collect();
+    }
+
+    // rewrite the args before the options are matched e.g. the own shorthands, the legacy flags or the injected defaults.
+    // the preprocessors run in the order of the addition, and the raw args are the rewritten ones
+    fn add_preprocessor( &mut self, preprocessor : impl Fn( Vec<String> ) -> Vec<String> + Send + Sync + 'static ) {
+        self.preprocessors.push( Preprocessor( Arc::new( preprocessor ) ) );
+    }
+
+    // inspect or adjust the values after the successful parse e.g. derive --frames by Matches::set_derived_value().
+    // the middlewares run in the order of the addition before the providers' after_parse(), and the first error fails the parse, see get_error()
+    fn add_middleware( &mut self, middleware : impl Fn( &mut Matches ) -> Result<(), OptParseError> + Send + Sync + 'static ) {
+        self.middlewares.push( Middleware( Arc::new( middleware ) ) );
+    }
+
+    fn get_error( &self ) -> Option<&OptParseError> {
+        self.matches.get_error()
+    }
+
+    fn get_violations( &self ) -> &[OptParseError] {
+        self.matches.get_violations()
+    }
+
+    // --check validates the args through the middlewares and the providers, prints "ok" or all the violations and exits instead of running the application
+    fn add_check_option( &mut self ) {
+        self.options.push( OptParseItem::new( "", "--check", false, "false", "Validate the args and exit" ) );
+        self.settings.check_option = true;
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // translate the messages of format_error(), see i18n
+    fn set_message_catalog<C : MessageCatalog + Send + Sync + 'static>( &mut self, catalog : C ) {
+        self.catalog = Some( CatalogRef( Arc::new( catalog ) ) );
+    }
+
+    // the error message by the catalog of set_message_catalog(), or the English as Display
+    fn format_error( &self, error : &OptParseError ) -> String {
+        match &self.catalog {
+            Some( catalog ) => error.localize( catalog.0.as_ref() ),
+            None => error.localize( &i18n::DefaultCatalog ),
+        }
+    }
+
+    // more options after new() e.g. the ones decided by parse_early()
+    fn add_options( &mut self, options : Vec<OptParseItem> ) {
+        self.options.extend( options );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the group's options are parsed with the own ones and shown under the group's section in the help
+    fn add_group( &mut self, group : OptionGroup ) {
+        let title = group.title;
+        self.options.extend( group.options.into_iter().map( |option| option.with_group( &title ) ) );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // embed the library's options under the prefix e.g. "cache" makes --dir the --cache-dir keyed by "cache.dir".
+    // the short options are dropped as they would collide, and the help shows them under the prefix's section
+    fn add_namespace( &mut self, prefix : &str, options : Vec<OptParseItem> ) {
+        for mut option in options {
+            let long = option.full_option.as_deref().or( option.option.as_deref() ).unwrap_or( "" ).trim_start_matches( '-' ).to_string();
+            option.name = format!( "{}.{}", prefix, option.name );
+            option.option = None;
+            option.full_option = Some( format!( "--{}-{}", prefix, long ) );
+            option.conflicts = option.conflicts.iter().map( |conflict| format!( "{}.{}", prefix, conflict ) ).collect();
+            option.group = prefix.to_string();
+            self.options.push( option );
+        }
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+
+    // the provider's options are parsed and shown in the help with the own ones, then its after_parse() runs
+    fn add_provider<P : OptionProvider + Send + Sync + 'static>( &mut self, provider : P ) {
+        self.options.extend( provider.options() );
+        self.index = FlagIndex::new( &self.options, &self.settings );
+        self.providers.providers.push( Arc::new( provider ) );
+    }
+
+    // parse the global options until the command name, then parse the rest with the command's options and run it.
+    // the args given to new() begin with the program name as env::args()
+    fn run_command( &mut self, is_finish_if_help : bool ) -> Result<i32, OptParseError> {
+        let posixly_correct = self.settings.posixly_correct;
+        self.set_posixly_correct( true );
+        let args : Vec<String> = self.args.iter().skip( 1 ).cloned().collect();
+        let result = self.parse_options_from_iter( args, is_finish_if_help );
+        self.set_posixly_correct( posixly_correct );
+        if !result {
+            return Err( OptParseError::InvalidArgs );
+        }
+
+        let mut args = self.matches.arg_values.clone().into_iter();
+        let name = args.next().ok_or( OptParseError::MissingCommand )?;
+        self.run_named_command( &name, args.collect(), is_finish_if_help )
+    }
+
+    // busybox style. the program name e.g. "/usr/bin/pcminfo" selects the command "pcminfo" taking all the args as its own.
+    // otherwise the first arg is the command name as run_command()
+    fn run_multi_call( &mut self, program : &str, is_finish_if_help : bool ) -> Result<i32, OptParseError> {
+        let name = program.rsplit( ['/', '\\'] ).next().unwrap_or( program );
+        let name = name.strip_suffix( ".exe" ).unwrap_or( name );
+        if self.commands.find( name ).is_some() {
+            let args = self.args.iter().skip( 1 ).cloned().collect();
+            return self.run_named_command( name, args, is_finish_if_help );
+        }
+        self.run_command( is_finish_if_help )
+    }
+
+    fn get_value( &self, option : &str ) -> String {
+        self.matches.get_value( option )
+    }
+
+    fn get_value_ref( &self, option : &str ) -> &str {
+        self.matches.get_value_ref( option )
+    }
+
+    fn get_value_or( &self, option : &str, fallback : &str ) -> String {
+        self.matches.get_value_or( option, fallback )
+    }
+
+    fn get_value_as<T>( &self, option : &str ) -> Result<T, OptParseError> where T : core::str::FromStr, T::Err : core::fmt::Display {
+        self.matches.get_value_as( option )
+    }
+
+    fn extract<T : FromOptParse>( &self ) -> Result<T, OptParseError> {
+        T::from_matches( &self.matches )
+    }
+
+    #[cfg(feature = "std")]
+    fn get_value_os( &self, option : &str ) -> &OsStr {
+        self.matches.get_value_os( option )
+    }
+
+    #[cfg(feature = "std")]
+    fn get_value_path( &self, option : &str ) -> &Path {
+        self.matches.get_value_path( option )
+    }
+
+    #[cfg(feature = "std")]
+    // the paths matching the pattern value by the option's no match policy. the value not being ValueType::Glob is the literal path
+    fn get_paths( &self, option : &str ) -> Vec<PathBuf> {
+        let no_match = match self.get_option_item( option ).map( |item| item.get_value_type() ) {
+            Some( ValueType::Glob( no_match ) ) => *no_match,
+            _ => glob::NoMatch::Literal,
+        };
+        glob::expand( self.matches.get_value_ref( option ), no_match ).unwrap_or_default()
+    }
+
+    // the bitmask of the option's flags declared by OptParseItem::with_flags(). 0 if it isn't such option
+    fn get_flags( &self, option : &str ) -> u64 {
+        self.get_option_item( option ).and_then( |item| item.to_flags( self.matches.get_value_ref( option ) ) ).unwrap_or( 0 )
+    }
+
+    fn get_values( &self, option : &str ) -> Vec<String> {
+        self.matches.get_values( option )
+    }
+
+    fn get_typed( &self, option : &str ) -> Option<&TypedValue> {
+        self.matches.get_typed( option )
+    }
+
+    fn get_int( &self, option : &str ) -> Option<i64> {
+        self.matches.get_int( option )
+    }
+
+    fn get_float( &self, option : &str ) -> Option<f64> {
+        self.matches.get_float( option )
+    }
+
+    fn get_bool( &self, option : &str ) -> Option<bool> {
+        self.matches.get_bool( option )
+    }
+
+    fn get_parsed<T : Any>( &self, option : &str ) -> Option<&T> {
+        self.matches.get_parsed( option )
+    }
+
+    fn occurrences_of( &self, option : &str ) -> usize {
+        self.matches.occurrences_of( option )
+    }
+
+    // the log level per the occurrences of the verbose and the quiet options from the error level
+    fn get_level_filter( &self, verbose : &str, quiet : &str ) -> verbosity::LevelFilter {
+        verbosity::LevelFilter::from_counts( verbosity::LevelFilter::Error, self.occurrences_of( verbose ), self.occurrences_of( quiet ) )
+    }
+
+    fn get_source( &self, option : &str ) -> Option<&ValueSource> {
+        self.matches.get_source( option )
+    }
+
+    fn was_set( &self, option : &str ) -> bool {
+        self.matches.was_set( option )
+    }
+
+    fn iter_values<'a>( &'a self ) -> impl Iterator<Item = ( &'a str, &'a str, &'a ValueSource )> + 'a {
+        self.matches.iter_values()
+    }
+
+    fn diff( &self, other : &Matches ) -> Vec<ValueDiff> {
+        self.matches.diff( other )
+    }
+
+    #[cfg(feature = "std")]
+    fn into_map( self ) -> HashMap<String, String> {
+        self.matches.into_map()
+    }
+
+    fn to_btreemap( &self ) -> BTreeMap<String, String> {
+        self.matches.to_btreemap()
+    }
+
+    fn to_json( &self ) -> String {
+        self.matches.to_json()
+    }
+
+    // see env::to_exports()
+    fn to_env_exports( &self, prefix : &str ) -> String {
+        env::to_exports( &self.matches, prefix )
+    }
+
+    // see env::to_dotenv()
+    fn to_dotenv( &self, prefix : &str, is_redacted : bool ) -> String {
+        env::to_dotenv( &self.matches, prefix, is_redacted )
+    }
+
+    #[cfg(feature = "std")]
+    fn write_dotenv<P : AsRef<Path>>( &self, path : P, prefix : &str, is_redacted : bool ) -> std::io::Result<()> {
+        std::fs::write( path, self.to_dotenv( prefix, is_redacted ) )
+    }
+
+    // the args giving the same results e.g. [ "--samplingRate=44100", "--verbose", "in.wav" ]. only the options specified in the args are included with their occurrences, and the args after "--" follow "--"
+    fn to_args( &self ) -> Vec<String> {
+        self.to_args_with( SecretArgs::Plain )
+    }
+
+    // to_args() quoted for the shell e.g. "--samplingRate=44100 'my file.pcm'". the secret values are masked
+    fn to_command_line( &self ) -> String {
+        shell::join( &self.to_args_with( SecretArgs::Masked ) )
+    }
+
+    fn get_matches( &self ) -> &Matches {
+        &self.matches
+    }
+
+    fn into_matches( self ) -> Matches {
+        self.matches
+    }
+
+    fn get_args_count(&self) -> usize {
+        self.matches.get_args_count()
+    }
+
+    fn get_ignored_args( &self ) -> &[String] {
+        self.matches.get_ignored_args()
+    }
+
+    // true: keep the copy of the args for get_raw_args(). off by default, so that take_args() frees the args
+    fn set_keep_raw_args( &mut self, enable : bool ) {
+        self.settings.keep_raw_args = enable;
+    }
+
+    fn get_raw_args( &self ) -> &[String] {
+        self.matches.get_raw_args()
+    }
+
+    fn get_passthrough_args( &self ) -> &[String] {
+        self.matches.get_passthrough_args()
+    }
+
+    #[cfg(feature = "std")]
+    fn get_passthrough_args_os( &self ) -> &[OsString] {
+        self.matches.get_passthrough_args_os()
+    }
+
+    fn get_ordered_matches( &self ) -> &[MatchEvent] {
+        self.matches.get_ordered_matches()
+    }
+
+    #[cfg(feature = "std")]
+    // run get_passthrough_args_os() and return its exit code e.g. std::process::exit( opt_parse.run_passthrough()? )
+    fn run_passthrough( &self ) -> std::io::Result<i32> {
+        exec::run( self.get_passthrough_args_os() )
+    }
+
+    fn get_option_position( &self, option : &str ) -> Option<usize> {
+        self.matches.get_option_position( option )
+    }
+
+    fn get_arg_position( &self, index : usize ) -> Option<usize> {
+        self.matches.get_arg_position( index )
+    }
+
+    fn get_args(&self, index : usize ) -> String {
+        self.matches.get_args( index )
+    }
+
+    #[cfg(feature = "std")]
+    fn get_arg_os( &self, index : usize ) -> &OsStr {
+        self.matches.get_arg_os( index )
+    }
+
+    #[cfg(feature = "std")]
+    fn get_arg_path( &self, index : usize ) -> &Path {
+        self.matches.get_arg_path( index )
+    }
+}
+
+
+impl OptParse
+{
+    // the help colored by color::paint_help() when the color feature is on and stderr or stdout is colored
+    #[cfg(feature = "std")]
+    fn styled_help( &self, help : String, is_stderr : bool ) -> String {
+        #[cfg(feature = "color")]
+        if self.should_color( is_stderr ) {
+            return color::paint_help( &help );
+        }
+        let _ = is_stderr;
+        help
+    }
+
+    // format_error() colored by color::paint_error() for stderr
+    #[cfg(feature = "std")]
+    fn styled_error( &self, error : &OptParseError ) -> String {
+        let message = self.format_error( error );
+        #[cfg(feature = "color")]
+        if self.should_color( true ) {
+            return color::paint_error( &message );
+        }
+        message
+    }
+
+    // by --color of add_color_option(), auto without it
+    #[cfg(feature = "color")]
+    fn should_color( &self, is_stderr : bool ) -> bool {
+        let choice = self.get_color_choice();
+        if is_stderr { choice.should_color_stderr() } else { choice.should_color_stdout() }
+    }
+
+    // reset the parsed state to the defaults
+    fn prepare_parse( &mut self ) {
+        if self.settings.auto_short_option {
+            self.derive_short_options();
+        }
+
+        self.matches = Matches::new( &self.settings );
+        for option in &self.options {
+            self.matches.register_option( option );
+        }
+    }
+
+    // -h or --help and call print_help(). without std or auto help, the caller checks is_help_requested() instead
+    fn finish_parse( &mut self, ( result, is_help ) : ( bool, bool ), _is_finish_if_help : bool ) -> bool {
+        #[cfg(feature = "std")]
+        if is_help {
+            if self.settings.auto_help {
+                let visibility = if self.matches.is_help_all_requested() { Visibility::Advanced } else { Visibility::Standard };
+                let help = match self.matches.get_help_topic().and_then( |topic| self.get_option_help( topic ) ) {
+                    Some( help ) => help,
+                    None => self.help_text( self.matches.is_long_help_requested(), visibility, self.get_terminal_width() ),
+                };
+                let _ = std::io::Write::write_all( &mut std::io::stdout(), self.styled_help( help, false ).as_bytes() );
+            }
+            if _is_finish_if_help {
+                exit_process( 0 );
+            }
+        }
+        if is_help {
+            return result;
+        }
+        #[cfg(feature = "std")]
+        for warning in self.get_warnings() {
+            eprintln!( "{}", warning );
+        }
+        if self.is_version_requested() {
+            #[cfg(feature = "std")]
+            {
+                println!( "{}", self.get_version().unwrap_or( "" ) );
+                if _is_finish_if_help {
+                    exit_process( 0 );
+                }
+            }
+            return result;
+        }
+        if self.settings.print_defaults_option && self.matches.get_value_ref( "print-defaults" ) == "true" {
+            #[cfg(feature = "std")]
+            {
+                print!( "{}", self.get_defaults() );
+                if _is_finish_if_help {
+                    exit_process( 0 );
+                }
+            }
+            return result;
+        }
+        #[cfg(feature = "completions")]
+        if let Some( shell ) = completions::Shell::from_value( self.matches.get_value_ref( "install-completions" ) ).filter( |_| self.get_option_item( "install-completions" ).is_some() ) {
+            let program = completions::program_name();
+            let is_installed = match completions::install( self, &program, shell ) {
+                Ok( path ) => { print!( "{}", completions::instructions( shell, &path ) ); true },
+                Err( error ) => { eprintln!( "failed to install the completion script: {}", error ); false },
+            };
+            if _is_finish_if_help {
+                exit_process( if is_installed { 0 } else { 1 } );
+            }
+            return result && is_installed;
+        }
+
+        let is_check = self.settings.check_option && self.matches.get_value_ref( "check" ) == "true";
+        if let Err( error ) = self.apply_env().and_then( |_| apply_values( &mut self.matches, &self.options, &self.index, &self.config_values, ValueSource::Config ) ).and_then( |_| self.apply_profile() ) {
+            self.matches.violations.push( error.clone() );
+            self.matches.error = Some( error );
+            if !is_check {
+                return false;
+            }
+        }
+        #[cfg(feature = "std")]
+        if self.settings.prompt_missing && self.is_interactive() {
+            let _ = self.prompt_missing_with( &mut std::io::stdin().lock(), &mut std::io::stderr(), &mut set_terminal_echo );
+        }
+        if let Err( error ) = self.resolve_default_from() {
+            self.matches.violations.push( error.clone() );
+            self.matches.error = Some( error );
+            if !is_check {
+                return false;
+            }
+        }
+        #[cfg(feature = "std")]
+        if self.is_dump_args_requested() {
+            eprint!( "{}", self.get_dump_args() );
+            if self.settings.exit_after_dump_args && _is_finish_if_help {
+                exit_process( 0 );
+            }
+        }
+        let mut violations = Vec::new();
+        for option in &self.options {
+            if option.is_required && !self.matches.was_set( &option.name ) {
+                violations.push( OptParseError::MissingOption { name : option.name.clone() } );
+            }
+            if self.matches.occurrences_of( &option.name ) == 0 {
+                continue;
+            }
+            for conflict in option.conflicts.iter().filter( |conflict| self.matches.occurrences_of( conflict ) > 0 ) {
+                push_conflict( &mut violations, &option.name, conflict );
+            }
+            let values = self.matches.get_values( &option.name );
+            let is_missing = self.matches.violations.iter().any( |violation| matches!( violation, OptParseError::MissingValue { name } if *name == option.name ) );
+            if option.is_greedy() && !is_missing && ( values.len() < option.value_count.0 || values.len() > option.value_count.1 ) {
+                let ( min, max ) = option.value_count;
+                let message = match max == usize::MAX {
+                    true => ValueMessage::new( MessageKey::ValueCountAtLeast, &[ ( "min", min.to_string() ) ] ),
+                    false => ValueMessage::new( MessageKey::ValueCount, &[ ( "min", min.to_string() ), ( "max", max.to_string() ) ] ),
+                };
+                violations.push( OptParseError::InvalidValue { name : option.name.clone(), value : values.join( " " ), message } );
+            }
+        }
+        for ( names, is_exclusive ) in &self.settings.required_groups {
+            let specified : Vec<&String> = names.iter().filter( |name| self.matches.was_set( name ) ).collect();
+            match specified.as_slice() {
+                [] => violations.push( OptParseError::MissingOneOf { names : names.clone() } ),
+                [ name, other, .. ] if *is_exclusive => push_conflict( &mut violations, name, other ),
+                _ => {},
+            }
+        }
+        for ( i, arg ) in self.matches.arg_values.iter().enumerate() {
+            if let Some( ( name, _, extensions ) ) = self.positionals.get( i ).or( self.positionals.last() ) {
+                if let Err( message ) = check_extension( arg, extensions ) {
+                    violations.push( OptParseError::InvalidValue { name : name.clone(), value : arg.clone(), message } );
+                }
+            }
+        }
+        if !result && violations.is_empty() && self.matches.violations.is_empty() {
+            violations.push( OptParseError::InvalidArgs );
+        }
+        self.matches.violations.extend( violations );
+
+        if !self.matches.violations.is_empty() && !is_check {
+            return false;
+        }
+        // the check reports all the errors while the parse stops at the first
+        let limit = if is_check { usize::MAX } else { 1 };
+        let mut errors : Vec<OptParseError> = self.middlewares.iter().filter_map( |middleware| ( middleware.0 )( &mut self.matches ).err() ).take( limit ).collect();
+        if errors.is_empty() || is_check {
+            errors.extend( self.providers.providers.iter().filter_map( |provider| provider.after_parse( &self.matches ).err() ).take( limit ) );
+        }
+        self.matches.error = self.matches.error.take().or( errors.first().cloned() );
+        self.matches.violations.extend( errors );
+        let is_success = self.matches.violations.is_empty();
+        #[cfg(feature = "std")]
+        if is_check {
+            if is_success {
+                println!( "ok" );
+            }
+            for violation in &self.matches.violations {
+                eprintln!( "{}", self.styled_error( violation ) );
+            }
+            if _is_finish_if_help {
+                exit_process( if is_success { 0 } else { 2 } );
+            }
+        }
+        is_success
+    }
+
+    #[cfg(feature = "std")]
+    // set_echo( false ) before reading the password and set_echo( true ) after. the error of set_echo( false ) stops the prompt
+    fn prompt_missing_with<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W, set_echo : &mut dyn FnMut( bool ) -> std::io::Result<()> ) -> std::io::Result<()> {
+        for option in &self.options {
+            if !( option.is_required || option.is_password ) || !option.arg_required || self.matches.was_set( &option.name ) {
+                continue;
+            }
+            let answer = match ask_option( option, input, output, set_echo )? {
+                Some( answer ) => answer,
+                None => break
+            };
+            let answer = if answer.is_empty() { option.value.as_str() } else { answer.as_str() };
+            if !answer.is_empty() {
+                self.matches.set_value( &option.name, answer, option.convert( answer ).ok().flatten(), ValueSource::Prompt );
+            }
+        }
+        Ok( () )
+    }
+
+    #[cfg(feature = "std")]
+    fn run_wizard_inner<R : std::io::BufRead, W : std::io::Write>( &mut self, input : &mut R, output : &mut W, set_echo : &mut dyn FnMut( bool ) -> std::io::Result<()> ) -> std::io::Result<String> {
+        for option in &self.options {
+            if option.arg_required {
+                match ask_option( option, input, output, set_echo )? {
+                    Some( answer ) if !answer.is_empty() => self.matches.set_value( &option.name, &answer, option.convert( &answer ).ok().flatten(), ValueSource::Prompt ),
+                    Some( _ ) => {},
+                    None => break
+                }
+            } else {
+                write!( output, "{} [y/N]: ", option.description )?;
+                output.flush()?;
+                let mut answer = String::new();
+                if input.read_line( &mut answer )? == 0 {
+                    break;
+                }
+                if matches!( answer.trim().to_ascii_lowercase().as_str(), "y" | "yes" ) {
+                    self.matches.set_value( &option.name, "true", None, ValueSource::Prompt );
+                }
+            }
+        }
+        Ok( self.to_command_line() )
+    }
+
+    // to_args() with the secret values as is, masked or excluded
+    pub(crate) fn to_args_with( &self, secrets : SecretArgs ) -> Vec<String> {
+        let mut result = Vec::new();
+        let mut is_greedy = false;  // true: the last option takes the following args unless the terminator
+        for option in self.options.iter().filter( |option| !option.is_env_only ) {
+            let entry = match self.matches.find( &option.name ) {
+                Some( entry ) if entry.source != ValueSource::Default && !( entry.is_secret && secrets == SecretArgs::Excluded ) => entry,
+                _ => continue
+            };
+            let flag = option.full_option.as_deref().unwrap_or( option.get_option() );
+            if !option.arg_required {
+                // the explicit false overrides the config or the environment. the short only flag can be turned off only by +x
+                match entry.value.as_str() {
+                    "true" => result.extend( core::iter::repeat_n( flag.to_string(), entry.occurrences.max( 1 ) ) ),
+                    "false" if option.full_option.is_some() => result.push( format!( "{}=false", flag ) ),
+                    "false" if self.settings.plus_toggle_option => result.push( format!( "+{}", &flag[ 1.. ] ) ),
+                    _ => continue,
+                }
+                is_greedy = false;
+                continue;
+            }
+            // each value of the greedy option is one occurrence as they are accumulated
+            let values = if option.is_accumulated() && !entry.values.is_empty() { entry.values.as_slice() } else { core::slice::from_ref( &entry.value ) };
+            for value in values {
+                let value = if secrets == SecretArgs::Masked && entry.is_secret { REDACTED } else { value.as_str() };
+                if option.full_option.is_none() {
+                    result.push( flag.to_string() );
+                    result.push( value.to_string() );
+                } else {
+                    result.push( format!( "{}={}", flag, value ) );
+                }
+            }
+            is_greedy = option.is_greedy();
+        }
+        if is_greedy && !self.matches.arg_values.is_empty() && !self.settings.value_terminator.is_empty() {
+            result.push( self.settings.value_terminator.clone() );
+        }
+        result.extend( self.matches.arg_values.iter().cloned() );
+        if !self.matches.passthrough_args.is_empty() {
+            result.push( "--".to_string() );
+            result.extend( self.matches.passthrough_args.iter().cloned() );
+        }
+        result
+    }
+
+    fn run_named_command( &self, name : &str, args : Vec<String>, is_finish_if_help : bool ) -> Result<i32, OptParseError> {
+        let command = self.commands.find( name ).ok_or_else( || OptParseError::UnknownCommand { name : name.to_string(), suggestion : self.commands.suggest( name ), available : self.commands.names() } )?;
+
+        let mut opt_parse = OptParse::new( args, command.options(), command.description() );
+        opt_parse.settings = self.settings.clone();
+        opt_parse.index = FlagIndex::new( &opt_parse.options, &opt_parse.settings );
+        if let Some( version ) = self.get_command_version( name ) {
+            opt_parse.set_version( version );
+        }
+        if !opt_parse.parse_options( is_finish_if_help ) {
+            return Err( OptParseError::InvalidArgs );
+        }
+        if opt_parse.is_version_requested() {
+            return Ok( 0 );
+        }
+        command.run( opt_parse.get_matches() )
+    }
+
+    fn is_version_requested( &self ) -> bool {
+        self.settings.version.is_some() && self.matches.get_value_ref( "version" ) == "true" && self.get_option_item( "version" ).is_some()
+    }
+
+    // the options added by add_check_option(), set_version() and so on rather than the application's, skipped by get_defaults() and get_dump_args()
+    fn is_builtin_option( &self, name : &str ) -> bool {
+        match name {
+            "version" => self.settings.version.is_some(),
+            "check" => self.settings.check_option,
+            "print-defaults" => self.settings.print_defaults_option,
+            "dump-args" => self.settings.dump_args_option,
+            "yes" => self.settings.yes_option,
+            "no-input" => self.settings.no_input_option,
+            "profile" => self.settings.profile_option,
+            _ => false
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn is_dump_args_requested( &self ) -> bool {
+        self.settings.dump_args_option && self.matches.get_value_ref( "dump-args" ) == "true"
+    }
+
+    // the values of the profile selected by --profile for the options not specified
+    fn apply_profile( &mut self ) -> Result<(), OptParseError> {
+        let name = self.matches.get_value( "profile" );
+        if name.is_empty() || !self.settings.profile_option {
+            return Ok( () );
+        }
+        let ( _, values ) = self.profiles.iter().find( |( profile, _ )| *profile == name ).ok_or_else( || {
+            let names : Vec<&str> = self.profiles.iter().map( |( profile, _ )| profile.as_str() ).collect();
+            OptParseError::InvalidValue { name : "profile".to_string(), value : name.clone(), message : ValueMessage::new( MessageKey::NotOneOf, &[ ( "choices", names.join( ", " ) ) ] ) }
+        })?;
+        let values : Vec<( String, String, String )> = values.iter().map( |( option, value )| ( option.clone(), value.clone(), format!( "profile {}", name ) ) ).collect();
+        apply_values( &mut self.matches, &self.options, &self.index, &values, ValueSource::Profile )
+    }
+
+    // the environment variables of the env only entries not set yet
+    fn apply_env( &mut self ) -> Result<(), OptParseError> {
+        #[cfg(feature = "std")]
+        {
+            let values : Vec<( String, String, String )> = self.options.iter().filter( |option| option.is_env_only ).filter_map( |option| {
+                let var = env::var_name( &self.settings.env_prefix, &option.name );
+                Some( ( option.name.clone(), std::env::var( &var ).ok()?, format!( "env {}", var ) ) )
+            }).collect();
+            apply_values( &mut self.matches, &self.options, &self.index, &values, ValueSource::Environment )?;
+        }
+        Ok( () )
+    }
+
+    // the values of the options not specified but defaulting to the others by with_default_from()
+    fn resolve_default_from( &mut self ) -> Result<(), OptParseError> {
+        for option in self.options.iter().filter( |option| option.default_from.is_some() ) {
+            if self.matches.was_set( &option.name ) {
+                continue;
+            }
+            let mut names = vec![ option.name.clone() ];
+            let mut from = option.default_from.as_deref();
+            let value = loop {
+                let source = from.and_then( |from| self.index.find( from ) ).map( |index| &self.options[ index ] );
+                let source = source.ok_or_else( || OptParseError::UnknownOption { name : from.unwrap_or( "" ).to_string() } )?;
+                if names.contains( &source.name ) {
+                    names.push( source.name.clone() );
+                    return Err( OptParseError::DefaultCycle { names } );
+                }
+                names.push( source.name.clone() );
+                if source.default_from.is_none() || self.matches.was_set( &source.name ) {
+                    break self.matches.get_value( &source.name );
+                }
+                from = source.default_from.as_deref();
+            };
+            if let Some( &index ) = self.matches.keys.get( &option.name ) {
+                let entry = &mut self.matches.entries[ index ];
+                entry.typed = option.convert( &value ).ok().flatten();
+                entry.values = vec![ value.clone() ];
+                entry.value = value;
+            }
+        }
+        Ok( () )
+    }
+
+    fn preprocess( &self, args : Vec<String> ) -> Vec<String> {
+        self.preprocessors.iter().fold( args, |args, preprocessor| ( preprocessor.0 )( args ) )
+    }
+
+    // the help. is_long: with the long descriptions, width: the wrap width of the help layout
+    // the options up to the visibility are shown
+    fn help_text( &self, is_long : bool, visibility : Visibility, width : usize ) -> String {
+        let mut result = String::new();
+        let layout = self.settings.help_layout.as_ref().map( |layout| HelpLayout { width, ..layout.clone() } );
+        let layout = layout.as_ref();
+        let options_len = &self.options.len();
+        let mut max_short_option_len : usize = 0;
+        let mut max_full_option_len : usize = 0;
+        for i in 0..*options_len {
+            if self.options[i].visibility > visibility {
+                continue;
+            }
+            max_short_option_len = cmp::max( max_short_option_len, self.options[i].get_option().len() );
+            max_full_option_len  = cmp::max( max_full_option_len,  self.options[i].get_full_option().len() );
+        }
+        let is_shown = |option : &OptParseItem| !option.is_env_only && option.visibility <= visibility;
+        let max_flags_len = self.options.iter().filter( |option| option.visibility <= visibility ).map( |option| self.help_flags( option ).chars().count() ).max().unwrap_or( 0 );
+        if !&self.description.is_empty() {
+            let _ = writeln!( result, "{}", &self.description );
+        }
+        let write_option = |result : &mut String, option : &OptParseItem| {
+            if !is_shown( option ) {
+                return;
+            }
+            let mut description = option.description.clone();
+            if !option.choices.is_empty() {
+                let _ = write!( description, " [possible values: {}]", option.choices.join( ", " ) );
+            }
+            if let ValueType::Unit( units ) = &option.value_type {
+                let _ = write!( description, " [{}]", units.describe() );
+            }
+            if is_long && !option.long_description.is_empty() {
+                let _ = write!( description, "\n{}", option.long_description );
+            }
+            match layout {
+                Some( layout ) => write_help_row( result, layout, &self.help_flags( option ), max_flags_len, &description ),
+                None => {
+                    let full_option = option.get_full_option();
+                    let full_option = if self.settings.single_dash_long_option { full_option.strip_prefix( '-' ).unwrap_or( full_option ) } else { full_option };
+                    // the same columns as the first line so that the continuation lines are at the same tab stop
+                    let continuation = format!( "\n {:short_len$}\t {:full_len$}\t   ", "", "", short_len = max_short_option_len, full_len = max_full_option_len );
+                    let _ = writeln!( result, " {:short_len$}\t {:full_len$}\t : {}", option.get_option(), full_option, description.replace( '\n', &continuation ), short_len = max_short_option_len, full_len = max_full_option_len );
+                }
+            }
+        };
+        // the name and the description like the commands and the positionals
+        let write_entry = |result : &mut String, name : &str, max_name_len : usize, description : &str| {
+            match layout {
+                Some( layout ) => write_help_row( result, layout, name, max_name_len, description ),
+                None => { let _ = writeln!( result, " {:name_len$}\t : {}", name, description, name_len = max_name_len ); }
+            }
+        };
+
+        if !self.commands.is_empty() {
+            let max_name_len = self.commands.commands.iter().map( |command| command.name().len() ).max().unwrap_or( 0 );
+            for ( category, commands ) in self.commands.categorized() {
+                let _ = writeln!( result, "{}:", if category.is_empty() { "Commands" } else { category } );
+                for command in commands {
+                    write_entry( &mut result, command.name(), max_name_len, command.description() );
+                }
+            }
+        }
+        if !self.positionals.is_empty() {
+            let max_name_len = self.positionals.iter().map( |( name, _, _ )| name.chars().count() ).max().unwrap_or( 0 );
+            let _ = writeln!( result, "Arguments:" );
+            for ( name, description, _ ) in &self.positionals {
+                write_entry( &mut result, name, max_name_len, description );
+            }
+        }
+
+        // the ungrouped options, then the groups in the order of their first options
+        let ungrouped = || self.options.iter().filter( |option| option.group.is_empty() && is_shown( option ) );
+        if self.settings.help_sections {
+            for ( title, is_arg_required ) in [ ( "Flags", false ), ( "Options", true ) ] {
+                if ungrouped().any( |option| option.arg_required == is_arg_required ) {
+                    let _ = writeln!( result, "{}:", title );
+                }
+                for option in ungrouped().filter( |option| option.arg_required == is_arg_required ) {
+                    write_option( &mut result, option );
+                }
+            }
+        } else {
+            if !self.commands.is_empty() || !self.positionals.is_empty() {
+                let _ = writeln!( result, "Options:" );
+            }
+            for option in ungrouped() {
+                write_option( &mut result, option );
+            }
+        }
+        let mut groups : Vec<&str> = Vec::new();
+        for option in &self.options {
+            if !option.group.is_empty() && is_shown( option ) && !groups.contains( &option.group.as_str() ) {
+                groups.push( &option.group );
+            }
+        }
+        for group in groups {
+            let _ = writeln!( result, "{}:", group );
+            for option in self.options.iter().filter( |option| option.group == group ) {
+                write_option( &mut result, option );
+            }
+        }
+        let environment : Vec<( String, &str )> = self.options.iter().filter( |option| option.visibility <= visibility ).filter_map( |option| Some( ( self.get_env_var( &option.name )?, option.description.as_str() ) ) ).collect();
+        if !environment.is_empty() {
+            let max_name_len = environment.iter().map( |( name, _ )| name.len() ).max().unwrap_or( 0 );
+            let _ = writeln!( result, "Environment:" );
+            for ( name, description ) in &environment {
+                write_entry( &mut result, name, max_name_len, description );
+            }
+        }
+        if visibility < Visibility::Advanced && self.options.iter().any( |option| option.visibility == Visibility::Advanced ) {
+            let _ = writeln!( result, "Use --help-all to show the advanced options" );
+        }
+        result
+    }
+
+    // "-s, --samplingRate" for the help layout. the long only options are aligned to the long ones if any has the short
+    fn help_flags( &self, option : &OptParseItem ) -> String {
+        let full_option = option.get_full_option();
+        let full_option = if self.settings.single_dash_long_option { full_option.strip_prefix( '-' ).unwrap_or( full_option ) } else { full_option };
+        match ( option.get_option(), full_option ) {
+            ( "", full_option ) if self.options.iter().any( |option| !option.get_option().is_empty() ) => format!( "    {}", full_option ),
+            ( "", full_option ) => full_option.to_string(),
+            ( short, "" ) => short.to_string(),
+            ( short, full_option ) => format!( "{}, {}", short, full_option ),
+        }
+    }
+
+    // give "--samplingRate" the short "-s", or the next free letter of the name. -h is reserved for the help
+    fn derive_short_options( &mut self ) {
+        for ( index, short ) in auto_short_options( &self.options ) {
+            self.options[ index ].option = Some( short );
+        }
+        self.index = FlagIndex::new( &self.options, &self.settings );
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::vec_init_then_push, clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opt_parse() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-r", "--samplingRate", true, "48000", "Set Sampling Rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-r".to_string() );
+        argv.push( "44100".to_string());
+        argv.push( "--encoding=PCM32".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-r"), "44100" );
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("-c"), "2" );
+    }
+
+    #[test]
+    fn test_opt_parse_help() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-r", "--samplingRate", true, "48000", "Set Sampling Rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-h".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-h"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_exception_no_opt_parse_item() {
+        let options = Vec::new();
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-r".to_string() );
+        argv.push( "44100".to_string());
+        argv.push( "--encoding=PCM32".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-r"), "" );
+        assert_eq!( opt_parse.get_value("-e"), "" );
+        assert_eq!( opt_parse.get_value("-c"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_no_arg() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Enable quiet mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-v".to_string() );
+        argv.push( "-s".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_value("-q"), "false" );
+        assert_eq!( opt_parse.get_value("-s"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_arg_required_but_nothing() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "-v".to_string() ); // Expects the -s's value here but not specified
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, false );
+
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+        assert_eq!( opt_parse.get_value("-x"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "input.csv".to_string() );
+        argv.push( "-v".to_string() );
+        argv.push( "output1.csv".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+        argv.push( "output2.csv".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("-x"), "" );
+
+        assert_eq!( opt_parse.get_args_count(), 3 );
+        assert_eq!( opt_parse.get_args(0), "input.csv" );
+        assert_eq!( opt_parse.get_args(0), "input.csv" );
+        assert_eq!( opt_parse.get_args(1), "output1.csv" );
+        assert_eq!( opt_parse.get_args(2), "output2.csv" );
+        assert_eq!( opt_parse.get_args(3), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_full_option_only() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "input.csv".to_string() );
+        argv.push( "-v".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_value("-s"), "" );
+        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
+        assert_eq!( opt_parse.get_value("-x"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_full_option_alias() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "input.csv".to_string() );
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("--encoding"), "PCM32" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
+        assert_eq!( opt_parse.get_value("-x"), "" );
+    }
+
+
+    #[test]
+    fn test_opt_parse_with_args_fixed() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "mycommands".to_string() );
+        argv.push( "input.csv".to_string() );
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_with_required_args( false, 1, 1 ); // ensure argument e.g. input.csv
+        assert_eq!( opt_parse.get_args_count(), 2 ); // 0:mycommand, 1:input.csv
+        assert_eq!( is_success, true );
+    }
+
+    #[test]
+    fn test_opt_parse_with_args_max_specified() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "mycommands".to_string() );
+        argv.push( "input1.csv".to_string() );
+        argv.push( "input2.csv".to_string() );
+        argv.push( "input3.csv".to_string() );
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_with_required_args( false, 1, 2 ); // ensure argument e.g. input.csv
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_with_args_more_than_min() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "mycommands".to_string() );
+        argv.push( "input1.csv".to_string() );
+        argv.push( "input2.csv".to_string() );
+        argv.push( "input3.csv".to_string() );
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_with_required_args( false, 1, -1 ); // ensure argument e.g. input.csv
+        assert_eq!( is_success, true );
+    }
+
+    #[test]
+    fn test_opt_parse_get_value_or() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--samplingRate=44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value_or("-s", "96000"), "44100" );
+        assert_eq!( opt_parse.get_value_or("--samplingRate", "96000"), "44100" );
+        assert_eq!( opt_parse.get_value_or("-e", "PCM24"), "PCM24" );
+        assert_eq!( opt_parse.get_value_or("-x", "none"), "none" );
+    }
+
+    #[test]
+    fn test_opt_parse_short_option_matched_once() {
+        // the arg matched as the short option isn't matched as the full option again, nor the args as the empty full option
+        let mut options = Vec::new();
+        options.push( OptParseItem::short_only( "-n", true, "10", "Number of lines") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-n".to_string() );
+        argv.push( "20".to_string() );
+        argv.push( "input.txt".to_string() );
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-n"), "20" );
+        assert_eq!( opt_parse.get_value_or("-n", "5"), "20" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_matches().occurrences_of( "n" ), 1 );
+        assert_eq!( opt_parse.get_matches().occurrences_of( "samplingRate" ), 1 );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args(0), "input.txt" );
+    }
+
+    #[test]
+    fn test_opt_parse_canonical_name_lookup() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "--encoding=PCM32".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
+        assert_eq!( opt_parse.get_value("samplingRate"), "44100" );
+        assert_eq!( opt_parse.get_value("--encoding"), "PCM32" );
+        assert_eq!( opt_parse.get_value("encoding"), "PCM32" );
+        assert_eq!( opt_parse.get_value("verbose"), "false" );
+    }
+
+    #[test]
+    fn test_opt_parse_explicit_name() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100").with_name( "sampling_rate" ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("sampling_rate"), "44100" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("samplingRate"), "" );
+        assert_eq!( opt_parse.get_value("encoding"), "PCM16" );
+    }
+
+    #[test]
+    fn test_opt_parse_auto_short_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "", "--endian", true, "little", "Set endian little, big") );
+        options.push( OptParseItem::new( "", "--hex", false, "false", "Dump in hex") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "-n".to_string() );
+        argv.push( "big".to_string() );
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "-x".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        opt_parse.set_auto_short_option( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("--endian"), "big" );
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("--hex"), "true" );
+        assert_eq!( opt_parse.get_args_count(), 0 );
+    }
+
+    #[test]
+    fn test_opt_parse_normalize_full_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "", "--dry-run", false, "false", "Don't write anything") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--sampling-rate=44100".to_string() );
+        argv.push( "--dryRun".to_string() );
+
+        let mut opt_parse = OptParse::new( argv.clone(), options.clone(), "rst_opt_parse_test" );
+        opt_parse.set_normalize_full_option( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("--sampling-rate"), "44100" );
+        assert_eq!( opt_parse.get_value("--dryRun"), "true" );
+        assert_eq!( opt_parse.get_value("--dry-run"), "true" );
+
+        // the run of the capitals is one word
+        let mut opt_parse = OptParse::new( Vec::new(), vec![ OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding"), OptParseItem::new( "", "--httpServer", true, "", "Set server") ], "rst_opt_parse_test" );
+        opt_parse.set_normalize_full_option( true );
+        let is_success = opt_parse.parse_from_str( "--ENCODING=PCM8 --HTTPServer=localhost", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value("encoding"), "PCM8" );
+        assert_eq!( opt_parse.get_value("--http-server"), "localhost" );
+        assert_eq!( opt_parse.settings.normalize_flag( "--ENCODING" ), "--encoding" );
+        assert_eq!( opt_parse.settings.normalize_flag( "--outputWAV" ), "--output-wav" );
+
+        // the spellings are different options without the mode
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+        assert_eq!( opt_parse.get_value("--dry-run"), "false" );
+    }
+
+    #[test]
+    fn test_opt_parse_ignore_case_full_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--ENCODING=PCM32".to_string() );
+        argv.push( "--Verbose".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        opt_parse.set_ignore_case_full_option( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("--Encoding"), "PCM32" );
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+    }
+
+    #[test]
+    fn test_opt_parse_iter_values() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--encoding=PCM32".to_string() );
+        argv.push( "-v".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        let values : Vec<( &str, &str, &ValueSource )> = opt_parse.iter_values().collect();
+        assert_eq!( values, vec![
+            ( "samplingRate", "48000", &ValueSource::Default ),
+            ( "encoding", "PCM32", &ValueSource::CommandLine ),
+            ( "verbose", "true", &ValueSource::CommandLine ),
+        ] );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_export_map() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-e".to_string() );
+        argv.push( "PCM32".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        let sorted : Vec<( String, String )> = opt_parse.to_btreemap().into_iter().collect();
+        assert_eq!( sorted, vec![ ( "encoding".to_string(), "PCM32".to_string() ), ( "samplingRate".to_string(), "48000".to_string() ) ] );
+
+        let map = opt_parse.into_map();
+        assert_eq!( map.len(), 2 );
+        assert_eq!( map.get( "encoding" ), Some( &"PCM32".to_string() ) );
+        assert_eq!( map.get( "samplingRate" ), Some( &"48000".to_string() ) );
+    }
+
+    #[test]
+    fn test_opt_parse_get_options() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        assert_eq!( opt_parse.get_description(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.get_options().len(), 2 );
+
+        let item = opt_parse.get_option_item( "--samplingRate" ).unwrap();
+        assert_eq!( item.get_name(), "samplingRate" );
+        assert_eq!( item.get_option(), "-s" );
+        assert_eq!( item.get_full_option(), "--samplingRate" );
+        assert_eq!( item.is_arg_required(), true );
+        assert_eq!( item.get_default_value(), "48000" );
+        assert_eq!( item.get_description(), "Set sampling rate e.g. 44100" );
+
+        assert_eq!( opt_parse.get_option_item( "-v" ).unwrap().get_name(), "verbose" );
+        assert_eq!( opt_parse.get_option_item( "-x" ).is_none(), true );
+    }
+
+    #[test]
+    fn test_opt_parse_occurrences_of() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Enable quiet mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-v".to_string() );
+        argv.push( "--verbose".to_string() );
+        argv.push( "-v".to_string() );
+        argv.push( "-s".to_string() );
+        argv.push( "48000".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.occurrences_of("-v"), 3 );
+        assert_eq!( opt_parse.occurrences_of("samplingRate"), 1 );
+        assert_eq!( opt_parse.occurrences_of("--quiet"), 0 );
+        assert_eq!( opt_parse.occurrences_of("-x"), 0 );
+    }
+
+    #[test]
+    fn test_opt_parse_was_set() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "48000".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+        assert_eq!( opt_parse.get_value("-e"), "PCM16" );
+        assert_eq!( opt_parse.was_set("-s"), true );
+        assert_eq!( opt_parse.was_set("--encoding"), false );
+        assert_eq!( opt_parse.was_set("-x"), false );
+        assert_eq!( opt_parse.get_source("samplingRate"), Some( &ValueSource::CommandLine ) );
+        assert_eq!( opt_parse.get_source("encoding"), Some( &ValueSource::Default ) );
+        assert_eq!( opt_parse.get_source("-x"), None );
+    }
+
+    #[test]
+    fn test_opt_parse_full_option_separated_value() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--encoding".to_string() );
+        argv.push( "PCM32".to_string() );
+        argv.push( "input.csv".to_string() );
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "output.csv".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args(0), "input.csv" );
+        assert_eq!( opt_parse.get_args(1), "output.csv" );
+    }
+
+    #[test]
+    fn test_opt_parse_borrowed() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let argv = [ "input.pcm", "-s", "44100", "--encoding=PCM32", "output.pcm" ];
+
+        let opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let matches = opt_parse.parse_options_borrowed( &argv );
+        assert_eq!( matches.is_success(), true );
+
+        assert_eq!( matches.get_value("-s"), "44100" );
+        assert_eq!( matches.get_value("--encoding"), "PCM32" );
+        assert_eq!( core::ptr::eq( matches.get_value("encoding"), &argv[3][11..] ), true );
+        assert_eq!( matches.get_value("verbose"), "false" );
+        assert_eq!( matches.was_set("-s"), true );
+        assert_eq!( matches.was_set("-v"), false );
+        assert_eq!( matches.get_value("-x"), "" );
+        assert_eq!( matches.get_args_count(), 2 );
+        assert_eq!( matches.get_args(0), "input.pcm" );
+        assert_eq!( matches.get_args(1), "output.pcm" );
+        assert_eq!( matches.get_args(2), "" );
+
+        // the derived short options as the owned parse
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "", "--hex", false, "false", "Dump in hex") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_auto_short_option( true );
+        let argv = [ "-s", "44100", "-e" ];
+        let matches = opt_parse.parse_options_borrowed( &argv );
+        assert_eq!( matches.is_success(), true );
+        assert_eq!( matches.get_value("samplingRate"), "44100" );
+        assert_eq!( matches.get_value("-s"), "44100" );
+        assert_eq!( matches.was_set("hex"), true );
+        assert_eq!( matches.get_args_count(), 0 );
+    }
+
+    #[test]
+    fn test_opt_parse_get_value_ref() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value_ref("-s"), "44100" );
+        assert_eq!( opt_parse.get_value_ref("--encoding"), "PCM16" );
+        assert_eq!( opt_parse.get_value_ref("-x"), "" );
+    }
+
+    #[test]
+    fn test_opt_parse_many_options() {
+        let mut options = Vec::new();
+        for i in 0..200 {
+            options.push( OptParseItem::new( "", &format!( "--option{}", i ), true, "default", "Generated option") );
+        }
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--option150=150".to_string() );
+        argv.push( "--option7".to_string() );
+        argv.push( "7".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("--option150"), "150" );
+        assert_eq!( opt_parse.get_value("option7"), "7" );
+        assert_eq!( opt_parse.get_value("--option199"), "default" );
+        assert_eq!( opt_parse.get_option_item("--option42").unwrap().get_name(), "option42" );
+    }
+
+    #[test]
+    fn test_opt_parse_take_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "input.pcm".to_string() );
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv.clone(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.take_args(), argv );
+        assert_eq!( opt_parse.take_args().is_empty(), true );
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_args(0), "input.pcm" );
+    }
+
+    #[test]
+    fn test_opt_parse_ordered_values() {
+        let mut options = Vec::new();
+        let names = [ "zeta", "alpha", "mu", "beta", "omega", "gamma" ];
+        for name in names {
+            options.push( OptParseItem::new( "", &format!( "--{}", name ), true, name, "Generated option") );
+        }
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--omega=last".to_string() );
+        argv.push( "--zeta=first".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        let iterated : Vec<&str> = opt_parse.iter_values().map( |( name, _, _ )| name ).collect();
+        assert_eq!( iterated, names );
+        assert_eq!( opt_parse.get_value("omega"), "last" );
+        assert_eq!( opt_parse.get_value("--zeta"), "first" );
+    }
+
+    #[test]
+    fn test_opt_parse_from_iter() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+
+        let stream = "input.pcm -s 44100 --encoding PCM32 output.pcm".split( ' ' ).map( |arg| arg.to_string() );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_from_iter( stream, false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args(1), "output.pcm" );
+
+        // the value is missing at the end of the stream
+        let is_success = opt_parse.parse_options_from_iter( vec![ "-s".to_string() ], false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_matches_send_sync() {
+        fn assert_send_sync<T : Send + Sync>() {}
+        assert_send_sync::<Matches>();
+        assert_send_sync::<OptParse>();
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "input.pcm".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        let matches = std::sync::Arc::new( opt_parse.into_matches() );
+        let shared = std::sync::Arc::clone( &matches );
+        let worker = std::thread::spawn( move || {
+            ( shared.get_value("--samplingRate"), shared.get_args(0) )
+        });
+        assert_eq!( worker.join().unwrap(), ( "44100".to_string(), "input.pcm".to_string() ) );
+        assert_eq!( matches.was_set("-s"), true );
+    }
+
+    #[test]
+    fn test_opt_parse_derives() {
+        let item = OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100");
+        assert_eq!( item.clone(), item );
+        assert_eq!( item == OptParseItem::new( "-s", "--samplingRate", true, "44100", "Set sampling rate e.g. 44100"), false );
+        assert_eq!( format!( "{:?}", item ).contains( "samplingRate" ), true );
+        assert_eq!( OptParseItem::default().get_name(), "" );
+        assert_eq!( ValueSource::default(), ValueSource::Default );
+
+        // the struct update syntax by OptParseItemSpec
+        let spec = OptParseItemSpec { option : "-s".to_string(), full_option : "--samplingRate".to_string(), arg_required : true, value : "48000".to_string(), description : "Set sampling rate e.g. 44100".to_string(), ..Default::default() };
+        assert_eq!( OptParseItem::from( spec.clone() ), item );
+        let spec = OptParseItemSpec { name : "rate".to_string(), is_required : true, value_type : ValueType::Integer, ..spec };
+        assert_eq!( OptParseItem::from( spec ), item.clone().with_name( "rate" ).with_required( true ).with_value_type( ValueType::Integer ) );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+
+        let mut opt_parse = OptParse::new( argv.clone(), vec![ item.clone() ], "rst_opt_parse_test" );
+        let mut expected = OptParse::new( argv, vec![ item ], "rst_opt_parse_test" );
+        assert_eq!( opt_parse, expected );
+        opt_parse.parse_options( false );
+        assert_eq!( opt_parse == expected, false );
+        expected.parse_options( false );
+        assert_eq!( opt_parse.get_matches(), expected.get_matches() );
+        assert_eq!( opt_parse.clone(), opt_parse );
+        assert_eq!( OptParse::default().get_options().is_empty(), true );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_os() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-o", "--output", true, "out.pcm", "Set output file") );
+
+        let mut argv : Vec<OsString> = Vec::new();
+        argv.push( OsString::from( "input.pcm" ) );
+        argv.push( OsString::from( "-s" ) );
+        argv.push( OsString::from( "44100" ) );
+        argv.push( OsString::from( "--output=result.pcm" ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_os( argv, false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("-o"), "result.pcm" );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args(0), "input.pcm" );
+        assert_eq!( opt_parse.get_value_path("-o"), Path::new( "result.pcm" ) );
+        assert_eq!( opt_parse.get_value_os("-s"), "44100" );
+        assert_eq!( opt_parse.get_arg_os(0), "input.pcm" );
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[test]
+    fn test_opt_parse_os_non_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-o", "--output", true, "out.pcm", "Set output file") );
+
+        let mut argv : Vec<OsString> = Vec::new();
+        argv.push( OsString::from_vec( b"in\xff.pcm".to_vec() ) );
+        argv.push( OsString::from_vec( b"--output=out\xfe.pcm".to_vec() ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_os( argv, false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-o"), "out\u{FFFD}.pcm" );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args(0), "in\u{FFFD}.pcm" );
+
+        assert_eq!( opt_parse.get_value_os("-o").as_encoded_bytes(), b"out\xfe.pcm" );
+        assert_eq!( opt_parse.get_value_path("--output").as_os_str().as_encoded_bytes(), b"out\xfe.pcm" );
+        assert_eq!( opt_parse.get_arg_os(0).as_encoded_bytes(), b"in\xff.pcm" );
+        assert_eq!( opt_parse.get_arg_path(0).as_os_str().as_encoded_bytes(), b"in\xff.pcm" );
+        assert_eq!( opt_parse.get_arg_os(1), "" );
+        assert_eq!( opt_parse.get_value_os("-x"), "" );
+
+        // the value after ':' even if the value has '='
+        opt_parse.set_colon_value_separator( true );
+        let is_success = opt_parse.parse_options_os( vec![ OsString::from_vec( b"--output:a=b\xfe.pcm".to_vec() ) ], false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value_os("-o").as_encoded_bytes(), b"a=b\xfe.pcm" );
+
+        let mut opt_parse = OptParse::new( Vec::new(), vec![ OptParseItem::new( "-o", "--output", true, "out.pcm", "Set output file") ], "rst_opt_parse_test" );
+        opt_parse.set_windows_style_option( true );
+        let is_success = opt_parse.parse_options_os( vec![ OsString::from_vec( b"/output:x=\xfe.pcm".to_vec() ) ], false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value_os("-o").as_encoded_bytes(), b"x=\xfe.pcm" );
+
+        // the passthrough args as given
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_os( vec![ OsString::from( "--" ), OsString::from( "cat" ), OsString::from_vec( b"in\xff.pcm".to_vec() ) ], false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_passthrough_args(), [ "cat", "in\u{FFFD}.pcm" ] );
+        assert_eq!( opt_parse.get_passthrough_args_os().len(), 2 );
+        assert_eq!( opt_parse.get_passthrough_args_os()[0], "cat" );
+        assert_eq!( opt_parse.get_passthrough_args_os()[1].as_encoded_bytes(), b"in\xff.pcm" );
+    }
+
+    #[test]
+    fn test_opt_parse_windows_style_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "/s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "/encoding:PCM32".to_string() );
+        argv.push( "/v".to_string() );
+        argv.push( "/data/input.pcm".to_string() );
+
+        let mut opt_parse = OptParse::new( argv.clone(), options.clone(), "rst_opt_parse_test" );
+        opt_parse.set_windows_style_option( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args(0), "/data/input.pcm" );
+
+        // the slashes are just args without the mode
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+        assert_eq!( opt_parse.get_args_count(), 5 );
+    }
+
+    #[test]
+    fn test_opt_parse_posixly_correct() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "input.pcm".to_string() );
+        argv.push( "-v".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options.clone(), "rst_opt_parse_test" );
+        opt_parse.set_posixly_correct( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        assert_eq!( opt_parse.get_value("-s"), "44100" );
+        assert_eq!( opt_parse.get_value("-v"), "false" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args(0), "input.pcm" );
+        assert_eq!( opt_parse.get_args(1), "-v" );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-v".to_string() );
+        argv.push( "--".to_string() );
+        argv.push( "-s".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        opt_parse.set_posixly_correct( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value("-v"), "true" );
+        assert_eq!( opt_parse.get_value("-s"), "48000" );
+        assert_eq!( opt_parse.get_args_count(), 0 );
+        assert_eq!( opt_parse.get_passthrough_args(), [ "-s" ] );
+    }
+
+    #[test]
+    fn test_opt_parse_get_help() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--help".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_help_requested(), true );
+
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\n -s\t --samplingRate\t : Set sampling rate e.g. 44100\n -v\t --verbose     \t : Enable verbose mode\n" );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_write_help() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-h".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        opt_parse.set_auto_help( false );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_help_requested(), true );
+
+        let mut output : Vec<u8> = Vec::new();
+        opt_parse.write_help( &mut output ).unwrap();
+        assert_eq!( String::from_utf8( output ).unwrap(), opt_parse.get_help() );
+    }
+
+    #[test]
+    fn test_opt_parse_to_json() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-o", "--output", true, "a \"b\".wav", "Set output file") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-g", "--gain", true, "0.5", "Set gain").with_value_type( ValueType::Float ) );
+        options.push( OptParseItem::new( "-n", "--name", true, "", "Set name") );
+        options.push( OptParseItem::new( "-t", "--tag", true, "", "Set tag") );
+        options.push( OptParseItem::new( "-c", "--channels", true, "", "Channels").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_value_type( ValueType::Integer ).with_greedy_values() );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-v".to_string() );
+        argv.push( "--name=true".to_string() );
+        argv.push( "--tag=1.10".to_string() );
+        argv.push( "-i".to_string() );
+        argv.push( "1".to_string() );
+        argv.push( "2".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        // the string options stay the strings even if they look like the others
+        let json = "{\"samplingRate\":48000,\"output\":\"a \\\"b\\\".wav\",\"verbose\":true,\"gain\":0.5,\"name\":\"true\",\"tag\":\"1.10\",\"channels\":null,\"inputs\":[1,2]}";
+        assert_eq!( opt_parse.to_json(), json );
+        #[cfg(feature = "serde")]
+        assert_eq!( serde_json::to_string( opt_parse.get_matches() ).unwrap(), json );
+    }
+
+    #[test]
+    fn test_opt_parse_from_opt_parse() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            sampling_rate : u32,
+            verbose : bool,
+        }
+        impl FromOptParse for Config {
+            fn from_matches( matches : &Matches ) -> Result<Self, OptParseError> {
+                Ok( Config { sampling_rate : matches.get_value_as( "samplingRate" )?, verbose : matches.get_value_as( "verbose" )? } )
+            }
+        }
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-v".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options.clone(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.extract::<Config>(), Ok( Config { sampling_rate : 48000, verbose : true } ) );
+        assert_eq!( opt_parse.get_value_as::<u32>( "encoding" ), Err( OptParseError::UnknownOption { name : "encoding".to_string() } ) );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--samplingRate=fast".to_string() );
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.extract::<Config>(), Err( OptParseError::InvalidValue { name : "samplingRate".to_string(), value : "fast".to_string(), message : "invalid digit found in string".into() } ) );
+    }
+
+    #[test]
+    fn test_opt_parse_choices() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding").with_choices( &[ "PCM8", "PCM16" ] ) );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-e".to_string() );
+        argv.push( "PCM8".to_string() );
+        let mut opt_parse = OptParse::new( argv, options.clone(), "" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM8" );
+        assert_eq!( opt_parse.get_help(), " -e\t --encoding\t : Set Encoding [possible values: PCM8, PCM16]\n" );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--encoding=PCM24".to_string() );
+        let mut opt_parse = OptParse::new( argv, options, "" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, false );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_opt_parse_value_enum() {
+        crate::value_enum! {
+            #[derive(Clone, Copy, Debug, PartialEq)]
+            enum Encoding {
+                Pcm8 = "PCM8",
+                Pcm16 = "PCM16",
+            }
+        }
+        assert_eq!( Encoding::possible_values(), &[ "PCM8", "PCM16" ] );
+        assert_eq!( "PCM16".parse::<Encoding>(), Ok( Encoding::Pcm16 ) );
+        assert_eq!( "PCM24".parse::<Encoding>(), Err( "expected one of PCM8, PCM16".to_string() ) );
+        assert_eq!( Encoding::Pcm8.to_string(), "PCM8" );
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding").with_value_enum::<Encoding>() );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-e".to_string() );
+        argv.push( "PCM8".to_string() );
+        let mut opt_parse = OptParse::new( argv, options, "" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value_as::<Encoding>( "encoding" ), Ok( Encoding::Pcm8 ) );
+    }
+
+    #[test]
+    fn test_opt_parse_from_opt_parse_parse_from() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            sampling_rate : u32,
+        }
+        impl FromOptParse for Config {
+            fn from_matches( matches : &Matches ) -> Result<Self, OptParseError> {
+                Ok( Config { sampling_rate : matches.get_value_as( "samplingRate" )? } )
+            }
+            fn options() -> Vec<OptParseItem> {
+                vec![ OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") ]
+            }
+        }
+
+        assert_eq!( Config::parse_from( vec![ "-s".to_string(), "44100".to_string() ] ), Ok( Config { sampling_rate : 44100 } ) );
+        assert_eq!( Config::parse_from( vec![ "-s".to_string() ] ), Err( OptParseError::InvalidArgs ) );
+    }
+
+    #[test]
+    fn test_opt_parse_run_command() {
+        struct Convert;
+        impl Command for Convert {
+            fn name( &self ) -> &str { "convert" }
+            fn description( &self ) -> &str { "convert the file" }
+            fn options( &self ) -> Vec<OptParseItem> {
+                vec![ OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") ]
+            }
+            fn run( &self, matches : &Matches ) -> Result<i32, OptParseError> {
+                assert_eq!( matches.get_args( 0 ), "in.wav" );
+                matches.get_value_as::<i32>( "samplingRate" )
+            }
+        }
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "mytool".to_string() );
+        argv.push( "-v".to_string() );
+        argv.push( "convert".to_string() );
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "in.wav".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options.clone(), "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        assert_eq!( opt_parse.run_command( false ), Ok( 44100 ) );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\nCommands:\n convert\t : convert the file\nOptions:\n -v\t --verbose\t : Enable verbose mode\n" );
+
+        let mut opt_parse = OptParse::new( vec![ "mytool".to_string(), "info".to_string() ], options.clone(), "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        assert_eq!( opt_parse.run_command( false ), Err( OptParseError::UnknownCommand { name : "info".to_string(), suggestion : None, available : vec![ "convert".to_string() ] } ) );
+
+        let mut opt_parse = OptParse::new( vec![ "mytool".to_string(), "covnert".to_string() ], options.clone(), "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        let error = opt_parse.run_command( false ).unwrap_err();
+        assert_eq!( error.to_string(), "unknown command covnert, did you mean convert? (available: convert)" );
+
+        let mut opt_parse = OptParse::new( vec![ "mytool".to_string() ], options, "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        assert_eq!( opt_parse.run_command( false ), Err( OptParseError::MissingCommand ) );
+    }
+
+    #[test]
+    fn test_opt_parse_run_command_suggestion() {
+        struct Named( &'static str );
+        impl Command for Named {
+            fn name( &self ) -> &str { self.0 }
+            fn options( &self ) -> Vec<OptParseItem> { Vec::new() }
+            fn run( &self, _matches : &Matches ) -> Result<i32, OptParseError> { Ok( 0 ) }
+        }
+
+        let suggestion = |name : &str| {
+            let mut opt_parse = OptParse::new( vec![ "mytool".to_string(), name.to_string() ], Vec::new(), "rst_opt_parse_test" );
+            for name in [ "convert", "resample", "stats" ] {
+                opt_parse.add_command( Named( name ) );
+            }
+            match opt_parse.run_command( false ) {
+                Err( OptParseError::UnknownCommand { suggestion, .. } ) => suggestion,
+                result => panic!( "unexpected {:?}", result ),
+            }
+        };
+        // the swap, the substitution, the deletion and the insertion are one edit each
+        assert_eq!( suggestion( "covnert" ), Some( "convert".to_string() ) );
+        assert_eq!( suggestion( "resampel" ), Some( "resample".to_string() ) );
+        assert_eq!( suggestion( "stat" ), Some( "stats".to_string() ) );
+        assert_eq!( suggestion( "convertt" ), Some( "convert".to_string() ) );
+        assert_eq!( suggestion( "sample" ), Some( "resample".to_string() ) );
+        assert_eq!( suggestion( "info" ), None );
+        assert_eq!( suggestion( "x" ), None );
+    }
+
+    #[test]
+    fn test_opt_parse_run_multi_call() {
+        struct Info;
+        impl Command for Info {
+            fn name( &self ) -> &str { "pcminfo" }
+            fn options( &self ) -> Vec<OptParseItem> {
+                vec![ OptParseItem::new( "-c", "--channel", true, "2", "Set channel") ]
+            }
+            fn run( &self, matches : &Matches ) -> Result<i32, OptParseError> {
+                matches.get_value_as::<i32>( "channel" )
+            }
+        }
+
+        let mut opt_parse = OptParse::new( vec![ "/usr/bin/pcminfo".to_string(), "-c".to_string(), "6".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_command( Info );
+        assert_eq!( opt_parse.run_multi_call( "/usr/bin/pcminfo", false ), Ok( 6 ) );
+
+        let mut opt_parse = OptParse::new( vec![ "C:\\tools\\pcmtools.exe".to_string(), "pcminfo".to_string(), "-c".to_string(), "4".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_command( Info );
+        assert_eq!( opt_parse.run_multi_call( "C:\\tools\\pcmtools.exe", false ), Ok( 4 ) );
+    }
+
+    #[test]
+    fn test_opt_parse_from_str() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "convert -s 44100 \"my file.pcm\"", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "-s" ), "44100" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args( 1 ), "my file.pcm" );
+
+        let is_success = opt_parse.parse_from_str( "convert \"my file.pcm", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_to_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding") );
+        options.push( OptParseItem::new( "-c", "", true, "2", "Set channel") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v -c 6 \"my file.pcm\" -s 44100", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.to_args(), vec![ "--samplingRate=44100".to_string(), "-c".to_string(), "6".to_string(), "--verbose".to_string(), "my file.pcm".to_string() ] );
+        assert_eq!( opt_parse.to_command_line(), "--samplingRate=44100 -c 6 --verbose 'my file.pcm'" );
+    }
+
+    #[test]
+    fn test_opt_parse_to_args_values() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_greedy_values() );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v -i a.wav b.wav ; out.wav --inputs c.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "a.wav", "b.wav", "c.wav" ] );
+        let args = opt_parse.to_args();
+        assert_eq!( args, vec![ "--inputs=a.wav", "--inputs=b.wav", "--inputs=c.wav", "--verbose", "out.wav" ] );
+
+        opt_parse.add_options( vec![ OptParseItem::new( "-e", "--encodings", true, "", "Encodings").with_value_count( 1, 2 ) ] );
+        let is_success = opt_parse.parse_from_str( "-e PCM16 PCM24 ; out.wav", false );
+        assert_eq!( is_success, true );
+        let args = opt_parse.to_args();
+        assert_eq!( args, vec![ "--encodings=PCM16", "--encodings=PCM24", ";", "out.wav" ] );
+        let is_success = opt_parse.parse_options_from_iter( args, false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "encodings" ), vec![ "PCM16", "PCM24" ] );
+        assert_eq!( opt_parse.get_args( 0 ), "out.wav" );
+
+        // the explicit false overrides the profile's true
+        opt_parse.add_profile_option();
+        opt_parse.add_profile( "loud", &[ ( "verbose", "true" ) ] );
+        let is_success = opt_parse.parse_from_str( "--profile loud --verbose=false", false );
+        assert_eq!( is_success, true );
+        let args = opt_parse.to_args();
+        assert_eq!( args, vec![ "--verbose=false", "--profile=loud" ] );
+        let is_success = opt_parse.parse_options_from_iter( args, false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "verbose" ), "false" );
+    }
+
+    #[test]
+    fn test_opt_parse_to_args_round_trip() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "More output, repeatable") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v -v -s 44100 -v in.wav -- make -v clean", false );
+        assert_eq!( is_success, true );
+        let args = opt_parse.to_args();
+        assert_eq!( args, vec![ "--samplingRate=44100", "--verbose", "--verbose", "--verbose", "in.wav", "--", "make", "-v", "clean" ] );
+
+        let mut other = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = other.parse_options_from_iter( args, false );
+        assert_eq!( is_success, true );
+        assert_eq!( other.to_btreemap(), opt_parse.to_btreemap() );
+        assert_eq!( other.occurrences_of( "verbose" ), 3 );
+        assert_eq!( other.get_args( 0 ), "in.wav" );
+        assert_eq!( other.get_passthrough_args(), opt_parse.get_passthrough_args() );
+        assert_eq!( other.to_args(), opt_parse.to_args() );
+    }
+
+    #[test]
+    fn test_opt_parse_secret() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-u", "--user", true, "", "Set user") );
+        options.push( OptParseItem::new( "-t", "--token", true, "", "Set access token").with_secret( true ) );
+        options.push( OptParseItem::new( "-p", "--port", true, "80", "Set port").with_secret( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-u alice -t s3cr3t -p abc", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "token" ), "s3cr3t" );
+        assert_eq!( opt_parse.to_args()[1], "--token=s3cr3t" );
+        assert_eq!( opt_parse.to_command_line(), "--user=alice '--token=********' '--port=********'" );
+        assert_eq!( opt_parse.to_json(), "{\"user\":\"alice\",\"token\":\"********\",\"port\":\"********\"}" );
+        assert_eq!( opt_parse.get_value_as::<u16>( "port" ), Err( OptParseError::InvalidValue { name : "port".to_string(), value : "********".to_string(), message : "invalid digit found in string".into() } ) );
+        assert_eq!( opt_parse.to_btreemap().get( "token" ), Some( &"********".to_string() ) );
+        assert_eq!( opt_parse.to_btreemap().get( "user" ), Some( &"alice".to_string() ) );
+
+        // neither Debug
+        opt_parse.set_keep_raw_args( true );
+        let is_success = opt_parse.parse_from_str( "-u alice --token=s3cr3t", false );
+        assert_eq!( is_success, true );
+        let debug = format!( "{:?}", opt_parse.get_matches() );
+        assert_eq!( debug.contains( "s3cr3t" ), false );
+        assert_eq!( debug.contains( "alice" ), true );
+        assert_eq!( format!( "{:#?}", opt_parse ).contains( "s3cr3t" ), false );
+        let mut argv_parse = OptParse::new( vec![ "app".to_string(), "--token=s3cr3t".to_string() ], opt_parse.options.clone(), "" );
+        assert_eq!( argv_parse.parse_options( false ), true );
+        assert_eq!( format!( "{:?}", argv_parse ).contains( "s3cr3t" ), false );
+        #[cfg(feature = "std")]
+        assert_eq!( opt_parse.into_map().get( "token" ), Some( &"********".to_string() ) );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_prompt_missing() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_required( true ).with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "", "Set encoding").with_required( true ).with_choices( &[ "PCM8", "PCM16" ] ) );
+        options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel").with_required( true ) );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-c".to_string() );
+        argv.push( "6".to_string() );
+
+        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, false );
+
+        let mut input = "fast\n\nPCM24\nPCM8\n".as_bytes();
+        let mut output : Vec<u8> = Vec::new();
+        opt_parse.prompt_missing( &mut input, &mut output ).unwrap();
+        assert_eq!( String::from_utf8( output ).unwrap(), "Set sampling rate [48000]: not an integer\nSet sampling rate [48000]: Set encoding (PCM8, PCM16): not one of PCM8, PCM16\nSet encoding (PCM8, PCM16): " );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "48000" );
+        assert_eq!( opt_parse.get_int( "samplingRate" ), Some( 48000 ) );
+        assert_eq!( opt_parse.get_source( "samplingRate" ), Some( &ValueSource::Prompt ) );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM8" );
+        assert_eq!( opt_parse.get_source( "channel" ), Some( &ValueSource::CommandLine ) );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_password() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-u", "--user", true, "guest", "Set user") );
+        options.push( OptParseItem::new( "-p", "--password", true, "", "Password").with_password( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_options()[1].is_secret(), true );
+
+        let mut input = "s3cr3t\n".as_bytes();
+        let mut output : Vec<u8> = Vec::new();
+        let mut echo = Vec::new();
+        opt_parse.prompt_missing_with( &mut input, &mut output, &mut |enable| { echo.push( enable ); Ok( () ) } ).unwrap();
+        assert_eq!( String::from_utf8( output ).unwrap(), "Password: \n" );
+        assert_eq!( echo, vec![ false, true ] );
+        assert_eq!( opt_parse.get_value( "password" ), "s3cr3t" );
+        assert_eq!( opt_parse.to_json(), "{\"user\":\"guest\",\"password\":\"********\"}" );
+
+        // the password isn't read when the echo can't be turned off
+        opt_parse.reset();
+        let mut input = "s3cr3t\n".as_bytes();
+        let mut output : Vec<u8> = Vec::new();
+        let result = opt_parse.prompt_missing_with( &mut input, &mut output, &mut |_| Err( std::io::Error::from( std::io::ErrorKind::Unsupported ) ) );
+        assert_eq!( result.map_err( |error| error.kind() ), Err( std::io::ErrorKind::Unsupported ) );
+        assert_eq!( input, "s3cr3t\n".as_bytes() );
+        assert_eq!( opt_parse.was_set( "password" ), false );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_confirm() {
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_yes_option();
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+
+        let mut output : Vec<u8> = Vec::new();
+        assert_eq!( opt_parse.confirm_with( "really overwrite output.pcm?", &mut "Y\n".as_bytes(), &mut output ).unwrap(), true );
+        assert_eq!( String::from_utf8( output ).unwrap(), "really overwrite output.pcm? [y/N]: " );
+        assert_eq!( opt_parse.confirm_with( "really overwrite output.pcm?", &mut "\n".as_bytes(), &mut Vec::new() ).unwrap(), false );
+
+        let mut opt_parse = OptParse::new( vec![ "--yes".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_yes_option();
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        let mut output : Vec<u8> = Vec::new();
+        assert_eq!( opt_parse.confirm_with( "really overwrite output.pcm?", &mut "n\n".as_bytes(), &mut output ).unwrap(), true );
+        assert_eq!( output.is_empty(), true );
+        assert_eq!( opt_parse.confirm( "really overwrite output.pcm?" ), true );
+
+        // the application's own --yes
+        let options = vec![ OptParseItem::new( "", "--yes", false, "false", "Print yes forever") ];
+        let mut opt_parse = OptParse::new( vec![ "--yes".to_string() ], options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.confirm_with( "really overwrite output.pcm?", &mut "n\n".as_bytes(), &mut Vec::new() ).unwrap(), false );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_no_input() {
+        let mut opt_parse = OptParse::new( vec![ "--no-input".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_no_input_option();
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_interactive(), false );
+        assert_eq!( opt_parse.confirm( "really overwrite output.pcm?" ), false );
+
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        opt_parse.set_interactive( false );
+        assert_eq!( opt_parse.is_interactive(), false );
+
+        // the application's own --no-input is up to the terminal
+        let options = vec![ OptParseItem::new( "", "--no-input", false, "false", "Don't read stdin") ];
+        let mut opt_parse = OptParse::new( vec![ "--no-input".to_string() ], options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_interactive(), tty::is_stdin_terminal() );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_wizard() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM8", "PCM16" ] ) );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let mut output : Vec<u8> = Vec::new();
+        let command_line = opt_parse.run_wizard_with( &mut "44100\nPCM8\ny\n".as_bytes(), &mut output ).unwrap();
+        assert_eq!( String::from_utf8( output ).unwrap(), "Set sampling rate [48000]: Set encoding (PCM8, PCM16) [PCM16]: Enable verbose mode [y/N]: " );
+        assert_eq!( command_line, "--samplingRate=44100 --encoding=PCM8 --verbose" );
+        assert_eq!( opt_parse.get_int( "samplingRate" ), Some( 44100 ) );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM8" );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+    }
+
+    #[test]
+    fn test_opt_parse_get_level_filter() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+        options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Enable quiet mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v -v --verbose", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_level_filter( "verbose", "quiet" ), verbosity::LevelFilter::Debug );
+
+        let is_success = opt_parse.parse_from_str( "-q", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_level_filter( "verbose", "quiet" ), verbosity::LevelFilter::Off );
+    }
+
+    #[test]
+    fn test_opt_parse_verbosity_options() {
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_verbosity_options();
+
+        let is_success = opt_parse.parse_from_str( "", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_verbosity(), verbosity::LevelFilter::Error );
+
+        let is_success = opt_parse.parse_from_str( "-v -v", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_verbosity(), verbosity::LevelFilter::Info );
+
+        let is_success = opt_parse.parse_from_str( "--quiet", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_verbosity(), verbosity::LevelFilter::Off );
+
+        let is_success = opt_parse.parse_from_str( "-v -q", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn test_opt_parse_color_option() {
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.get_color_choice(), color::ColorChoice::Auto );
+        opt_parse.add_color_option();
+
+        let is_success = opt_parse.parse_from_str( "--color=never", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_color_choice(), color::ColorChoice::Never );
+
+        assert_eq!( opt_parse.styled_error( &OptParseError::InvalidArgs ), opt_parse.format_error( &OptParseError::InvalidArgs ) );
+
+        let is_success = opt_parse.parse_from_str( "--color always", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_color_choice(), color::ColorChoice::Always );
+        assert_eq!( opt_parse.styled_error( &OptParseError::InvalidArgs ), color::paint_error( &opt_parse.format_error( &OptParseError::InvalidArgs ) ) );
+        assert_eq!( opt_parse.styled_help( "Options:\n".to_string(), false ), "\x1b[1;4mOptions:\x1b[0m\n" );
+
+        let is_success = opt_parse.parse_from_str( "--color=rainbow", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_to_env_exports() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Set output file") );
+        options.push( OptParseItem::new( "-t", "--token", true, "", "Set access token").with_secret( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-s 44100 -o 'my file.pcm' -t s3cr3t", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.to_env_exports( "mytool" ), "export MYTOOL_SAMPLING_RATE=44100\nexport MYTOOL_OUTPUT='my file.pcm'\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_to_dotenv() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Set output file") );
+        options.push( OptParseItem::new( "-t", "--token", true, "", "Set access token").with_secret( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-o 'my \"file\".pcm' -t s3cr3t", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.to_dotenv( "mytool", false ), "MYTOOL_SAMPLING_RATE=48000\nMYTOOL_OUTPUT=\"my \\\"file\\\".pcm\"\n" );
+        assert_eq!( opt_parse.to_dotenv( "", true ), "SAMPLING_RATE=48000\nOUTPUT=\"my \\\"file\\\".pcm\"\nTOKEN=\"********\"\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_flag_explicit_bool() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v --verbose=false", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "verbose" ), "false" );
+
+        let is_success = opt_parse.parse_from_str( "--verbose=true", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+
+        let is_success = opt_parse.parse_from_str( "--verbose=maybe", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_normalizer() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100")
+            .with_normalizer( Normalizer::Trim )
+            .with_normalizer( Normalizer::Lowercase )
+            .with_normalizer( Normalizer::Synonyms( vec![ ( "cd".to_string(), "44100".to_string() ), ( "dvd".to_string(), "48000".to_string() ) ] ) ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding").with_normalizer( Normalizer::Uppercase ).with_choices( &[ "PCM8", "PCM16" ] ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-s ' CD ' -e pcm8", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM8" );
+    }
+
+    #[test]
+    fn test_opt_parse_strip_quotes() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-n", "--name", true, "", "Set name") );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "--name=\"my file\"".to_string() );
+
+        let mut opt_parse = OptParse::new( argv.clone(), options.clone(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "name" ), "\"my file\"" );
+
+        let mut opt_parse = OptParse::new( argv, options.clone(), "rst_opt_parse_test" );
+        opt_parse.set_strip_quotes( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "name" ), "my file" );
+
+        let mut opt_parse = OptParse::new( vec![ "-n".to_string(), "'it\"".to_string() ], options, "rst_opt_parse_test" );
+        opt_parse.set_strip_quotes( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "name" ), "'it\"" );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_expand_tilde() {
+        let home = std::env::var( "HOME" ).unwrap();
+        let home = home.trim_end_matches( '/' );
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Set output file").with_normalizer( Normalizer::ExpandTilde ) );
+        options.push( OptParseItem::new( "-n", "--name", true, "", "Set name") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_expand_tilde_args( true );
+        let is_success = opt_parse.parse_from_str( "'--output=~/out.pcm' -n '~' '~/in.pcm'", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "output" ), format!( "{}/out.pcm", home ) );
+        assert_eq!( opt_parse.get_value( "name" ), "~" );
+        assert_eq!( opt_parse.get_args( 0 ), format!( "{}/in.pcm", home ) );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_interpolate_env() {
+        let home = std::env::var( "HOME" ).unwrap();
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-o", "--output", true, "${HOME}/renders", "Set output directory") );
+        options.push( OptParseItem::new( "-n", "--name", true, "", "Set name") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-n '${HOME}'", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "output" ), "${HOME}/renders" );
+
+        opt_parse.set_interpolate_env( true );
+        let is_success = opt_parse.parse_from_str( "-n '$${HOME} is ${HOME}'", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "output" ), format!( "{}/renders", home ) );
+        assert_eq!( opt_parse.get_value( "name" ), format!( "${{HOME}} is {}", home ) );
+
+        // interpolated before the conversion
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-c", "--channels", true, "${RST_OPT_PARSE_TEST_CHANNELS}", "Set channels").with_value_type( ValueType::Integer ) );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_interpolate_env( true );
+        std::env::set_var( "RST_OPT_PARSE_TEST_RATE", "44100" );
+        std::env::set_var( "RST_OPT_PARSE_TEST_CHANNELS", "6" );
+        let is_success = opt_parse.parse_from_str( "--samplingRate=${RST_OPT_PARSE_TEST_RATE}", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_int( "samplingRate" ), Some( 44100 ) );
+        assert_eq!( opt_parse.get_int( "channels" ), Some( 6 ) );
+        assert_eq!( opt_parse.get_values( "channels" ), [ "6" ] );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn test_opt_parse_datetime() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "", "--since", true, "1970-01-01", "Process the files since").with_value_type( ValueType::DateTime ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--since=2022-10-01T09:00:00+09:00", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value_as::<datetime::DateTime>( "since" ).map( |since| since.to_unix_seconds() ), Ok( 1664582400 ) );
+
+        let is_success = opt_parse.parse_from_str( "--since=yesterday", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_opt_parse_glob() {
+        let dir = std::env::temp_dir().join( format!( "rst_opt_parse_glob_test_{}", std::process::id() ) );
+        std::fs::create_dir_all( &dir ).unwrap();
+        std::fs::write( dir.join( "a.pcm" ), "" ).unwrap();
+        std::fs::write( dir.join( "b.pcm" ), "" ).unwrap();
+        let base = dir.to_string_lossy().to_string();
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_value_type( ValueType::Glob( glob::NoMatch::Error ) ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_options_from_iter( vec![ format!( "--inputs={}/*.pcm", base ) ], false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_paths( "inputs" ), vec![ dir.join( "a.pcm" ), dir.join( "b.pcm" ) ] );
+
+        let is_success = opt_parse.parse_options_from_iter( vec![ format!( "--inputs={}/*.wav", base ) ], false );
+        assert_eq!( is_success, false );
+
+        std::fs::remove_dir_all( &dir ).unwrap();
+    }
+
+    #[test]
+    fn test_opt_parse_flags() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-f", "--feature", true, "", "Enable the feature").with_flags( &[ ( "resample", 0x1 ), ( "dither", 0x2 ), ( "normalize", 0x4 ) ] ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--feature=resample -f dither", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_flags( "feature" ), 0x3 );
+
+        let is_success = opt_parse.parse_from_str( "--feature=resample,normalize", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_flags( "feature" ), 0x5 );
+
+        let is_success = opt_parse.parse_from_str( "", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_flags( "feature" ), 0 );
+
+        let is_success = opt_parse.parse_from_str( "--feature=resample --feature=unknown", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_plus_toggle_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-x", "--xtrace", false, "false", "Print the commands") );
+        options.push( OptParseItem::new( "-e", "--errexit", false, "true", "Exit on the error") );
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Output file") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_plus_toggle_option( true );
+        let is_success = opt_parse.parse_from_str( "-x +e +o script.sh", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "xtrace" ), "true" );
+        assert_eq!( opt_parse.get_value( "errexit" ), "false" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args( 0 ), "+o" );
+
+        opt_parse.set_plus_toggle_option( false );
+        let is_success = opt_parse.parse_from_str( "+e", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "errexit" ), "true" );
+        assert_eq!( opt_parse.get_args( 0 ), "+e" );
+    }
+
+    #[test]
+    fn test_opt_parse_long_only_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::long_only( "--dry-run", false, "false", "Show what would be done") );
+        options.push( OptParseItem::long_only( "--output", true, "", "Output file") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--dry-run --output out.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "dry-run" ), "true" );
+        assert_eq!( opt_parse.get_value( "output" ), "out.wav" );
+        assert_eq!( opt_parse.get_value( "" ), "" );
+        assert_eq!( opt_parse.get_option_item( "" ), None );
+        assert_eq!( opt_parse.get_options()[ 0 ].get_option(), "" );
+        assert_eq!( opt_parse.to_args(), vec![ "--dry-run".to_string(), "--output=out.wav".to_string() ] );
+    }
+
+    #[test]
+    fn test_opt_parse_short_only_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::short_only( "-n", true, "10", "Number of lines") );
+        options.push( OptParseItem::short_only( "-q", false, "false", "Never print the headers") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_auto_short_option( true );
+        let is_success = opt_parse.parse_from_str( "-n 20 --unknown=1 input.txt -q", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "n" ), "20" );
+        assert_eq!( opt_parse.get_value( "q" ), "true" );
+        assert_eq!( opt_parse.get_value( "verbose" ), "false" );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_options()[ 0 ].get_full_option(), "" );
+        assert_eq!( opt_parse.to_args(), vec![ "-n".to_string(), "20".to_string(), "-q".to_string(), "input.txt".to_string() ] );
+    }
+
+    #[test]
+    fn test_opt_parse_single_dash_long_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "", "--encoding", true, "PCM16", "Set encoding") );
+        options.push( OptParseItem::new( "", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_single_dash_long_option( true );
+        let is_success = opt_parse.parse_from_str( "-samplingRate 44100 -encoding=PCM24 -verbose input.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM24" );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+        assert_eq!( opt_parse.get_args( 0 ), "input.wav" );
+        assert_eq!( opt_parse.get_help().contains( "--encoding" ), false );
+        assert_eq!( opt_parse.get_help().contains( "-encoding" ), true );
+
+        let is_success = opt_parse.parse_from_str( "-s 96000 --encoding PCM32", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "96000" );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM32" );
+
+        let is_success = opt_parse.parse_from_str( "-help", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_help_requested(), true );
+    }
+
+    #[test]
+    fn test_opt_parse_numeric_short_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::short_only( "-1", false, "false", "Mix down to mono") );
+        options.push( OptParseItem::short_only( "-2", false, "false", "Mix down to stereo") );
+        options.push( OptParseItem::new( "-g", "--gain", true, "0", "Set gain in dB") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-1 --gain -3.5 input.wav -10", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "1" ), "true" );
+        assert_eq!( opt_parse.get_value( "2" ), "false" );
+        assert_eq!( opt_parse.get_value( "gain" ), "-3.5" );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args( 1 ), "-10" );
+
+        // the registered flag wins over the negative number
+        let is_success = opt_parse.parse_from_str( "-g -2", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_value( "2" ), "true" );
+    }
+
+    #[test]
+    fn test_opt_parse_action() {
+        use core::sync::atomic::{ AtomicUsize, Ordering };
+        let verbosity = Arc::new( AtomicUsize::new( 0 ) );
+        let level = Arc::clone( &verbosity );
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages").with_action( move |_| { level.fetch_add( 1, Ordering::SeqCst ); } ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ).with_action( |value| assert_eq!( value, "PCM24" ) ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-v -v --encoding=PCM24", false );
+        assert_eq!( is_success, true );
+        assert_eq!( verbosity.load( Ordering::SeqCst ), 2 );
+
+        // no action for the invalid value
+        let is_success = opt_parse.parse_from_str( "--encoding=PCM32", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_provider() {
+        struct Mp3Plugin;
+        impl OptionProvider for Mp3Plugin {
+            fn name( &self ) -> &str {
+                "mp3"
+            }
+
+            fn options( &self ) -> Vec<OptParseItem> {
+                vec![ OptParseItem::new( "-b", "--bitrate", true, "128", "Set mp3 bitrate in kbps") ]
+            }
+
+            fn after_parse( &self, matches : &Matches ) -> Result<(), OptParseError> {
+                matches.get_value_as::<u32>( "bitrate" ).map( |_| () )
+            }
+        }
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_provider( Mp3Plugin );
+        assert_eq!( opt_parse.get_help().contains( "--bitrate" ), true );
+
+        let is_success = opt_parse.parse_from_str( "-s 44100 --bitrate 320", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "bitrate" ), "320" );
+
+        let is_success = opt_parse.parse_from_str( "--bitrate=high", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_add_group() {
+        fn audio_format_options() -> OptionGroup {
+            let mut options = Vec::new();
+            options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+            options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding") );
+            options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel") );
+            OptionGroup::new( "Audio format options", options )
+        }
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_group( audio_format_options() );
+        let is_success = opt_parse.parse_from_str( "-v -s 44100 --channel=1", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "channel" ), "1" );
+        assert_eq!( opt_parse.get_option_item( "-e" ).map( |option| option.get_group() ), Some( "Audio format options" ) );
+
+        let help = opt_parse.get_help();
+        let section = help.find( "Audio format options:\n" ).unwrap();
+        assert_eq!( help.find( "--verbose" ).unwrap() < section, true );
+        assert_eq!( help.find( "--samplingRate" ).unwrap() > section, true );
+    }
+
+    #[test]
+    fn test_opt_parse_add_namespace() {
+        let mut cache_options = Vec::new();
+        cache_options.push( OptParseItem::new( "-d", "--dir", true, "/tmp/cache", "Set cache directory") );
+        cache_options.push( OptParseItem::new( "-n", "--disable", false, "false", "Disable cache").with_conflicts( &[ "dir" ] ) );
+
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-d", "--dir", true, ".", "Set output directory") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_namespace( "cache", cache_options );
+        let is_success = opt_parse.parse_from_str( "-d out --cache-dir=/var/cache", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "dir" ), "out" );
+        assert_eq!( opt_parse.get_value( "cache.dir" ), "/var/cache" );
+        assert_eq!( opt_parse.get_value( "--cache-dir" ), "/var/cache" );
+        assert_eq!( opt_parse.get_help().contains( "cache:\n" ), true );
+
+        let is_success = opt_parse.parse_from_str( "--cache-dir=/var/cache --cache-disable", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_reset_and_parse_again() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut args = Vec::new();
+        args.push( "-s".to_string() );
+        args.push( "44100".to_string() );
+        args.push( "first.wav".to_string() );
+        let mut opt_parse = OptParse::new( args, options, "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_options( false ), true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+
+        opt_parse.reset();
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "48000" );
+        assert_eq!( opt_parse.was_set( "samplingRate" ), false );
+        assert_eq!( opt_parse.get_args_count(), 0 );
+
+        let is_success = opt_parse.parse_again( vec![ "-v".to_string(), "second.wav".to_string() ], false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "48000" );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+        assert_eq!( opt_parse.get_args( 0 ), "second.wav" );
+    }
+
+    #[test]
+    fn test_opt_parse_ignored_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-x 1 -s 44100 --unknown=2 in.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_ignored_args(), &[ "-x".to_string(), "--unknown=2".to_string() ] );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+
+        let is_success = opt_parse.parse_from_str( "-s 44100", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_ignored_args().is_empty(), true );
+    }
+
+    #[test]
+    fn test_opt_parse_raw_args_positions() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "in.wav -s 44100 --encoding=PCM24 out.wav -s 96000", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_raw_args().is_empty(), true );
+        assert_eq!( opt_parse.get_option_position( "samplingRate" ), Some( 5 ) );
+
+        opt_parse.set_keep_raw_args( true );
+        let is_success = opt_parse.parse_from_str( "in.wav -s 44100 --encoding=PCM24 out.wav -s 96000", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_raw_args().len(), 7 );
+        assert_eq!( opt_parse.get_raw_args()[ 3 ], "--encoding=PCM24" );
+        assert_eq!( opt_parse.get_option_position( "samplingRate" ), Some( 5 ) );
+        assert_eq!( opt_parse.get_option_position( "-e" ), Some( 3 ) );
+        assert_eq!( opt_parse.get_option_position( "verbose" ), None );
+        assert_eq!( opt_parse.get_arg_position( 0 ), Some( 0 ) );
+        assert_eq!( opt_parse.get_arg_position( 1 ), Some( 4 ) );
+        assert_eq!( opt_parse.get_arg_position( 2 ), None );
+    }
+
+    #[test]
+    fn test_opt_parse_help_layout() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "", "--dry-run", false, "false", "Show what would be done") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_help_layout( HelpLayout::default() );
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\n  -s, --samplingRate  Set sampling rate\n      --dry-run       Show what would be done\n  -e, --encoding      Set encoding [possible values: PCM16, PCM24]\n" );
+
+        opt_parse.set_help_layout( HelpLayout { indent : 1, gap : 3, max_option_width : 14, ..Default::default() } );
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\n -s, --samplingRate\n                  Set sampling rate\n     --dry-run    Show what would be done\n -e, --encoding   Set encoding [possible values: PCM16, PCM24]\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_help_multi_line_description() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding\nPCM8, PCM16, PCM24, PCM32 or FLOAT") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "" );
+        assert_eq!( opt_parse.get_help(), " -e\t --encoding\t : Set encoding\n   \t           \t   PCM8, PCM16, PCM24, PCM32 or FLOAT\n -v\t --verbose \t : Enable verbose mode\n" );
+
+        opt_parse.set_help_layout( HelpLayout::default() );
+        assert_eq!( opt_parse.get_help(), "  -e, --encoding  Set encoding\n                  PCM8, PCM16, PCM24, PCM32 or FLOAT\n  -v, --verbose   Enable verbose mode\n" );
+
+        opt_parse.set_help_layout( HelpLayout { width : 40, ..Default::default() } );
+        assert_eq!( opt_parse.get_help(), "  -e, --encoding  Set encoding\n                  PCM8, PCM16, PCM24,\n                  PCM32 or FLOAT\n  -v, --verbose   Enable verbose mode\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_long_description() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_long_description( "PCM8, PCM16, PCM24, PCM32 or FLOAT" ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "" );
+        opt_parse.set_help_layout( HelpLayout::default() );
+        assert_eq!( opt_parse.get_help(), "  -e, --encoding  Set encoding\n" );
+        assert_eq!( opt_parse.get_long_help(), "  -e, --encoding  Set encoding\n                  PCM8, PCM16, PCM24, PCM32 or FLOAT\n" );
+
+        opt_parse.set_auto_help( false );
+        let is_success = opt_parse.parse_from_str( "--help", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_matches().is_long_help_requested(), true );
+        let is_success = opt_parse.parse_from_str( "-h", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_matches().is_long_help_requested(), false );
+    }
+
+    #[test]
+    fn test_opt_parse_help_sections() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "" );
+        opt_parse.add_positional( "INPUT", "Input wav file" );
+        assert_eq!( opt_parse.get_help(), "Arguments:\n INPUT\t : Input wav file\nOptions:\n -s\t --samplingRate\t : Set sampling rate\n -v\t --verbose     \t : Enable verbose mode\n" );
+
+        opt_parse.set_help_sections( true );
+        opt_parse.set_help_layout( HelpLayout::default() );
+        assert_eq!( opt_parse.get_help(), "Arguments:\n  INPUT  Input wav file\nFlags:\n  -v, --verbose       Enable verbose mode\nOptions:\n  -s, --samplingRate  Set sampling rate\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_print_defaults() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ) );
+        options.push( OptParseItem::new( "-o", "--output", true, "my file.wav", "Output file").with_required( true ) );
+        options.push( OptParseItem::new( "", "--token", true, "abc", "API token").with_secret( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        opt_parse.add_print_defaults_option();
+        assert_eq!( opt_parse.get_defaults(), "samplingRate=48000\nencoding=PCM16\t# possible values: PCM16, PCM24\noutput='my file.wav'\ntoken=********\n" );
+
+        // none of the built-in options
+        let mut other = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        other.set_version( "mytool 1.2.0" );
+        other.add_check_option();
+        other.add_print_defaults_option();
+        other.add_dump_args_option();
+        other.add_yes_option();
+        other.add_no_input_option();
+        other.add_profile_option();
+        assert_eq!( other.get_defaults(), opt_parse.get_defaults() );
+
+        // the required options needn't be given
+        let is_success = opt_parse.parse_from_str( "--print-defaults", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "print-defaults" ), "true" );
+
+        // the application's own --print-defaults doesn't skip the required options
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Output file").with_required( true ) );
+        options.push( OptParseItem::new( "", "--print-defaults", false, "false", "Print the defaults of the device") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--print-defaults", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[ OptParseError::MissingOption { name : "output".to_string() } ] );
+    }
+
+    #[test]
+    fn test_opt_parse_message_catalog() {
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        let error = OptParseError::UnknownOption { name : "--rate".to_string() };
+        assert_eq!( opt_parse.format_error( &error ), "unknown option --rate" );
+
+        opt_parse.set_message_catalog( |key| if key == MessageKey::UnknownOption { Some( "unbekannte Option {name}".to_string() ) } else { None } );
+        assert_eq!( opt_parse.format_error( &error ), "unbekannte Option --rate" );
+        assert_eq!( opt_parse.format_error( &OptParseError::InvalidArgs ), "invalid arguments" );
+        assert_eq!( error.to_string(), "unknown option --rate" );
+
+        // the message of the value type is translated by its key
+        let options = vec![ OptParseItem::new( "-c", "--channels", true, "2", "Channels").with_choices( &[ "1", "2" ] ) ];
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_message_catalog( |key| if key == MessageKey::NotOneOf { Some( "keiner von {choices}".to_string() ) } else { None } );
+        let is_success = opt_parse.parse_from_str( "-c 6", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.format_error( opt_parse.get_error().unwrap() ), "invalid value \"6\" for channels: keiner von 1, 2" );
+    }
+
+    #[test]
+    fn test_opt_parse_terminal_width() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set the sampling rate of the output file") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "" );
+        opt_parse.set_help_layout( HelpLayout { width : 40, ..Default::default() } );
+        assert_eq!( opt_parse.get_terminal_width(), 40 );
+
+        opt_parse.set_terminal_width( 48 );
+        assert_eq!( opt_parse.get_terminal_width(), 48 );
+        assert_eq!( opt_parse.get_help(), "  -s, --samplingRate  Set the sampling rate of\n                      the output file\n" );
+
+        opt_parse.set_terminal_width( 0 );
+        assert_eq!( opt_parse.get_help(), "  -s, --samplingRate  Set the sampling rate of the output file\n" );
+    }
+
+    #[test]
+    fn test_opt_parse_dump_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-o", "--output", true, "out.wav", "Output file") );
+        options.push( OptParseItem::new( "", "--token", true, "abc", "API token").with_secret( true ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_dump_args_option();
+        let is_success = opt_parse.parse_from_str( "--dump-args -o 'my file.wav' --token xyz in.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
+        assert_eq!( opt_parse.get_dump_args(), "samplingRate=48000\t# default\noutput='my file.wav'\t# command line\ntoken=********\t# command line\n" );
+        #[cfg(feature = "std")]
+        assert_eq!( opt_parse.is_dump_args_requested(), true );
+
+        // none of the built-in options
+        opt_parse.add_check_option();
+        opt_parse.add_yes_option();
+        opt_parse.add_no_input_option();
+        opt_parse.add_profile_option();
+        let is_success = opt_parse.parse_from_str( "--dump-args --yes -o 'my file.wav' --token xyz", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_dump_args(), "samplingRate=48000\t# default\noutput='my file.wav'\t# command line\ntoken=********\t# command line\n" );
+
+        // the application's own --dump-args
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "", "--dump-args", false, "false", "Dump the args of the child process") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--dump-args", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "dump-args" ), "true" );
+        assert_eq!( opt_parse.get_dump_args(), "dump-args=true\t# command line\n" );
+        #[cfg(feature = "std")]
+        assert_eq!( opt_parse.is_dump_args_requested(), false );
+    }
+
+    #[test]
+    fn test_opt_parse_greedy_values() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--inputs", true, "in.wav", "Input files").with_greedy_values() );
+        options.push( OptParseItem::new( "-o", "--output", true, "out.wav", "Output file") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "c.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "in.wav" ] );
+
+        let is_success = opt_parse.parse_from_str( "-i a.wav b.wav -o mix.wav c.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "a.wav", "b.wav" ] );
+        assert_eq!( opt_parse.get_value( "inputs" ), "b.wav" );
+        assert_eq!( opt_parse.get_value( "output" ), "mix.wav" );
+        assert_eq!( opt_parse.get_args( 0 ), "c.wav" );
+
+        // the terminator ends the values, and the repeated option accumulates
+        let is_success = opt_parse.parse_from_str( "--inputs=a.wav b.wav ; c.wav -i d.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "a.wav", "b.wav", "d.wav" ] );
+        assert_eq!( opt_parse.get_args( 0 ), "c.wav" );
+
+        opt_parse.set_value_terminator( "--end" );
+        let is_success = opt_parse.parse_from_str( "-i a.wav --end b.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "a.wav" ] );
+        assert_eq!( opt_parse.get_args( 0 ), "b.wav" );
+
+        let is_success = opt_parse.parse_from_str( "-i --end b.wav", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_value_count() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_value_count( 2, 3 ) );
+        options.push( OptParseItem::new( "-r", "--range", true, "", "Start and end").with_value_count( 2, 2 ) );
+
+        // the values beyond the max are too many
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-i a.wav b.wav c.wav d.wav", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[ OptParseError::InvalidValue { name : "inputs".to_string(), value : "a.wav b.wav c.wav d.wav".to_string(), message : ValueMessage::new( MessageKey::ValueCount, &[ ( "min", "2".to_string() ), ( "max", "3".to_string() ) ] ) } ] );
+
+        // no value is missing only
+        let is_success = opt_parse.parse_from_str( "-i", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[ OptParseError::MissingValue { name : "inputs".to_string() } ] );
+
+        // or the args when they are declared
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_positional( "OUTPUT", "Output file" );
+        let is_success = opt_parse.parse_from_str( "-i a.wav b.wav c.wav d.wav -r 0 10 e.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_values( "inputs" ), vec![ "a.wav", "b.wav", "c.wav" ] );
+        assert_eq!( opt_parse.get_values( "range" ), vec![ "0", "10" ] );
+        assert_eq!( opt_parse.get_args_count(), 2 );
+        assert_eq!( opt_parse.get_args( 0 ), "d.wav" );
+
+        // too few
+        let is_success = opt_parse.parse_from_str( "-i a.wav -r 0 10", false );
+        assert_eq!( is_success, false );
+        // too many in total
+        let is_success = opt_parse.parse_from_str( "-i a.wav b.wav -i c.wav d.wav", false );
+        assert_eq!( is_success, false );
+        // not specified
+        let is_success = opt_parse.parse_from_str( "e.wav", false );
+        assert_eq!( is_success, true );
+    }
+
+    #[test]
+    fn test_opt_parse_passthrough_args() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_posixly_correct( true );
+        let is_success = opt_parse.parse_from_str( "-v -- sh -c 'exit 5'", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_passthrough_args(), [ "sh", "-c", "exit 5" ] );
+        #[cfg(all(feature = "std", unix))]
+        assert_eq!( opt_parse.run_passthrough().unwrap(), 5 );
+
+        let is_success = opt_parse.parse_from_str( "-v", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_passthrough_args().is_empty(), true );
+
+        // the child's -v isn't the host's --verbose without posixly_correct too
+        let mut opt_parse = OptParse::new( Vec::new(), vec![ OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") ], "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "in.wav -- make -v -- clean", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "verbose" ), "false" );
+        assert_eq!( opt_parse.get_args_count(), 1 );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
+        assert_eq!( opt_parse.get_passthrough_args(), [ "make", "-v", "--", "clean" ] );
+    }
+
+    #[test]
+    fn test_opt_parse_preprocessor() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( vec![ "-cd".to_string(), "in.wav".to_string() ], options, "rst_opt_parse_test" );
+        // the shorthand -cd is -s 44100, and the legacy --debug is --verbose
+        opt_parse.add_preprocessor( |args| args.into_iter().flat_map( |arg| if arg == "-cd" { vec![ "-s".to_string(), "44100".to_string() ] } else { vec![ arg ] } ).collect() );
+        opt_parse.add_preprocessor( |args| args.into_iter().map( |arg| if arg == "--debug" { "--verbose".to_string() } else { arg } ).collect() );
+        opt_parse.set_keep_raw_args( true );
+        let is_success = opt_parse.parse_options( false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
+        assert_eq!( opt_parse.get_raw_args(), [ "-s", "44100", "in.wav" ] );
+
+        let is_success = opt_parse.parse_from_str( "--debug -cd", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+    }
+
+    #[test]
+    fn test_opt_parse_middleware() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-d", "--duration", true, "1", "Duration in seconds") );
+        options.push( OptParseItem::new( "", "--frames", true, "", "Frames to render").with_value_type( ValueType::Integer ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_middleware( |matches| {
+            if matches.was_set( "frames" ) {
+                return Ok( () );
+            }
+            let frames = matches.get_value_as::<u64>( "duration" )? * matches.get_value_as::<u64>( "samplingRate" )?;
+            matches.set_derived_value( "frames", &frames.to_string() )
+        });
+        let is_success = opt_parse.parse_from_str( "-s 44100 -d 2", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "frames" ), "88200" );
+        assert_eq!( opt_parse.get_int( "frames" ), Some( 88200 ) );
+        assert_eq!( opt_parse.get_source( "frames" ), Some( &ValueSource::Derived ) );
+        assert_eq!( opt_parse.get_error(), None );
+
+        let is_success = opt_parse.parse_from_str( "-d two", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"two\" for duration: invalid digit found in string".to_string() ) );
+    }
+
+    #[test]
+    fn test_opt_parse_typed_values() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-g", "--gain", true, "1.0", "Gain").with_value_type( ValueType::Float ) );
+        options.push( OptParseItem::new( "", "--dither", true, "no", "Dither").with_value_type( ValueType::Bool ) );
+        options.push( OptParseItem::new( "-i", "--inputs", true, "", "Input files").with_value_type( ValueType::Integer ).with_greedy_values() );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-g 0.5 --dither=yes", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_int( "samplingRate" ), Some( 48000 ) );
+        assert_eq!( opt_parse.get_float( "gain" ), Some( 0.5 ) );
+        assert_eq!( opt_parse.get_bool( "dither" ), Some( true ) );
+        assert_eq!( opt_parse.get_typed( "gain" ), Some( &TypedValue::Float( 0.5 ) ) );
+        assert_eq!( opt_parse.get_int( "gain" ), None );
+
+        let is_success = opt_parse.parse_from_str( "-s 44.1k -g loud", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error(), Some( &OptParseError::InvalidValue { name : "samplingRate".to_string(), value : "44.1k".to_string(), message : ValueMessage::new( MessageKey::NotInteger, &[] ) } ) );
+
+        let is_success = opt_parse.parse_from_str( "-i 1 x", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"x\" for inputs: not an integer".to_string() ) );
+    }
+
+    #[test]
+    fn test_opt_parse_ordered_matches() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--input", true, "", "Input file") );
+        options.push( OptParseItem::new( "-g", "--gain", true, "0", "Gain in dB") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-i a.pcm --gain 3 -i b.pcm --gain=6 out.pcm", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "input" ), "b.pcm" );
+        assert_eq!( opt_parse.get_ordered_matches(), [
+            MatchEvent::Option { name : "input".to_string(), value : "a.pcm".to_string(), position : 0 },
+            MatchEvent::Option { name : "gain".to_string(), value : "3".to_string(), position : 2 },
+            MatchEvent::Option { name : "input".to_string(), value : "b.pcm".to_string(), position : 4 },
+            MatchEvent::Option { name : "gain".to_string(), value : "6".to_string(), position : 6 },
+            MatchEvent::Arg { value : "out.pcm".to_string(), position : 7 },
+        ] );
+
+        // the gain per input
+        let mut gains = Vec::new();
+        for event in opt_parse.get_ordered_matches() {
+            match event {
+                MatchEvent::Option { name, value, .. } if name == "input" => gains.push( ( value.as_str(), "0" ) ),
+                MatchEvent::Option { name, value, .. } if name == "gain" => if let Some( last ) = gains.last_mut() { last.1 = value.as_str(); },
+                _ => {}
+            }
+        }
+        assert_eq!( gains, vec![ ( "a.pcm", "3" ), ( "b.pcm", "6" ) ] );
+    }
+
+    #[test]
+    fn test_opt_parse_long_value_syntax() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_long_value_syntax( LongValueSyntax::EqualsOnly );
+        let is_success = opt_parse.parse_from_str( "--samplingRate=44100 -s 96000 --verbose=true", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "96000" );
+        let is_success = opt_parse.parse_from_str( "--samplingRate 44100", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_args( 0 ), "44100" );
+
+        opt_parse.set_long_value_syntax( LongValueSyntax::SeparateOnly );
+        let is_success = opt_parse.parse_from_str( "--samplingRate 44100", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        let is_success = opt_parse.parse_from_str( "--samplingRate=44100", false );
+        assert_eq!( is_success, false );
+    }
+
+    #[test]
+    fn test_opt_parse_early() {
+        let early = vec![ OptParseItem::new( "-c", "--config", true, "default.toml", "Config file"), OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") ];
+        let args = vec![ "--rate".to_string(), "44100".to_string(), "-c".to_string(), "studio.toml".to_string(), "in.wav".to_string() ];
+
+        let mut opt_parse = OptParse::new( Vec::new(), early.clone(), "rst_opt_parse_test" );
+        let matches = opt_parse.parse_early( &args, &early );
+        assert_eq!( matches.get_value( "config" ), "studio.toml" );
+        assert_eq!( matches.get_value( "verbose" ), "false" );
+        assert_eq!( matches.get_args_count(), 0 );
+
+        // the rest of the options per the config
+        if matches.get_value( "config" ) == "studio.toml" {
+            opt_parse.add_options( vec![ OptParseItem::new( "-r", "--rate", true, "48000", "Set sampling rate") ] );
+        }
+        let is_success = opt_parse.parse_options_from_iter( args, false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "rate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "config" ), "studio.toml" );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
+    }
+
+    #[test]
+    fn test_opt_parse_default_from() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "", "--outputRate", true, "", "Output sampling rate").with_default_from( "samplingRate" ) );
+        options.push( OptParseItem::new( "", "--monitorRate", true, "", "Monitor sampling rate").with_default_from( "--outputRate" ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-s 44100", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "outputRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "monitorRate" ), "44100" );
+        assert_eq!( opt_parse.get_source( "monitorRate" ), Some( &ValueSource::Default ) );
+
+        let is_success = opt_parse.parse_from_str( "--outputRate 96000", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "outputRate" ), "96000" );
+        assert_eq!( opt_parse.get_value( "monitorRate" ), "96000" );
+
+        options[ 0 ] = options[ 0 ].clone().with_default_from( "monitorRate" );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "the defaults refer to each other: samplingRate -> monitorRate -> outputRate -> samplingRate".to_string() ) );
+    }
+
+    #[test]
+    fn test_opt_parse_profile() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM24", "Set encoding") );
+        options.push( OptParseItem::new( "-c", "--channels", true, "2", "Channels").with_value_type( ValueType::Integer ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_profile_option();
+        opt_parse.add_profile( "cd-quality", &[ ( "samplingRate", "44100" ), ( "--encoding", "PCM16" ) ] );
+        let result = opt_parse.add_profiles_from_str( "# the presets\n[mono]\nchannels = 1\n[broken]\nchannels = \"two\"\n[tagged]\nencoding = \"PCM#16\"  # quoted\n" );
+        assert_eq!( result, Ok( () ) );
+
+        let is_success = opt_parse.parse_from_str( "--profile cd-quality -s 22050", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "22050" );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM16" );
+        assert_eq!( opt_parse.get_source( "encoding" ), Some( &ValueSource::Profile ) );
+        assert_eq!( opt_parse.get_value( "channels" ), "2" );
+
+        let is_success = opt_parse.parse_from_str( "--profile=mono", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_int( "channels" ), Some( 1 ) );
+
+        let is_success = opt_parse.parse_from_str( "--profile tagged", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM#16" );
+
+        let is_success = opt_parse.parse_from_str( "--profile broken", false );
+        assert_eq!( is_success, false );
+        let is_success = opt_parse.parse_from_str( "--profile dvd", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"dvd\" for profile: not one of cd-quality, mono, broken, tagged".to_string() ) );
+
+        assert_eq!( opt_parse.add_profiles_from_str( "channels = 1" ), Err( OptParseError::InvalidSpec { line : 1, message : "the value is outside of [profile]".to_string() } ) );
+
+        // the application's own --profile isn't the preset
+        let options = vec![ OptParseItem::new( "", "--profile", true, "", "Profile of the encoder") ];
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--profile=fast", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "profile" ), "fast" );
     }
 
+    #[test]
+    fn test_opt_parse_unit_value() {
+        let rate = units::UnitTable::new( &[ ( "Hz", 1.0 ), ( "kHz", 1000.0 ) ] );
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48kHz", "Set sampling rate").with_value_type( ValueType::Unit( rate ) ) );
+        options.push( OptParseItem::new( "-g", "--gain", true, "100%", "Gain").with_value_type( ValueType::Unit( units::UnitTable::new( &[ ( "%", 0.01 ) ] ).with_range( 0.0, 2.0 ) ) ) );
 
-    fn parse_options( &mut self, is_finish_if_help : bool ) -> bool {
-        let mut result = true;
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-s 44.1kHz --gain=50%", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44.1kHz" );
+        assert_eq!( opt_parse.get_float( "samplingRate" ), Some( 44100.0 ) );
+        assert_eq!( opt_parse.get_float( "gain" ), Some( 0.5 ) );
 
-        let  _options = &self.options.clone();
-        for option in _options {
-            result = result & self.parse_option( &option );
-        }
+        let is_success = opt_parse.parse_from_str( "--gain=300%", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_float( "samplingRate" ), Some( 48000.0 ) );
+        assert_eq!( opt_parse.get_help().contains( "Set sampling rate [accepts Hz/kHz suffixes]" ), true );
+    }
 
-        let argc = &self.args.len();
+    #[test]
+    fn test_opt_parse_check() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--input", true, "", "Input file").with_required( true ) );
+        options.push( OptParseItem::new( "-c", "--channels", true, "2", "Channels").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Quiet").with_conflicts( &[ "verbose" ] ) );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Verbose").with_conflicts( &[ "quiet" ] ) );
 
-        // -h or --help and call print_help()
-        for i in 0..*argc {
-            let arg = &self.args[i];
-            if arg.eq( "-h" ) || arg.starts_with( "--help" ){
-                self.print_help();
-                if is_finish_if_help {
-                    std::process::exit(0);
-                }
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_check_option();
+        opt_parse.add_middleware( |matches : &mut Matches| {
+            match matches.get_value( "channels" ) == "8" {
+                true => Err( OptParseError::InvalidValue { name : "channels".to_string(), value : "8".to_string(), message : "not supported".into() } ),
+                false => Ok( () ),
             }
-        }
+        });
 
-        // parse args
-        let mut i : usize = 0;
-        while i < *argc {
-            let arg = &self.args[i];
-            if arg.starts_with( "-" ){
-                for option in _options {
-                    if arg.eq( &option.option ) && option.arg_required {
-                        i = i + 1;
-                    }
-                }
-            } else {
-                self.arg_values.push( arg.to_string() );
-            }
-            i = i + 1;
-        }
+        // the conflict declared by the both is reported once
+        let is_success = opt_parse.parse_from_str( "--check -c two -q -v", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[
+            OptParseError::InvalidValue { name : "channels".to_string(), value : "two".to_string(), message : ValueMessage::new( MessageKey::NotInteger, &[] ) },
+            OptParseError::MissingOption { name : "input".to_string() },
+            OptParseError::ConflictingOptions { name : "quiet".to_string(), other : "verbose".to_string() },
+        ] );
+        assert_eq!( opt_parse.format_error( &opt_parse.get_violations()[2] ), "quiet can't be used with verbose" );
 
-        result
-    }
+        let is_success = opt_parse.parse_from_str( "--check -i in.wav -c 8", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations().len(), 1 );
 
-    fn parse_option( &mut self, option : &OptParseItem ) -> bool {
-        let mut result = true;
-
-        let argc = &self.args.len();
-        let mut value : String = option.value.clone();
-        let mut found_set_true = false;
-        for i in 0..*argc {
-            let arg = &self.args[i];
-            if option.option.eq( arg ) {
-                // -s case
-                if option.arg_required {
-                    if (i+1) < *argc  {
-                        if !self.args[ i+1 ].starts_with("-") {
-                            value = self.args[ i+1 ].clone();
-                        } else {
-                            // this is arg required case but i+1 is not the value for the option
-                            result = false;
-                        }
-                    } else {
-                        // this is arg required case but i+1 isn't present
-                        result = false;
+        let is_success = opt_parse.parse_from_str( "--check -i in.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_violations().is_empty(), true );
 
-                    }
-                } else {
-                    found_set_true = true;
-                }
-            } if arg.starts_with( &option.full_option ) {
-                // --something case
-                if option.arg_required {
-                    let pos = arg.find("=");
-                    match pos {
-                        Some(the_pos) => {
-                            value = arg[the_pos+1..].to_string();
-                        },
-                        None => { result = false }
-                    }
-                } else {
-                    found_set_true = true;
-                }
-            }
-        }
-        if found_set_true {
-            value = "true".to_string();
-        }
-        let mut key = option.option.clone();
-        if key.is_empty() {
-            key = option.full_option.clone();
-        }
-        let _ = &self.alias.insert( option.full_option.clone(), key.clone() );
-        let _ = &self.values.insert( key, value );
+        let is_success = opt_parse.parse_from_str( "-i in.wav -c", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[ OptParseError::MissingValue { name : "channels".to_string() } ] );
 
-        result
-    }
+        // the application's own --check isn't the validation
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-i", "--input", true, "", "Input file").with_required( true ) );
+        options.push( OptParseItem::new( "-c", "--channels", true, "2", "Channels").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::new( "", "--check", false, "false", "Check the output") );
 
-    fn print_help(&self){
-        let options_len = &self.options.len();
-        let mut max_short_option_len : usize = 0;
-        let mut max_full_option_len : usize = 0;
-        for i in 0..*options_len {
-            max_short_option_len = cmp::max( max_short_option_len, self.options[i].option.len() );
-            max_full_option_len  = cmp::max( max_full_option_len,  self.options[i].full_option.len() );
-        }
-        if !&self.description.is_empty() {
-            println!( "{}", &self.description );
-        }
-        for i in 0..*options_len {
-            println!( " {:short_len$}\t {:full_len$}\t : {}", &self.options[i].option, &self.options[i].full_option, &self.options[i].description, short_len = max_short_option_len, full_len = max_full_option_len );
-        }
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_middleware( |_ : &mut Matches| Err( OptParseError::InvalidArgs ) );
+        let is_success = opt_parse.parse_from_str( "--check -i in.wav -c two", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations().len(), 1 );
+
+        let is_success = opt_parse.parse_from_str( "--check -i in.wav", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_value( "check" ), "true" );
     }
 
-    fn get_value( &self, option : &str ) -> String {
-        let mut key = option.to_string();
+    #[test]
+    fn test_opt_parse_renamed_option() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Verbose") );
 
-        match self.alias.get( option ){
-            Some( v ) => { key = v.to_string(); },
-            None => {}
-        }
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_renamed_option( "--rate", "--samplingRate" );
+        opt_parse.add_renamed_option( "--debug", "verbose" );
+        opt_parse.add_renamed_option( "--verbose", "samplingRate" );
 
-        match self.values.get( &key ){
-            Some( v ) => v.to_string(),
-            None => String::from("")
-        }
-    }
+        let is_success = opt_parse.parse_from_str( "--rate=44100 --debug --verbose", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_source( "samplingRate" ), Some( &ValueSource::CommandLine ) );
+        assert_eq!( opt_parse.get_value( "verbose" ), "true" );
+        assert_eq!( opt_parse.get_warnings(), vec![
+            "warning: --rate is deprecated, use --samplingRate instead".to_string(),
+            "warning: --debug is deprecated, use --verbose instead".to_string(),
+        ] );
 
-    fn get_args_count(&self) -> usize {
-        self.arg_values.len()
-    }
+        let is_success = opt_parse.parse_from_str( "--rate 22050", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "22050" );
 
-    fn get_args(&self, index : usize ) -> String {
-        let mut result = String::from("");
-        if index < self.get_args_count() {
-            result = self.arg_values[ index ].to_string();
-        }
-        result
+        let is_success = opt_parse.parse_from_str( "-s 96000", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_warnings().is_empty(), true );
     }
-}
-
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_opt_parse() {
+    fn test_opt_parse_env_only() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-r", "--samplingRate", true, "48000", "Set Sampling Rate") );
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::env_only( "apiToken", "", "API token").with_secret( true ).with_required( true ) );
+        options.push( OptParseItem::env_only( "retries", "3", "Retries").with_value_type( ValueType::Integer ) );
+        options.push( OptParseItem::env_only( "cacheDir", "", "Cache directory").with_normalizer( Normalizer::ExpandTilde ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "-r".to_string() );
-        argv.push( "44100".to_string());
-        argv.push( "--encoding=PCM32".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_env_prefix( "rst_opt_parse_test" );
+        assert_eq!( opt_parse.get_env_var( "apiToken" ), Some( "RST_OPT_PARSE_TEST_API_TOKEN".to_string() ) );
+        assert_eq!( opt_parse.get_env_var( "samplingRate" ), None );
+        assert_eq!( opt_parse.get_help().ends_with( "Environment:\n RST_OPT_PARSE_TEST_API_TOKEN\t : API token\n RST_OPT_PARSE_TEST_RETRIES  \t : Retries\n RST_OPT_PARSE_TEST_CACHE_DIR\t : Cache directory\n" ), true );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        let is_success = opt_parse.parse_from_str( "-s 44100", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[ OptParseError::MissingOption { name : "apiToken".to_string() } ] );
+
+        std::env::set_var( "RST_OPT_PARSE_TEST_API_TOKEN", "s3cr3t" );
+        std::env::set_var( "RST_OPT_PARSE_TEST_RETRIES", "5" );
+        std::env::set_var( "RST_OPT_PARSE_TEST_CACHE_DIR", "~/y" );
+        let is_success = opt_parse.parse_from_str( "-s 44100", false );
         assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "apiToken" ), "s3cr3t" );
+        assert_eq!( opt_parse.get_source( "apiToken" ), Some( &ValueSource::Environment ) );
+        assert_eq!( opt_parse.get_int( "retries" ), Some( 5 ) );
+        assert_eq!( opt_parse.get_value( "cacheDir" ), format!( "{}/y", std::env::var( "HOME" ).unwrap().trim_end_matches( '/' ) ) );
+        assert_eq!( opt_parse.to_args(), vec![ "--samplingRate=44100".to_string() ] );
 
-        assert_eq!( opt_parse.get_value("-r"), "44100" );
-        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
-        assert_eq!( opt_parse.get_value("-c"), "2" );
+        std::env::set_var( "RST_OPT_PARSE_TEST_RETRIES", "many" );
+        let is_success = opt_parse.parse_from_str( "", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"many\" for retries: not an integer (from env RST_OPT_PARSE_TEST_RETRIES)".to_string() ) );
+        std::env::remove_var( "RST_OPT_PARSE_TEST_API_TOKEN" );
+        std::env::remove_var( "RST_OPT_PARSE_TEST_RETRIES" );
+        std::env::remove_var( "RST_OPT_PARSE_TEST_CACHE_DIR" );
     }
 
+    #[cfg(feature = "config")]
     #[test]
-    fn test_opt_parse_help() {
+    fn test_opt_parse_config_values() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-r", "--samplingRate", true, "48000", "Set Sampling Rate") );
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM24", "Set encoding").with_normalizer( Normalizer::Uppercase ) );
+        options.push( OptParseItem::env_only( "region", "us", "Region") );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "-h".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_profile_option();
+        opt_parse.add_profile( "cd-quality", &[ ( "samplingRate", "44100" ), ( "encoding", "PCM16" ) ] );
+        assert_eq!( opt_parse.add_config_from_str( "region = eu\n--encoding = pcm32\n" ), Ok( () ) );
+        assert_eq!( opt_parse.add_config_from_str( "bitrate = 320" ).map_err( |error| error.to_string() ), Err( "unknown option bitrate (from config file)".to_string() ) );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        let is_success = opt_parse.parse_from_str( "--profile cd-quality", false );
         assert_eq!( is_success, true );
-
-        assert_eq!( opt_parse.get_value("-h"), "" );
+        assert_eq!( opt_parse.get_value( "region" ), "eu" );
+        assert_eq!( opt_parse.get_source( "region" ), Some( &ValueSource::Config ) );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM32" );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_source( "samplingRate" ), Some( &ValueSource::Profile ) );
     }
 
     #[test]
-    fn test_opt_parse_exception_no_opt_parse_item() {
-        let options = Vec::new();
+    fn test_opt_parse_visibility() {
+        let mut options = Vec::new();
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "", "--dither", true, "tpdf", "Dither").with_visibility( Visibility::Advanced ) );
+        options.push( OptParseItem::new( "", "--dump-internal-state", false, "false", "Dump the state").with_visibility( Visibility::Internal ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "-r".to_string() );
-        argv.push( "44100".to_string());
-        argv.push( "--encoding=PCM32".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\n -s\t --samplingRate\t : Set sampling rate\nUse --help-all to show the advanced options\n" );
+        assert_eq!( opt_parse.get_help_all(), "rst_opt_parse_test\n -s\t --samplingRate\t : Set sampling rate\n   \t --dither      \t : Dither\n" );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        let is_success = opt_parse.parse_from_str( "--help-all", false );
         assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_matches().is_help_all_requested(), true );
+        assert_eq!( opt_parse.get_matches().is_long_help_requested(), true );
 
-        assert_eq!( opt_parse.get_value("-r"), "" );
-        assert_eq!( opt_parse.get_value("-e"), "" );
-        assert_eq!( opt_parse.get_value("-c"), "" );
+        let is_success = opt_parse.parse_from_str( "--dither rpdf --dump-internal-state", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "dither" ), "rpdf" );
+        assert_eq!( opt_parse.get_value( "dump-internal-state" ), "true" );
     }
 
     #[test]
-    fn test_opt_parse_no_arg() {
+    fn test_opt_parse_extensions() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
-        options.push( OptParseItem::new( "-q", "--quiet", false, "false", "Enable quiet mode") );
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Output file").with_extensions( &[ "pcm", ".wav" ] ) );
+        options.push( OptParseItem::new( "-l", "--log", true, "", "Log file").with_extensions( &[ ".log" ] ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "-v".to_string() );
-        argv.push( "-s".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_positional_with_extensions( "INPUT", "Input files", &[ ".wav" ] );
+        assert_eq!( opt_parse.get_option_item( "output" ).map( |item| item.get_extensions() ), Some( &[ ".pcm".to_string(), ".wav".to_string() ][..] ) );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        let is_success = opt_parse.parse_from_str( "-o out.PCM in.wav in2.wav", false );
         assert_eq!( is_success, true );
 
-        assert_eq!( opt_parse.get_value("-v"), "true" );
-        assert_eq!( opt_parse.get_value("-q"), "false" );
-        assert_eq!( opt_parse.get_value("-s"), "" );
+        let is_success = opt_parse.parse_from_str( "-o out.mp3 --log=run.txt in.wav in2.mp3", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[
+            OptParseError::InvalidValue { name : "output".to_string(), value : "out.mp3".to_string(), message : ValueMessage::new( MessageKey::NotExtension, &[ ( "extensions", ".pcm or .wav".to_string() ) ] ) },
+            OptParseError::InvalidValue { name : "log".to_string(), value : "run.txt".to_string(), message : ValueMessage::new( MessageKey::NotExtension, &[ ( "extensions", ".log".to_string() ) ] ) },
+            OptParseError::InvalidValue { name : "INPUT".to_string(), value : "in2.mp3".to_string(), message : ValueMessage::new( MessageKey::NotExtension, &[ ( "extensions", ".wav".to_string() ) ] ) },
+        ] );
     }
 
+    #[cfg(feature = "config")]
     #[test]
-    fn test_opt_parse_arg_required_but_nothing() {
+    fn test_opt_parse_error_origin() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_value_type( ValueType::Integer ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "-s".to_string() );
-        argv.push( "-v".to_string() ); // Expects the -s's value here but not specified
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.add_config_from_source( "samplingRate = abc", "~/.mytool.toml" ), Ok( () ) );
+        let is_success = opt_parse.parse_from_str( "", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"abc\" for samplingRate: not an integer (from config file ~/.mytool.toml)".to_string() ) );
+        assert_eq!( opt_parse.add_config_from_source( "samplingRate", "~/.mytool.toml" ).map_err( |error| error.to_string() ), Err( "invalid spec at line 1: expected key = value (from config file ~/.mytool.toml)".to_string() ) );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        // the values in the args are from the command line as before
+        let is_success = opt_parse.parse_from_str( "-s abc", false );
         assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"abc\" for samplingRate: not an integer".to_string() ) );
 
-        assert_eq!( opt_parse.get_value("-v"), "true" );
-        assert_eq!( opt_parse.get_value("-s"), "48000" );
-        assert_eq!( opt_parse.get_value("-x"), "" );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_profile_option();
+        opt_parse.add_profile( "broken", &[ ( "samplingRate", "fast" ) ] );
+        let is_success = opt_parse.parse_from_str( "--profile broken", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error().map( |error| error.to_string() ), Some( "invalid value \"fast\" for samplingRate: not an integer (from profile broken)".to_string() ) );
     }
 
     #[test]
-    fn test_opt_parse_args() {
-        let mut options = Vec::new();
-        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+    fn test_opt_parse_command_categories() {
+        struct Named( &'static str, &'static str );
+        impl Command for Named {
+            fn name( &self ) -> &str { self.0 }
+            fn description( &self ) -> &str { self.0 }
+            fn category( &self ) -> &str { self.1 }
+            fn options( &self ) -> Vec<OptParseItem> { Vec::new() }
+            fn run( &self, _matches : &Matches ) -> Result<i32, OptParseError> { Ok( 0 ) }
+        }
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "input.csv".to_string() );
-        argv.push( "-v".to_string() );
-        argv.push( "output1.csv".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
-        argv.push( "output2.csv".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_command( Named( "gc", "Maintenance" ) );
+        opt_parse.add_command( Named( "stats", "Analysis" ) );
+        opt_parse.add_command( Named( "convert", "Conversion" ) );
+        opt_parse.add_command( Named( "help", "" ) );
+        opt_parse.add_command( Named( "resample", "Conversion" ) );
+        opt_parse.set_command_categories( &[ "Conversion", "Analysis" ] );
+        assert_eq!( opt_parse.get_help(), "rst_opt_parse_test\nCommands:\n help    \t : help\nConversion:\n convert \t : convert\n resample\t : resample\nAnalysis:\n stats   \t : stats\nMaintenance:\n gc      \t : gc\nOptions:\n" );
+    }
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
-        assert_eq!( is_success, true );
+    #[test]
+    fn test_opt_parse_command_version() {
+        struct Convert;
+        impl Command for Convert {
+            fn name( &self ) -> &str { "convert" }
+            fn version( &self ) -> Option<&str> { Some( "convert 0.9.1 (libsamplerate 0.2.2)" ) }
+            fn options( &self ) -> Vec<OptParseItem> { Vec::new() }
+            fn run( &self, _matches : &Matches ) -> Result<i32, OptParseError> { Ok( 1 ) }
+        }
+        struct Info;
+        impl Command for Info {
+            fn name( &self ) -> &str { "info" }
+            fn options( &self ) -> Vec<OptParseItem> { Vec::new() }
+            fn run( &self, _matches : &Matches ) -> Result<i32, OptParseError> { Ok( 2 ) }
+        }
 
-        assert_eq!( opt_parse.get_value("-v"), "true" );
-        assert_eq!( opt_parse.get_value("-s"), "44100" );
-        assert_eq!( opt_parse.get_value("-x"), "" );
+        let mut opt_parse = OptParse::new( Vec::new(), Vec::new(), "rst_opt_parse_test" );
+        opt_parse.set_version( "mytool 1.2.0" );
+        opt_parse.add_command( Convert );
+        opt_parse.add_command( Info );
+        assert_eq!( opt_parse.get_version(), Some( "mytool 1.2.0" ) );
+        assert_eq!( opt_parse.get_command_version( "convert" ), Some( "convert 0.9.1 (libsamplerate 0.2.2)" ) );
+        assert_eq!( opt_parse.get_command_version( "info" ), Some( "mytool 1.2.0" ) );
+        assert_eq!( opt_parse.get_help().contains( "--version\t : Print the version\n" ), true );
 
-        assert_eq!( opt_parse.get_args_count(), 3 );
-        assert_eq!( opt_parse.get_args(0), "input.csv" );
-        assert_eq!( opt_parse.get_args(0), "input.csv" );
-        assert_eq!( opt_parse.get_args(1), "output1.csv" );
-        assert_eq!( opt_parse.get_args(2), "output2.csv" );
-        assert_eq!( opt_parse.get_args(3), "" );
+        let is_success = opt_parse.parse_from_str( "--version", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_version_requested(), true );
+
+        let mut opt_parse = OptParse::new( vec![ "mytool".to_string(), "convert".to_string(), "--version".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        assert_eq!( opt_parse.run_command( false ), Ok( 0 ) );
+        let mut opt_parse = OptParse::new( vec![ "mytool".to_string(), "convert".to_string() ], Vec::new(), "rst_opt_parse_test" );
+        opt_parse.add_command( Convert );
+        assert_eq!( opt_parse.run_command( false ), Ok( 1 ) );
     }
 
     #[test]
-    fn test_opt_parse_full_option_only() {
+    fn test_opt_parse_diff() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode") );
-        options.push( OptParseItem::new( "", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
-
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "input.csv".to_string() );
-        argv.push( "-v".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding") );
+        options.push( OptParseItem::new( "-p", "--password", true, "", "Password").with_secret( true ) );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
-        assert_eq!( is_success, true );
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_from_str( "-e PCM16 -p old", false ), true );
+        let baseline = opt_parse.get_matches().clone();
+        assert_eq!( opt_parse.parse_from_str( "-s 44100 -p new", false ), true );
+        let diffs = opt_parse.diff( &baseline );
+        assert_eq!( diffs, vec![ ValueDiff { name : "samplingRate".to_string(), before : Some( ( "44100".to_string(), ValueSource::CommandLine ) ), after : Some( ( "48000".to_string(), ValueSource::Default ) ) } ] );
+        assert_eq!( diffs[0].to_string(), "samplingRate: 44100 (command line) -> 48000 (default)" );
+        assert_eq!( baseline.diff( &baseline ), Vec::new() );
 
-        assert_eq!( opt_parse.get_value("-v"), "true" );
-        assert_eq!( opt_parse.get_value("-s"), "" );
-        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
-        assert_eq!( opt_parse.get_value("-x"), "" );
+        let mut other = OptParse::new( Vec::new(), vec![ OptParseItem::new( "-o", "--output", true, "out.wav", "Output") ], "rst_opt_parse_test" );
+        assert_eq!( other.parse_from_str( "", false ), true );
+        let diffs = baseline.diff( other.get_matches() );
+        assert_eq!( diffs.iter().map( |diff| diff.to_string() ).collect::<Vec<_>>(), vec![
+            "samplingRate: 48000 (default) -> (none)".to_string(),
+            "encoding: PCM16 (command line) -> (none)".to_string(),
+            "password: '********' (command line) -> (none)".to_string(),
+            "output: (none) -> out.wav (default)".to_string(),
+        ] );
     }
 
     #[test]
-    fn test_opt_parse_full_option_alias() {
+    fn test_opt_parse_required_one_of() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-i", "--input", true, "", "Input file") );
+        options.push( OptParseItem::new( "-d", "--device", true, "", "Input device") );
+        options.push( OptParseItem::new( "-g", "--generate", false, "false", "Generate the sine wave") );
+        options.push( OptParseItem::new( "-o", "--output", true, "", "Output file") );
+        options.push( OptParseItem::new( "-p", "--play", false, "false", "Play") );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "input.csv".to_string() );
-        argv.push( "-e".to_string() );
-        argv.push( "PCM32".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.add_required_exactly_one_of( &[ "input", "device", "generate" ] );
+        opt_parse.add_required_one_of( &[ "output", "play" ] );
+        assert_eq!( opt_parse.get_required_groups()[1], ( vec![ "output".to_string(), "play".to_string() ], false ) );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options( false );
+        let is_success = opt_parse.parse_from_str( "-i in.wav -o out.wav -p", false );
         assert_eq!( is_success, true );
 
-        assert_eq!( opt_parse.get_value("-e"), "PCM32" );
-        assert_eq!( opt_parse.get_value("--encoding"), "PCM32" );
-        assert_eq!( opt_parse.get_value("-s"), "44100" );
-        assert_eq!( opt_parse.get_value("--samplingRate"), "44100" );
-        assert_eq!( opt_parse.get_value("-x"), "" );
-    }
+        let is_success = opt_parse.parse_from_str( "-p", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.format_error( &opt_parse.get_violations()[0] ), "missing one of input, device, generate" );
 
+        let is_success = opt_parse.parse_from_str( "-d hw:0 -g", false );
+        assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_violations(), &[
+            OptParseError::ConflictingOptions { name : "device".to_string(), other : "generate".to_string() },
+            OptParseError::MissingOneOf { names : vec![ "output".to_string(), "play".to_string() ] },
+        ] );
+    }
 
     #[test]
-    fn test_opt_parse_with_args_fixed() {
+    fn test_opt_parse_colon_value_separator() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") );
+        options.push( OptParseItem::new( "", "--encoding", true, "PCM16", "Set encoding") );
+        options.push( OptParseItem::new( "-l", "--level", true, "0", "Set level") );
+        options.push( OptParseItem::new( "-d", "--device", true, "", "Set device") );
+        options.push( OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages") );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "mycommands".to_string() );
-        argv.push( "input.csv".to_string() );
-        argv.push( "-e".to_string() );
-        argv.push( "PCM32".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "--level:5", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "level" ), "0" );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options_with_required_args( false, 1, 1 ); // ensure argument e.g. input.csv
-        assert_eq!( opt_parse.get_args_count(), 2 ); // 0:mycommand, 1:input.csv
+        opt_parse.set_colon_value_separator( true );
+        let is_success = opt_parse.parse_from_str( "-s:44100 --level:5 --device:hw:0 --encoding=PCM:24 -v:false input.wav", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "level" ), "5" );
+        assert_eq!( opt_parse.get_value( "device" ), "hw:0" );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM:24" );
+        assert_eq!( opt_parse.get_value( "verbose" ), "false" );
+        assert_eq!( opt_parse.get_args( 0 ), "input.wav" );
+
+        opt_parse.set_single_dash_long_option( true );
+        let is_success = opt_parse.parse_from_str( "-encoding:PCM24 -level:3 -l 4", false );
         assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_value( "encoding" ), "PCM24" );
+        assert_eq!( opt_parse.get_value( "level" ), "4" );
     }
 
     #[test]
-    fn test_opt_parse_with_args_max_specified() {
+    fn test_opt_parse_custom_parser() {
+        #[derive(Debug, PartialEq)]
+        struct Ratio( u32, u32 );
+        fn parse_ratio( value : &str ) -> Result<Ratio, String> {
+            let ( width, height ) = value.split_once( ':' ).ok_or_else( || "not a ratio e.g. 16:9".to_string() )?;
+            match ( width.parse(), height.parse() ) {
+                ( Ok( width ), Ok( height ) ) if height != 0 => Ok( Ratio( width, height ) ),
+                _ => Err( "not a ratio e.g. 16:9".to_string() ),
+            }
+        }
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-a", "--aspect", true, "16:9", "Aspect ratio").with_parser( parse_ratio ) );
+        options.push( OptParseItem::new( "-c", "--channels", true, "2.0", "Channel layout").with_parser( |value| value.split( '.' ).map( |count| count.parse::<u8>() ).collect::<Result<Vec<_>, _>>().map_err( |_| "not a layout e.g. 5.1.2".to_string() ) ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "mycommands".to_string() );
-        argv.push( "input1.csv".to_string() );
-        argv.push( "input2.csv".to_string() );
-        argv.push( "input3.csv".to_string() );
-        argv.push( "-e".to_string() );
-        argv.push( "PCM32".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        let is_success = opt_parse.parse_from_str( "-c 5.1.2", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_parsed::<Ratio>( "aspect" ), Some( &Ratio( 16, 9 ) ) );
+        assert_eq!( opt_parse.get_parsed::<Vec<u8>>( "channels" ), Some( &vec![ 5, 1, 2 ] ) );
+        assert_eq!( opt_parse.get_parsed::<String>( "channels" ), None );
+        assert_eq!( opt_parse.get_value( "channels" ), "5.1.2" );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options_with_required_args( false, 1, 2 ); // ensure argument e.g. input.csv
+        let is_success = opt_parse.parse_from_str( "-a 4:0", false );
         assert_eq!( is_success, false );
+        assert_eq!( opt_parse.get_error(), Some( &OptParseError::InvalidValue { name : "aspect".to_string(), value : "4:0".to_string(), message : "not a ratio e.g. 16:9".into() } ) );
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn test_opt_parse_with_args_more_than_min() {
+    fn test_opt_parse_parse() {
+        let options = vec![
+            OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate").with_value_type( ValueType::Integer ),
+            OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose messages"),
+        ];
+        let matches = parse_args_or_exit( [ "-s", "44100", "in.wav" ].map( String::from ), options, "rst_opt_parse_test", Some( "mytool 1.0" ) );
+        assert_eq!( matches.get_int( "samplingRate" ), Some( 44100 ) );
+        assert_eq!( matches.get_value( "verbose" ), "false" );
+        assert_eq!( matches.get_value( "version" ), "false" );
+        assert_eq!( matches.get_args( 0 ), "in.wav" );
+    }
+
+    #[test]
+    fn test_opt_parse_option_help() {
         let mut options = Vec::new();
-        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100") );
+        options.push( OptParseItem::new( "-s", "--samplingRate", true, "48kHz", "Set sampling rate").with_value_type( ValueType::Unit( units::UnitTable::new( &[ ( "Hz", 1.0 ), ( "kHz", 1000.0 ) ] ) ) ).with_long_description( "The rate of the output" ).with_examples( &[ "mytool -s 44.1kHz in.wav" ] ) );
+        options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM16", "PCM24" ] ) );
+        options.push( OptParseItem::new( "", "--quiet", false, "false", "Quiet").with_conflicts( &[ "verbose" ] ) );
+        options.push( OptParseItem::env_only( "apiToken", "secret", "API token" ).with_secret( true ) );
 
-        let mut argv : Vec<String> = Vec::new();
-        argv.push( "mycommands".to_string() );
-        argv.push( "input1.csv".to_string() );
-        argv.push( "input2.csv".to_string() );
-        argv.push( "input3.csv".to_string() );
-        argv.push( "-e".to_string() );
-        argv.push( "PCM32".to_string() );
-        argv.push( "--samplingRate=44100".to_string() );
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        opt_parse.set_env_prefix( "mytool" );
+        assert_eq!( opt_parse.get_option_help( "-s" ), Some( "-s, --samplingRate <VALUE>\n  Set sampling rate\n  The rate of the output\n  Default: 48kHz\n  Units: Hz/kHz\n  Examples:\n    mytool -s 44.1kHz in.wav\n".to_string() ) );
+        assert_eq!( opt_parse.get_option_help( "encoding" ), Some( "-e, --encoding <VALUE>\n  Set encoding\n  Default: PCM16\n  Possible values: PCM16, PCM24\n".to_string() ) );
+        assert_eq!( opt_parse.get_option_help( "--quiet" ), Some( "--quiet\n  Quiet\n  Conflicts with: verbose\n".to_string() ) );
+        assert_eq!( opt_parse.get_option_help( "apiToken" ), Some( "MYTOOL_API_TOKEN\n  API token\n  Default: ********\n".to_string() ) );
+        assert_eq!( opt_parse.get_option_help( "unknown" ), None );
 
-        let mut opt_parse = OptParse::new( argv, options, "rst_opt_parse_test" );
-        let is_success = opt_parse.parse_options_with_required_args( false, 1, -1 ); // ensure argument e.g. input.csv
+        let is_success = opt_parse.parse_from_str( "--help samplingRate", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.is_help_requested(), true );
+        assert_eq!( opt_parse.get_matches().get_help_topic(), Some( "samplingRate" ) );
+
+        let is_success = opt_parse.parse_from_str( "--help=-e", false );
+        assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_matches().get_help_topic(), Some( "-e" ) );
+
+        let is_success = opt_parse.parse_from_str( "--help in.wav", false );
         assert_eq!( is_success, true );
+        assert_eq!( opt_parse.get_matches().get_help_topic(), None );
+        assert_eq!( opt_parse.get_args( 0 ), "in.wav" );
     }
 }
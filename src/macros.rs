@@ -0,0 +1,68 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// declares the enum with its value spellings and implements ValueEnum, FromStr and Display e.g.
+//   value_enum! {
+//       #[derive(Clone, Copy, Debug, PartialEq)]
+//       pub enum Encoding { Pcm8 = "PCM8", Pcm16 = "PCM16" }
+//   }
+#[macro_export]
+macro_rules! value_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $( $variant:ident = $value:literal ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $variant ),*
+        }
+
+        impl $crate::ValueEnum for $name {
+            fn possible_values() -> &'static [&'static str] {
+                &[ $( $value ),* ]
+            }
+
+            fn from_value( value : &str ) -> Option<Self> {
+                match value {
+                    $( $value => Some( $name::$variant ), )*
+                    _ => None
+                }
+            }
+
+            fn as_value( &self ) -> &'static str {
+                match self {
+                    $( $name::$variant => $value ),*
+                }
+            }
+        }
+
+        impl core::str::FromStr for $name {
+            type Err = $crate::__String;
+
+            fn from_str( value : &str ) -> Result<Self, Self::Err> {
+                <$name as $crate::ValueEnum>::parse_value( value )
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+                f.write_str( $crate::ValueEnum::as_value( self ) )
+            }
+        }
+    };
+}
@@ -20,10 +20,11 @@ use rst_opt_parse::OptParse;
 use rst_opt_parse::OptParseItem;
 
 fn main() {
-    let mut options = Vec::new();
-    options.push( OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set Sampling Rate") );
-    options.push( OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT") );
-    options.push( OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1") );
+    let options = vec![
+        OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set Sampling Rate"),
+        OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding PCM8, PCM16, PCM24, PCM32, PCMFLOAT"),
+        OptParseItem::new( "-c", "--channel", true, "2", "Set channel 2, 2.1, 4, 4.1, 5, 5.1, 5.1.2, 7.1"),
+    ];
 
     let argv: Vec<String> = env::args().collect();
 
@@ -0,0 +1,40 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the common types for the glob import e.g.
+//   use rst_opt_parse::prelude::*;
+//   let matches = parse( vec![ OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate") ], "mytool" );
+
+pub use crate::Command;
+pub use crate::FromOptParse;
+pub use crate::IOptParse;
+pub use crate::Matches;
+pub use crate::OptParse;
+pub use crate::OptParseError;
+pub use crate::OptParseItem;
+pub use crate::OptionGroup;
+pub use crate::TypedValue;
+pub use crate::ValueEnum;
+pub use crate::ValueSource;
+pub use crate::ValueType;
+#[cfg(feature = "std")]
+pub use crate::parse;
+#[cfg(feature = "std")]
+pub use crate::parse_with_version;
+#[cfg(feature = "std")]
+pub use crate::run_main;
+#[cfg(feature = "derive")]
+pub use crate::value_enum;
@@ -0,0 +1,68 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the options contributed by the plugins e.g. a codec plugin adds its --bitrate to the host's parser and help
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::Matches;
+use crate::OptParseError;
+use crate::OptParseItem;
+
+pub trait OptionProvider
+{
+    fn name( &self ) -> &str;
+
+    // added after the host's options
+    fn options( &self ) -> Vec<OptParseItem>;
+
+    // run after the successful parse e.g. to configure the plugin. the parse fails on the error
+    fn after_parse( &self, _matches : &Matches ) -> Result<(), OptParseError> {
+        Ok( () )
+    }
+}
+
+
+// the registered providers in the order of add_provider()
+#[derive(Clone, Default)]
+pub(crate) struct ProviderRegistry
+{
+    pub(crate) providers : Vec<Arc<dyn OptionProvider + Send + Sync>>,
+}
+
+impl ProviderRegistry
+{
+    pub(crate) fn names( &self ) -> Vec<String> {
+        self.providers.iter().map( |provider| String::from( provider.name() ) ).collect()
+    }
+}
+
+impl core::fmt::Debug for ProviderRegistry
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.debug_list().entries( self.providers.iter().map( |provider| provider.name() ) ).finish()
+    }
+}
+
+// the providers can't be compared then the same names are the same
+impl PartialEq for ProviderRegistry
+{
+    fn eq( &self, other : &Self ) -> bool {
+        self.names() == other.names()
+    }
+}
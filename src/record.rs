@@ -0,0 +1,206 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the record of the invocation to rerun it later e.g. "rerun the last job". the file is the lines of the shell style words :
+//   timestamp 1697000000
+//   arg --samplingRate=44100
+//   arg 'my file.pcm'
+//   value samplingRate CommandLine 44100
+// the secret options are left out of the args and their values are masked, then those aren't replayed
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::shell;
+use crate::diff_values;
+use crate::IOptParse;
+use crate::Matches;
+use crate::OptParse;
+use crate::OptParseError;
+use crate::SecretArgs;
+use crate::ValueDiff;
+use crate::ValueSource;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Invocation
+{
+    pub timestamp : u64,                                // seconds since the UNIX epoch
+    pub args : Vec<String>,                             // the args giving the same results, see to_args()
+    pub values : Vec<( String, String, ValueSource )>,  // ( name, value, source ) resolved at the time
+}
+
+impl Invocation
+{
+    pub fn from_opt_parse( opt_parse : &OptParse ) -> Self {
+        Self {
+            timestamp : SystemTime::now().duration_since( UNIX_EPOCH ).map( |duration| duration.as_secs() ).unwrap_or( 0 ),
+            args : opt_parse.to_args_with( SecretArgs::Excluded ),
+            values : opt_parse.get_matches().entries.iter().map( |entry| ( entry.name.clone(), entry.display_value().to_string(), entry.source.clone() ) ).collect(),
+        }
+    }
+
+    pub fn to_text( &self ) -> String {
+        let mut result = format!( "timestamp {}\n", self.timestamp );
+        for arg in &self.args {
+            result.push_str( &shell::join( &[ "arg", arg ] ) );
+            result.push( '\n' );
+        }
+        for ( name, value, source ) in &self.values {
+            result.push_str( &shell::join( &[ "value", name, source_name( source ), value ] ) );
+            result.push( '\n' );
+        }
+        result
+    }
+
+    pub fn from_text( text : &str ) -> Result<Self, OptParseError> {
+        let mut result = Self::default();
+        let mut record = String::new();
+        let mut line = 0;
+        for ( i, text_line ) in text.lines().enumerate() {
+            if record.is_empty() {
+                line = i + 1;
+            }
+            record.push_str( text_line );
+            // the quoted value can continue to the next line
+            let words = match shell::split( &record ) {
+                Ok( words ) => words,
+                Err( OptParseError::UnterminatedQuote ) => { record.push( '\n' ); continue; },
+                Err( err ) => return Err( err ),
+            };
+            record.clear();
+            let error = |message : &str| OptParseError::InvalidRecord { line, message : message.to_string() };
+
+            match words.iter().map( |word| word.as_str() ).collect::<Vec<_>>().as_slice() {
+                [] => {},
+                [ "timestamp", timestamp ] => result.timestamp = timestamp.parse().map_err( |_| error( "invalid timestamp" ) )?,
+                [ "arg", arg ] => result.args.push( arg.to_string() ),
+                [ "value", name, source, value ] => {
+                    let source = parse_source( source ).ok_or_else( || error( "unknown source" ) )?;
+                    result.values.push( ( name.to_string(), value.to_string(), source ) );
+                },
+                _ => return Err( error( "unknown record" ) ),
+            }
+        }
+        if !record.is_empty() {
+            return Err( OptParseError::UnterminatedQuote );
+        }
+        Ok( result )
+    }
+
+    pub fn save<P : AsRef<Path>>( &self, path : P ) -> std::io::Result<()> {
+        std::fs::write( path, self.to_text() )
+    }
+
+    pub fn load<P : AsRef<Path>>( path : P ) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string( path )?;
+        Self::from_text( &text ).map_err( |err| std::io::Error::new( std::io::ErrorKind::InvalidData, err ) )
+    }
+
+    // the values changed from this recorded baseline to the results e.g. of this run. the secrets are compared as masked
+    pub fn diff( &self, matches : &Matches ) -> Vec<ValueDiff> {
+        diff_values( self.values.iter().map( |( name, value, source )| ( name.as_str(), value.as_str(), source ) ), matches.iter_display_values() )
+    }
+
+    // parse the recorded args as if they were given in the command line
+    pub fn replay( &self, opt_parse : &mut OptParse ) -> bool {
+        opt_parse.parse_options_from_iter( self.args.clone(), false )
+    }
+}
+
+fn source_name( source : &ValueSource ) -> &'static str {
+    match source {
+        ValueSource::Default => "Default",
+        ValueSource::CommandLine => "CommandLine",
+        ValueSource::Prompt => "Prompt",
+        ValueSource::Derived => "Derived",
+        ValueSource::Profile => "Profile",
+        ValueSource::Environment => "Environment",
+        ValueSource::Config => "Config",
+    }
+}
+
+fn parse_source( name : &str ) -> Option<ValueSource> {
+    match name {
+        "Default" => Some( ValueSource::Default ),
+        "CommandLine" => Some( ValueSource::CommandLine ),
+        "Prompt" => Some( ValueSource::Prompt ),
+        "Derived" => Some( ValueSource::Derived ),
+        "Profile" => Some( ValueSource::Profile ),
+        "Environment" => Some( ValueSource::Environment ),
+        "Config" => Some( ValueSource::Config ),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::OptParseItem;
+
+    #[test]
+    fn test_invocation_record_replay() {
+        let options = vec![
+            OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100"),
+            OptParseItem::new( "-o", "--output", true, "", "Set output file"),
+        ];
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_from_str( "-o 'two\nlines.wav' 'my file.pcm'", false ), true );
+
+        let mut invocation = Invocation::from_opt_parse( &opt_parse );
+        invocation.timestamp = 1697000000;
+        assert_eq!( invocation.to_text(), "timestamp 1697000000\narg '--output=two\nlines.wav'\narg 'my file.pcm'\nvalue samplingRate Default 48000\nvalue output CommandLine 'two\nlines.wav'\n" );
+
+        let path = std::env::temp_dir().join( format!( "rst_opt_parse_invocation_{}.txt", std::process::id() ) );
+        invocation.save( &path ).unwrap();
+        let loaded = Invocation::load( &path ).unwrap();
+        let _ = std::fs::remove_file( &path );
+        assert_eq!( loaded, invocation );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        assert_eq!( loaded.replay( &mut opt_parse ), true );
+        assert_eq!( opt_parse.get_value( "output" ), "two\nlines.wav" );
+        assert_eq!( opt_parse.get_args( 0 ), "my file.pcm" );
+        assert_eq!( loaded.diff( opt_parse.get_matches() ), Vec::new() );
+
+        assert_eq!( opt_parse.parse_from_str( "-s 44100 -o out.wav", false ), true );
+        assert_eq!( loaded.diff( opt_parse.get_matches() ).iter().map( |diff| diff.to_string() ).collect::<Vec<_>>(), vec![
+            "samplingRate: 48000 (default) -> 44100 (command line)".to_string(),
+            "output: 'two\nlines.wav' (command line) -> out.wav (command line)".to_string(),
+        ] );
+    }
+
+    #[test]
+    fn test_invocation_replay_secret() {
+        let options = vec![
+            OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100"),
+            OptParseItem::new( "-t", "--token", true, "", "API token").with_secret( true ),
+        ];
+        let mut opt_parse = OptParse::new( Vec::new(), options.clone(), "rst_opt_parse_test" );
+        assert_eq!( opt_parse.parse_from_str( "-s 44100 --token=abc", false ), true );
+
+        let invocation = Invocation::from_opt_parse( &opt_parse );
+        assert_eq!( invocation.args, [ "--samplingRate=44100" ] );
+        assert_eq!( invocation.to_text().contains( "abc" ), false );
+        assert_eq!( invocation.values[1], ( "token".to_string(), "********".to_string(), ValueSource::CommandLine ) );
+
+        let mut opt_parse = OptParse::new( Vec::new(), options, "rst_opt_parse_test" );
+        assert_eq!( invocation.replay( &mut opt_parse ), true );
+        assert_eq!( opt_parse.get_value( "samplingRate" ), "44100" );
+        assert_eq!( opt_parse.get_value( "token" ), "" );
+    }
+}
@@ -0,0 +1,120 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the shell (POSIX sh) style words e.g. convert -s 44100 "my file.pcm"
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::OptParseError;
+
+// split the line to the words as sh does with the quotes and the backslash escapes. no expansion is done
+pub fn split( line : &str ) -> Result<Vec<String>, OptParseError> {
+    let mut words = Vec::new();
+    let mut word : Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some( c ) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some( word ) = word.take() {
+                    words.push( word );
+                }
+            },
+            '\'' => {
+                let word = word.get_or_insert_with( String::new );
+                loop {
+                    match chars.next() {
+                        Some( '\'' ) => break,
+                        Some( c ) => word.push( c ),
+                        None => return Err( OptParseError::UnterminatedQuote ),
+                    }
+                }
+            },
+            '"' => {
+                let word = word.get_or_insert_with( String::new );
+                loop {
+                    match chars.next() {
+                        Some( '"' ) => break,
+                        Some( '\\' ) => match chars.next() {
+                            Some( c @ ( '"' | '\\' | '$' | '`' ) ) => word.push( c ),
+                            Some( '\n' ) => {},
+                            Some( c ) => { word.push( '\\' ); word.push( c ); },
+                            None => return Err( OptParseError::UnterminatedQuote ),
+                        },
+                        Some( c ) => word.push( c ),
+                        None => return Err( OptParseError::UnterminatedQuote ),
+                    }
+                }
+            },
+            '\\' => match chars.next() {
+                Some( '\n' ) => {},
+                Some( c ) => word.get_or_insert_with( String::new ).push( c ),
+                None => word.get_or_insert_with( String::new ).push( '\\' ),
+            },
+            c => word.get_or_insert_with( String::new ).push( c ),
+        }
+    }
+    if let Some( word ) = word {
+        words.push( word );
+    }
+    Ok( words )
+}
+
+// quote the word for sh when needed e.g. "my file.pcm" -> 'my file.pcm'
+pub fn quote( word : &str ) -> String {
+    let is_safe = |c : char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains( c );
+    if !word.is_empty() && word.chars().all( is_safe ) {
+        return String::from( word );
+    }
+    let mut result = String::from( "'" );
+    for c in word.chars() {
+        if c == '\'' {
+            result.push_str( "'\\''" );
+        } else {
+            result.push( c );
+        }
+    }
+    result.push( '\'' );
+    result
+}
+
+// the words quoted and joined by the space, the inverse of split()
+pub fn join<S : AsRef<str>>( words : &[S] ) -> String {
+    words.iter().map( |word| quote( word.as_ref() ) ).collect::<Vec<_>>().join( " " )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_split() {
+        assert_eq!( split( "convert -s 44100 \"my file.pcm\"" ), Ok( vec![ "convert".to_string(), "-s".to_string(), "44100".to_string(), "my file.pcm".to_string() ] ) );
+        assert_eq!( split( "  a\\ b 'c \"d\"' \"e \\\"f\\\"\" '' -x=\"1 2\"" ), Ok( vec![ "a b".to_string(), "c \"d\"".to_string(), "e \"f\"".to_string(), "".to_string(), "-x=1 2".to_string() ] ) );
+        assert_eq!( split( "a 'b" ), Err( OptParseError::UnterminatedQuote ) );
+    }
+
+    #[test]
+    fn test_join() {
+        let words = vec![ "convert".to_string(), "--samplingRate=44100".to_string(), "my file.pcm".to_string(), "it's".to_string(), "".to_string() ];
+        assert_eq!( join( &words ), "convert --samplingRate=44100 'my file.pcm' 'it'\\''s' ''" );
+        assert_eq!( split( &join( &words ) ), Ok( words ) );
+    }
+}
@@ -0,0 +1,462 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the option specification declared in a TOML document e.g.
+//
+//   description = "audio converter"
+//
+//   [[option]]
+//   short = "-s"
+//   long = "--samplingRate"
+//   arg_required = true
+//   default = "48000"
+//   help = "Set sampling rate e.g. 44100"
+//
+// flags = "resample, dither" declares the bitmask option, see OptParseItem::with_flags(). the bits are assigned in the order from 0x1
+// choices = "PCM8, PCM16" restricts the value, see OptParseItem::with_choices(). both can also be the arrays e.g. choices = [ "PCM8", "PCM16" ]
+//
+// the subcommands are declared by [[command]] with name, description and category, and their options by [[command.option]]
+//
+//   [[command]]
+//   name = "convert"
+//   description = "convert the file"
+//
+//   [[command.option]]
+//   long = "--encoding"
+//   arg_required = true
+//   choices = "PCM8, PCM16"
+//
+// only TOML is supported, not YAML. and only the subset needed for the spec : comments, key = value with strings, booleans, numbers
+// and the one line arrays of them, and [[option]], [[command]] and [[command.option]] tables
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::Command;
+use crate::IOptParse;
+use crate::Matches;
+use crate::OptParse;
+use crate::OptParseError;
+use crate::OptParseItem;
+use crate::strip_comment;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spec
+{
+    pub description : String,
+    pub options : Vec<OptParseItem>,
+    pub commands : Vec<SpecCommand>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpecCommand
+{
+    pub name : String,
+    pub description : String,
+    pub category : String,
+    pub options : Vec<OptParseItem>,
+}
+
+impl Spec
+{
+    pub fn from_toml( doc : &str ) -> Result<Self, OptParseError> {
+        let mut spec = Spec::default();
+        let mut current : Option<( usize, SpecOption )> = None;
+        let mut is_command = false;     // true: the keys and the [[command.option]] are of the last [[command]]
+        let mut command_lines = Vec::new();
+
+        for ( i, raw_line ) in doc.lines().enumerate() {
+            let line_no = i + 1;
+            let line = strip_comment( raw_line ).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message : &str| OptParseError::InvalidSpec { line : line_no, message : message.to_string() };
+
+            if line.starts_with( '[' ) {
+                if let Some( ( start, option ) ) = current.take() {
+                    spec.push_option( option.into_item( start )?, is_command );
+                }
+                match line {
+                    "[[option]]" => {
+                        is_command = false;
+                        current = Some( ( line_no, SpecOption::default() ) );
+                    },
+                    "[[command]]" => {
+                        is_command = true;
+                        spec.commands.push( SpecCommand::default() );
+                        command_lines.push( line_no );
+                    },
+                    "[[command.option]]" if is_command => current = Some( ( line_no, SpecOption::default() ) ),
+                    "[[command.option]]" => return Err( error( "[[command.option]] needs [[command]]" ) ),
+                    _ => return Err( error( &format!( "unknown table {}", line ) ) ),
+                }
+                continue;
+            }
+
+            let ( key, value ) = line.split_once( '=' ).ok_or_else( || error( "expected key = value" ) )?;
+            let key = key.trim();
+            let value = parse_value( value.trim() ).ok_or_else( || error( &format!( "invalid value for {}", key ) ) )?;
+
+            match ( current.as_mut(), spec.commands.last_mut().filter( |_| is_command ) ) {
+                ( None, None ) => match key {
+                    "description" => spec.description = value.into_string(),
+                    _ => return Err( error( &format!( "unknown key {}", key ) ) ),
+                },
+                ( None, Some( command ) ) => match key {
+                    "name" => command.name = value.into_string(),
+                    "description" => command.description = value.into_string(),
+                    "category" => command.category = value.into_string(),
+                    _ => return Err( error( &format!( "unknown key {}", key ) ) ),
+                },
+                ( Some( ( _, option ) ), _ ) => match ( key, value ) {
+                    ( "name", value ) => option.name = Some( value.into_string() ),
+                    ( "short", value ) => option.short = value.into_string(),
+                    ( "long", value ) => option.long = value.into_string(),
+                    ( "arg_required", Value::Bool( value ) ) => option.arg_required = value,
+                    ( "default", value ) => option.default = Some( value.into_string() ),
+                    ( "help", value ) => option.help = value.into_string(),
+                    ( "flags", value ) => option.flags = value.into_list(),
+                    ( "choices", value ) => option.choices = value.into_list(),
+                    ( "arg_required", _ ) => return Err( error( "arg_required must be true or false" ) ),
+                    ( key, _ ) => return Err( error( &format!( "unknown key {}", key ) ) ),
+                },
+            }
+        }
+        if let Some( ( start, option ) ) = current.take() {
+            spec.push_option( option.into_item( start )?, is_command );
+        }
+        if let Some( position ) = spec.commands.iter().position( |command| command.name.is_empty() ) {
+            return Err( OptParseError::InvalidSpec { line : command_lines[ position ], message : "command needs name".to_string() } );
+        }
+        Ok( spec )
+    }
+
+    // the commands aren't registered as they can't run without the Rust side, see into_opt_parse_with_commands()
+    pub fn into_opt_parse( self, args : Vec<String> ) -> OptParse {
+        OptParse::new( args, self.options, &self.description )
+    }
+
+    // run( the command name, its matches ) runs all of the declared commands e.g. by run_command()
+    pub fn into_opt_parse_with_commands<F>( self, args : Vec<String>, run : F ) -> OptParse
+        where F : Fn( &str, &Matches ) -> Result<i32, OptParseError> + Send + Sync + 'static
+    {
+        let mut opt_parse = OptParse::new( args, self.options, &self.description );
+        let run = Arc::new( run );
+        for command in self.commands {
+            opt_parse.add_command( SpecRunner { command, run : run.clone() } );
+        }
+        opt_parse
+    }
+
+    fn push_option( &mut self, option : OptParseItem, is_command : bool ) {
+        match self.commands.last_mut().filter( |_| is_command ) {
+            Some( command ) => command.options.push( option ),
+            None => self.options.push( option ),
+        }
+    }
+}
+
+
+// the declared command running the closure of into_opt_parse_with_commands()
+struct SpecRunner<F>
+{
+    command : SpecCommand,
+    run : Arc<F>,
+}
+
+impl<F> Command for SpecRunner<F>
+    where F : Fn( &str, &Matches ) -> Result<i32, OptParseError> + Send + Sync + 'static
+{
+    fn name( &self ) -> &str {
+        &self.command.name
+    }
+
+    fn description( &self ) -> &str {
+        &self.command.description
+    }
+
+    fn category( &self ) -> &str {
+        &self.command.category
+    }
+
+    fn options( &self ) -> Vec<OptParseItem> {
+        self.command.options.clone()
+    }
+
+    fn run( &self, matches : &Matches ) -> Result<i32, OptParseError> {
+        ( self.run )( &self.command.name, matches )
+    }
+}
+
+
+#[derive(Default)]
+struct SpecOption
+{
+    name : Option<String>,
+    short : String,
+    long : String,
+    arg_required : bool,
+    default : Option<String>,
+    help : String,
+    flags : Vec<String>,
+    choices : Vec<String>,
+}
+
+impl SpecOption
+{
+    fn into_item( self, line : usize ) -> Result<OptParseItem, OptParseError> {
+        if self.short.is_empty() && self.long.is_empty() {
+            return Err( OptParseError::InvalidSpec { line, message : "option needs short or long".to_string() } );
+        }
+        // the flag's default is "false" as the parser sets "true" when it's specified
+        let default = self.default.unwrap_or_else( || if self.arg_required { String::new() } else { "false".to_string() } );
+        let mut item = OptParseItem::new( &self.short, &self.long, self.arg_required, &default, &self.help );
+        if !self.flags.is_empty() {
+            if self.flags.len() > 64 {
+                return Err( OptParseError::InvalidSpec { line, message : "too many flags".to_string() } );
+            }
+            let flags : Vec<( &str, u64 )> = self.flags.iter().enumerate().map( |( i, name )| ( name.as_str(), 1u64 << i ) ).collect();
+            item = item.with_flags( &flags );
+        }
+        if !self.choices.is_empty() {
+            let choices : Vec<&str> = self.choices.iter().map( |choice| choice.as_str() ).collect();
+            item = item.with_choices( &choices );
+        }
+        Ok( match self.name {
+            Some( name ) => item.with_name( &name ),
+            None => item,
+        } )
+    }
+}
+
+
+enum Value
+{
+    Str( String ),
+    Bool( bool ),
+    Number( String ),
+    Array( Vec<String> ),
+}
+
+impl Value
+{
+    fn into_string( self ) -> String {
+        match self {
+            Value::Str( value ) | Value::Number( value ) => value,
+            Value::Bool( value ) => value.to_string(),
+            Value::Array( values ) => values.join( ", " ),
+        }
+    }
+
+    // "resample, dither" or [ "resample", "dither" ] -> [ resample, dither ]
+    fn into_list( self ) -> Vec<String> {
+        match self {
+            Value::Array( values ) => values,
+            value => value.into_string().split( ',' ).map( |name| name.trim().to_string() ).filter( |name| !name.is_empty() ).collect(),
+        }
+    }
+}
+
+// the items split by the commas outside of the quotes e.g. "PCM8", 'PCM16', -> [ "PCM8", 'PCM16' ]. None for the unterminated quote or the empty item
+fn split_array( array : &str ) -> Option<Vec<&str>> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut is_escaped = false;
+    for ( i, c ) in array.char_indices() {
+        match ( quote, c ) {
+            ( Some( '"' ), '\\' ) if !is_escaped => {
+                is_escaped = true;
+                continue;
+            },
+            ( Some( open ), c ) if c == open && !is_escaped => quote = None,
+            ( None, '"' | '\'' ) => quote = Some( c ),
+            ( None, ',' ) => {
+                items.push( array[ start..i ].trim() );
+                start = i + 1;
+            },
+            _ => {},
+        }
+        is_escaped = false;
+    }
+    if quote.is_some() {
+        return None;
+    }
+    // the trailing comma is allowed
+    let last = array[ start.. ].trim();
+    if !last.is_empty() {
+        items.push( last );
+    }
+    ( !items.iter().any( |item| item.is_empty() ) ).then_some( items )
+}
+
+fn parse_value( value : &str ) -> Option<Value> {
+    if let Some( array ) = value.strip_prefix( '[' ) {
+        let items = split_array( array.strip_suffix( ']' )? )?;
+        return items.into_iter().map( |item| match parse_value( item )? {
+            Value::Array( _ ) => None,
+            value => Some( value.into_string() ),
+        }).collect::<Option<Vec<String>>>().map( Value::Array );
+    }
+    if let Some( literal ) = value.strip_prefix( '\'' ) {
+        return literal.strip_suffix( '\'' ).filter( |s| !s.contains( '\'' ) ).map( |s| Value::Str( s.to_string() ) );
+    }
+    if let Some( basic ) = value.strip_prefix( '"' ) {
+        let basic = basic.strip_suffix( '"' )?;
+        let mut result = String::new();
+        let mut chars = basic.chars();
+        while let Some( c ) = chars.next() {
+            match c {
+                '\\' => result.push( match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '"' => '"',
+                    '\\' => '\\',
+                    _ => return None,
+                } ),
+                '"' => return None,
+                c => result.push( c ),
+            }
+        }
+        return Some( Value::Str( result ) );
+    }
+    match value {
+        "true" => Some( Value::Bool( true ) ),
+        "false" => Some( Value::Bool( false ) ),
+        _ if value.parse::<f64>().is_ok() => Some( Value::Number( value.to_string() ) ),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::vec_init_then_push, clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_spec_from_toml() {
+        let doc = r#"
+description = "rst_opt_parse_test" # the help header
+
+[[option]]
+short = "-s"
+long = "--samplingRate"
+name = "sampling_rate"
+arg_required = true
+default = 48000
+help = "Set sampling rate e.g. 44100"
+
+[[option]]
+long = '--verbose'
+help = "Enable \"verbose\" mode # not a comment"
+
+[[option]]
+long = "--feature"
+arg_required = true
+flags = "resample, dither"
+help = "Enable the feature"
+
+[[option]]
+long = "--encoding"
+arg_required = true
+choices = [ "PCM8", 'PCM16', "PCM\"24\", 32", ] # the trailing comma
+flags = []
+"#;
+        let spec = Spec::from_toml( doc ).unwrap();
+        assert_eq!( spec.description, "rst_opt_parse_test" );
+        assert_eq!( spec.options, vec![
+            OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100").with_name( "sampling_rate" ),
+            OptParseItem::new( "", "--verbose", false, "false", "Enable \"verbose\" mode # not a comment"),
+            OptParseItem::new( "", "--feature", true, "", "Enable the feature").with_flags( &[ ( "resample", 0x1 ), ( "dither", 0x2 ) ] ),
+            OptParseItem::new( "", "--encoding", true, "", "").with_choices( &[ "PCM8", "PCM16", "PCM\"24\", 32" ] ),
+        ] );
+
+        let mut argv : Vec<String> = Vec::new();
+        argv.push( "-s".to_string() );
+        argv.push( "44100".to_string() );
+        argv.push( "--feature=dither".to_string() );
+        let mut opt_parse = spec.into_opt_parse( argv );
+        assert_eq!( opt_parse.parse_options( false ), true );
+        assert_eq!( opt_parse.get_value( "sampling_rate" ), "44100" );
+        assert_eq!( opt_parse.get_flags( "feature" ), 0x2 );
+    }
+
+    #[test]
+    fn test_spec_from_toml_command() {
+        let doc = r#"
+description = "rst_opt_parse_test"
+
+[[option]]
+long = "--verbose"
+
+[[command]]
+name = "convert"
+description = "convert the file"
+
+[[command.option]]
+short = "-e"
+long = "--encoding"
+arg_required = true
+default = "PCM16"
+choices = "PCM8, PCM16, PCM24"
+help = "Set encoding"
+
+[[command]]
+name = "info"
+category = "Inspection"
+"#;
+        let spec = Spec::from_toml( doc ).unwrap();
+        assert_eq!( spec.options, vec![ OptParseItem::new( "", "--verbose", false, "false", "" ) ] );
+        assert_eq!( spec.commands, vec![
+            SpecCommand {
+                name : "convert".to_string(),
+                description : "convert the file".to_string(),
+                category : String::new(),
+                options : vec![ OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set encoding").with_choices( &[ "PCM8", "PCM16", "PCM24" ] ) ],
+            },
+            SpecCommand { name : "info".to_string(), category : "Inspection".to_string(), ..SpecCommand::default() },
+        ] );
+
+        let argv = vec![ "mytool".to_string(), "--verbose".to_string(), "convert".to_string(), "-e".to_string(), "PCM24".to_string() ];
+        let mut opt_parse = spec.clone().into_opt_parse_with_commands( argv, |name, matches| {
+            assert_eq!( name, "convert" );
+            Ok( matches.get_value( "encoding" ).len() as i32 )
+        } );
+        assert_eq!( opt_parse.run_command( false ), Ok( 5 ) );
+
+        let argv = vec![ "mytool".to_string(), "convert".to_string(), "-e".to_string(), "PCM32".to_string() ];
+        let mut opt_parse = spec.into_opt_parse_with_commands( argv, |_, _| Ok( 0 ) );
+        assert_eq!( opt_parse.run_command( false ).is_err(), true );
+    }
+
+    #[test]
+    fn test_spec_from_toml_error() {
+        assert_eq!( Spec::from_toml( "[[option]]\nshort = \"-s\"\ncolor = true\n" ), Err( OptParseError::InvalidSpec { line : 3, message : "unknown key color".to_string() } ) );
+        assert_eq!( Spec::from_toml( "\n[[option]]\nhelp = \"no flag\"\n" ), Err( OptParseError::InvalidSpec { line : 2, message : "option needs short or long".to_string() } ) );
+        assert_eq!( Spec::from_toml( "[[command.option]]\nlong = \"--encoding\"\n" ), Err( OptParseError::InvalidSpec { line : 1, message : "[[command.option]] needs [[command]]".to_string() } ) );
+        assert_eq!( Spec::from_toml( "[[command]]\ndescription = \"no name\"\n" ), Err( OptParseError::InvalidSpec { line : 1, message : "command needs name".to_string() } ) );
+        assert_eq!( Spec::from_toml( "[[option]]\nlong = \"--encoding\"\nchoices = [ \"PCM8\", , \"PCM16\" ]\n" ), Err( OptParseError::InvalidSpec { line : 3, message : "invalid value for choices".to_string() } ) );
+        assert_eq!( Spec::from_toml( "[[option]]\nlong = \"--encoding\"\nchoices = [ \"PCM8, PCM16 ]\n" ), Err( OptParseError::InvalidSpec { line : 3, message : "invalid value for choices".to_string() } ) );
+        assert_eq!( Spec::from_toml( "[[option]]\nlong = \"--encoding\"\nchoices = [ [ \"PCM8\" ] ]\n" ), Err( OptParseError::InvalidSpec { line : 3, message : "invalid value for choices".to_string() } ) );
+    }
+}
@@ -0,0 +1,46 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// whether the standard streams are the terminals. the prompts need stdin, the colors need stdout or stderr
+
+use std::io::IsTerminal;
+
+pub fn is_stdin_terminal() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+pub fn is_stdout_terminal() -> bool {
+    std::io::stdout().is_terminal()
+}
+
+pub fn is_stderr_terminal() -> bool {
+    std::io::stderr().is_terminal()
+}
+
+// the columns of the terminal: COLUMNS if set, or the size of the terminal on stdout. None when unknown e.g. piped
+pub fn terminal_width() -> Option<usize> {
+    if let Some( columns ) = std::env::var( "COLUMNS" ).ok().and_then( |columns| columns.trim().parse::<usize>().ok() ).filter( |columns| *columns > 0 ) {
+        return Some( columns );
+    }
+    #[cfg(unix)]
+    if is_stdout_terminal() {
+        // "rows columns"
+        let tty = std::fs::File::open( "/dev/tty" ).ok()?;
+        let output = std::process::Command::new( "stty" ).arg( "size" ).stdin( tty ).output().ok()?;
+        return String::from_utf8_lossy( &output.stdout ).split_whitespace().nth( 1 ).and_then( |columns| columns.parse().ok() ).filter( |columns| *columns > 0 );
+    }
+    None
+}
@@ -0,0 +1,121 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the values with the unit suffixes e.g. --rate=44.1kHz, --timeout=1.5s or --buffer=4KiB, see ValueType::Unit
+// the value is converted to the base unit of the table e.g. 44.1kHz -> 44100.0 and get it by get_float()
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::MessageKey;
+use crate::ValueMessage;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitTable
+{
+    pub units : Vec<( String, f64 )>,   // the suffix and its factor to the base unit. the value without the suffix is in the base unit
+    pub range : Option<( f64, f64 )>,   // the min and the max in the base unit
+}
+
+impl UnitTable
+{
+    // e.g. UnitTable::new( &[ ( "Hz", 1.0 ), ( "kHz", 1000.0 ) ] )
+    pub fn new( units : &[( &str, f64 )] ) -> Self {
+        Self {
+            units : units.iter().map( |( suffix, factor )| ( suffix.to_string(), *factor ) ).collect(),
+            range : None,
+        }
+    }
+
+    // the values out of min..=max in the base unit fail the parse
+    pub fn with_range( mut self, min : f64, max : f64 ) -> Self {
+        self.range = Some( ( min, max ) );
+        self
+    }
+
+    // the seconds by ms, s, m, h and d e.g. 1.5s or 100ms
+    pub fn duration() -> Self {
+        Self::new( &[ ( "ms", 0.001 ), ( "s", 1.0 ), ( "m", 60.0 ), ( "h", 3600.0 ), ( "d", 86400.0 ) ] )
+    }
+
+    // the bytes by the SI and the binary prefixes e.g. 4KiB or 1.5GB
+    pub fn size() -> Self {
+        Self::new( &[ ( "B", 1.0 ),
+            ( "KB", 1e3 ), ( "MB", 1e6 ), ( "GB", 1e9 ), ( "TB", 1e12 ),
+            ( "KiB", 1024.0 ), ( "MiB", 1048576.0 ), ( "GiB", 1073741824.0 ), ( "TiB", 1099511627776.0 ) ] )
+    }
+
+    // the value in the base unit. the longest suffix matches first then the case insensitive one e.g. "44.1khz"
+    pub fn parse( &self, value : &str ) -> Result<f64, ValueMessage> {
+        let value = value.trim();
+        let mut units : Vec<&( String, f64 )> = self.units.iter().collect();
+        units.sort_by_key( |( suffix, _ )| core::cmp::Reverse( suffix.len() ) );
+        let suffix_of = |is_exact : bool| units.iter().find( |( suffix, _ )| {
+            value.len() > suffix.len() && value.is_char_boundary( value.len() - suffix.len() ) && {
+                let tail = &value[ value.len() - suffix.len()..];
+                if is_exact { tail == suffix } else { tail.eq_ignore_ascii_case( suffix ) }
+            }
+        });
+        let ( number, factor ) = match suffix_of( true ).or_else( || suffix_of( false ) ) {
+            Some( ( suffix, factor ) ) => ( &value[ ..value.len() - suffix.len()], *factor ),
+            None => ( value, 1.0 ),
+        };
+        let number : f64 = number.trim_end().parse().map_err( |_| ValueMessage::new( MessageKey::NotNumberWithUnit, &[ ( "units", self.suffixes() ) ] ) )?;
+        let result = number * factor;
+        match self.range {
+            Some( ( min, max ) ) if !( min..=max ).contains( &result ) => Err( ValueMessage::new( MessageKey::OutOfRange, &[ ( "min", min.to_string() ), ( "max", max.to_string() ) ] ) ),
+            _ => Ok( result ),
+        }
+    }
+
+    // e.g. "Hz/kHz"
+    pub fn suffixes( &self ) -> String {
+        self.units.iter().map( |( suffix, _ )| suffix.as_str() ).collect::<Vec<_>>().join( "/" )
+    }
+
+    // the note for the help e.g. "accepts Hz/kHz suffixes"
+    pub fn describe( &self ) -> String {
+        format!( "accepts {} suffixes", self.suffixes() )
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_table() {
+        let rate = UnitTable::new( &[ ( "Hz", 1.0 ), ( "kHz", 1000.0 ) ] ).with_range( 8000.0, 192000.0 );
+        assert_eq!( rate.parse( "44.1kHz" ), Ok( 44100.0 ) );
+        assert_eq!( rate.parse( "48000 Hz" ), Ok( 48000.0 ) );
+        assert_eq!( rate.parse( "96khz" ), Ok( 96000.0 ) );
+        assert_eq!( rate.parse( "22050" ), Ok( 22050.0 ) );
+        assert_eq!( rate.parse( "1Hz" ), Err( ValueMessage::new( MessageKey::OutOfRange, &[ ( "min", "8000".to_string() ), ( "max", "192000".to_string() ) ] ) ) );
+        assert_eq!( rate.parse( "fastHz" ), Err( ValueMessage::new( MessageKey::NotNumberWithUnit, &[ ( "units", "Hz/kHz".to_string() ) ] ) ) );
+        assert_eq!( rate.describe(), "accepts Hz/kHz suffixes" );
+
+        let gain = UnitTable::new( &[ ( "%", 0.01 ) ] );
+        assert_eq!( gain.parse( "50%" ), Ok( 0.5 ) );
+        assert_eq!( UnitTable::duration().parse( "1.5m" ), Ok( 90.0 ) );
+        assert_eq!( UnitTable::duration().parse( "250ms" ), Ok( 0.25 ) );
+        assert_eq!( UnitTable::size().parse( "4KiB" ), Ok( 4096.0 ) );
+        assert_eq!( UnitTable::size().parse( "2MB" ), Ok( 2e6 ) );
+    }
+}
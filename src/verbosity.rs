@@ -0,0 +1,130 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// the log level from the counts of -v and -q from error e.g. -v -v -v is debug and -v -v -v -v is trace.
+// with the log feature, the level is log::LevelFilter::from( level ) and init_logger( level ) prints the logs to stderr
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LevelFilter
+{
+    Off,
+    #[default]
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+const LEVELS : [LevelFilter; 6] = [ LevelFilter::Off, LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info, LevelFilter::Debug, LevelFilter::Trace ];
+
+impl LevelFilter
+{
+    // each -v raises and each -q lowers the level from the base
+    pub fn from_counts( base : LevelFilter, verbose : usize, quiet : usize ) -> Self {
+        let level = ( base as usize + verbose ).saturating_sub( quiet );
+        LEVELS[ level.min( LEVELS.len() - 1 ) ]
+    }
+
+    // the same names as log::LevelFilter
+    pub fn as_str( &self ) -> &'static str {
+        match self {
+            LevelFilter::Off => "OFF",
+            LevelFilter::Error => "ERROR",
+            LevelFilter::Warn => "WARN",
+            LevelFilter::Info => "INFO",
+            LevelFilter::Debug => "DEBUG",
+            LevelFilter::Trace => "TRACE",
+        }
+    }
+}
+
+impl core::fmt::Display for LevelFilter
+{
+    fn fmt( &self, f : &mut core::fmt::Formatter ) -> core::fmt::Result {
+        f.write_str( self.as_str() )
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LevelFilter> for log::LevelFilter
+{
+    fn from( level : LevelFilter ) -> Self {
+        match level {
+            LevelFilter::Off => log::LevelFilter::Off,
+            LevelFilter::Error => log::LevelFilter::Error,
+            LevelFilter::Warn => log::LevelFilter::Warn,
+            LevelFilter::Info => log::LevelFilter::Info,
+            LevelFilter::Debug => log::LevelFilter::Debug,
+            LevelFilter::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+// "WARN: message" per line to stderr
+#[cfg(feature = "log")]
+struct StderrLogger;
+
+#[cfg(feature = "log")]
+impl log::Log for StderrLogger
+{
+    fn enabled( &self, metadata : &log::Metadata ) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log( &self, record : &log::Record ) {
+        if self.enabled( record.metadata() ) {
+            eprintln!( "{}: {}", record.level(), record.args() );
+        }
+    }
+
+    fn flush( &self ) {}
+}
+
+// print the logs up to the level to stderr e.g. init_logger( opt_parse.get_verbosity() ). Err when the other logger is already set
+#[cfg(feature = "log")]
+pub fn init_logger( level : LevelFilter ) -> Result<(), log::SetLoggerError> {
+    log::set_logger( &StderrLogger )?;
+    log::set_max_level( level.into() );
+    Ok( () )
+}
+
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter_from_counts() {
+        assert_eq!( LevelFilter::from_counts( LevelFilter::Error, 0, 0 ), LevelFilter::Error );
+        assert_eq!( LevelFilter::from_counts( LevelFilter::Error, 3, 0 ), LevelFilter::Debug );
+        assert_eq!( LevelFilter::from_counts( LevelFilter::Error, 9, 0 ), LevelFilter::Trace );
+        assert_eq!( LevelFilter::from_counts( LevelFilter::Warn, 1, 3 ), LevelFilter::Off );
+        assert_eq!( LevelFilter::Trace.as_str(), "TRACE" );
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_level_filter_log() {
+        assert_eq!( log::LevelFilter::from( LevelFilter::from_counts( LevelFilter::Error, 3, 0 ) ), log::LevelFilter::Debug );
+        assert_eq!( log::LevelFilter::from( LevelFilter::Off ), log::LevelFilter::Off );
+        assert_eq!( init_logger( LevelFilter::Info ).is_ok(), true );
+        assert_eq!( log::max_level(), log::LevelFilter::Info );
+        assert_eq!( log::log_enabled!( log::Level::Debug ), false );
+        assert_eq!( init_logger( LevelFilter::Debug ).is_err(), true );
+    }
+}
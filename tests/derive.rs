@@ -0,0 +1,105 @@
+/*
+  Copyright (C) 2022 hidenorly
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+// #[main] owns fn main(), so this runs without the test harness (see Cargo.toml) and
+// runs itself again with the real args to check what the expanded main parsed
+#[cfg(feature = "derive")]
+use rst_opt_parse::{FromOptParse, Matches, OptParseError, OptParseItem};
+#[cfg(feature = "derive")]
+use std::process::Command;
+
+#[cfg(feature = "derive")]
+const CHILD_ENV : &str = "RST_OPT_PARSE_DERIVE_CHILD";
+
+#[cfg(feature = "derive")]
+rst_opt_parse::value_enum! {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Encoding {
+        Pcm8 = "PCM8",
+        Pcm16 = "PCM16",
+    }
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, PartialEq)]
+struct Options
+{
+    sampling_rate : u32,
+    encoding : Encoding,
+    verbose : bool,
+}
+
+#[cfg(feature = "derive")]
+impl FromOptParse for Options
+{
+    fn from_matches( matches : &Matches ) -> Result<Self, OptParseError> {
+        Ok( Options { sampling_rate : matches.get_value_as( "samplingRate" )?, encoding : matches.get_value_as( "encoding" )?, verbose : matches.get_value_as( "verbose" )? } )
+    }
+
+    fn options() -> Vec<OptParseItem> {
+        vec![
+            OptParseItem::new( "-s", "--samplingRate", true, "48000", "Set sampling rate e.g. 44100"),
+            OptParseItem::new( "-e", "--encoding", true, "PCM16", "Set Encoding").with_value_enum::<Encoding>(),
+            OptParseItem::new( "-v", "--verbose", false, "false", "Enable verbose mode"),
+        ]
+    }
+
+    fn description() -> String {
+        "rst_opt_parse_derive_test".to_string()
+    }
+}
+
+#[cfg(feature = "derive")]
+fn run_child( args : &[&str] ) -> std::process::Output {
+    Command::new( std::env::current_exe().unwrap() ).args( args ).env( CHILD_ENV, "1" ).output().unwrap()
+}
+
+#[cfg(feature = "derive")]
+#[allow(clippy::bool_assert_comparison)]
+#[rst_opt_parse::main]
+fn main( opts : Options ) {
+    if std::env::var_os( CHILD_ENV ).is_some() {
+        println!( "{:?}", opts );
+        return;
+    }
+
+    // the defaults
+    let output = run_child( &[] );
+    assert_eq!( output.status.code(), Some( 0 ) );
+    assert_eq!( String::from_utf8_lossy( &output.stdout ).trim(), "Options { sampling_rate: 48000, encoding: Pcm16, verbose: false }" );
+
+    // the real args
+    let output = run_child( &[ "-s", "44100", "--encoding=PCM8", "-v" ] );
+    assert_eq!( output.status.code(), Some( 0 ) );
+    assert_eq!( String::from_utf8_lossy( &output.stdout ).trim(), "Options { sampling_rate: 44100, encoding: Pcm8, verbose: true }" );
+
+    // the invalid args print the error with the help and exit with 2
+    let output = run_child( &[ "--samplingRate=fast" ] );
+    let stderr = String::from_utf8_lossy( &output.stderr );
+    assert_eq!( output.status.code(), Some( 2 ) );
+    assert_eq!( output.stdout.is_empty(), true );
+    assert_eq!( stderr.contains( "samplingRate" ), true );
+    assert_eq!( stderr.contains( "rst_opt_parse_derive_test" ), true );
+
+    let output = run_child( &[ "--encoding=PCM24" ] );
+    assert_eq!( output.status.code(), Some( 2 ) );
+    assert_eq!( String::from_utf8_lossy( &output.stderr ).contains( "PCM8, PCM16" ), true );
+
+    println!( "test derive ... ok" );
+}
+
+#[cfg(not(feature = "derive"))]
+fn main() {}